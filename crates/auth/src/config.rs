@@ -1,13 +1,46 @@
 use serde::Deserialize;
 
+/// The kind of OpenID Connect provider.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthProviderKind {
+    /// A Keycloak realm, whose issuer URL is derived from the address and realm.
+    #[default]
+    Keycloak,
+    /// Any other OpenID Connect provider, which is configured by its issuer URL.
+    Oidc,
+}
+
+/// How the public endpoint treats bearer tokens.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PublicAuthMode {
+    /// Bearer tokens are ignored and every request is anonymous.
+    #[default]
+    Anonymous,
+    /// Requests with a valid bearer token are served for the signed-in user, requests without one
+    /// are anonymous. Requests with an invalid bearer token are rejected.
+    Optional,
+}
+
 /// Authentication configuration.
 #[derive(Clone, Debug, Deserialize)]
 pub struct AuthConfig {
+    auth_provider: Option<AuthProviderKind>,
     auth_host: Option<String>,
     auth_port: Option<u16>,
     auth_address: Option<String>,
+    auth_issuer_url: Option<String>,
+    auth_required_issuer: Option<String>,
+    auth_audiences: Option<String>,
+    auth_leeway: Option<u64>,
+    auth_public_mode: Option<PublicAuthMode>,
+    auth_public_roles: Option<String>,
     auth_realm: Option<String>,
     auth_client_id: Option<String>,
+    auth_state_secret: Option<String>,
+    auth_jwks_file: Option<String>,
+    auth_public_key_file: Option<String>,
 }
 
 impl AuthConfig {
@@ -15,13 +48,22 @@ impl AuthConfig {
     const DEFAULT_PORT: u16 = 8080;
     const DEFAULT_REALM: &'static str = "QGT";
     const DEFAULT_CLIENT_ID: &'static str = "qgt";
+    const DEFAULT_LEEWAY: u64 = 60;
+    const DEFAULT_PUBLIC_ROLES: &'static str = "todo:write,tag:write";
 
     /// Get an auth config instance with values from env variables.
     pub fn from_env() -> envy::Result<Self> {
-        let mut cfg =
-            envy::from_env::<AuthConfig>().expect("auth config should be parsable from env");
+        Self::from_values(std::env::vars())
+    }
+
+    /// Get an auth config instance with the `values` of env variables by their names.
+    pub fn from_values(values: impl IntoIterator<Item = (String, String)>) -> envy::Result<Self> {
+        let mut cfg = envy::from_iter::<_, AuthConfig>(values)?;
 
         // Set defaults if not provided from environment
+        if cfg.auth_provider.is_none() {
+            cfg.auth_provider = Some(AuthProviderKind::default());
+        }
         if cfg.auth_host.is_none() {
             cfg.auth_host = Some(String::from(Self::DEFAULT_HOST));
         }
@@ -41,14 +83,79 @@ impl AuthConfig {
         if cfg.auth_client_id.is_none() {
             cfg.auth_client_id = Some(String::from(Self::DEFAULT_CLIENT_ID));
         }
+        if cfg.auth_leeway.is_none() {
+            cfg.auth_leeway = Some(Self::DEFAULT_LEEWAY);
+        }
+        if cfg.auth_public_mode.is_none() {
+            cfg.auth_public_mode = Some(PublicAuthMode::default());
+        }
+        if cfg.auth_public_roles.is_none() {
+            cfg.auth_public_roles = Some(String::from(Self::DEFAULT_PUBLIC_ROLES));
+        }
 
         Ok(cfg)
     }
 
+    pub fn provider(&self) -> AuthProviderKind {
+        self.auth_provider.unwrap()
+    }
+
     pub fn address(&self) -> &str {
         self.auth_address.as_deref().unwrap()
     }
 
+    /// Get the configured issuer URL of the provider.
+    ///
+    /// This is required for [AuthProviderKind::Oidc] and overrides the derived issuer URL of
+    /// [AuthProviderKind::Keycloak], e.g. to use `https`.
+    pub fn issuer_url(&self) -> Option<&str> {
+        self.auth_issuer_url.as_deref()
+    }
+
+    /// Get the issuer tokens must be issued by, if it differs from the issuer URL of the provider.
+    ///
+    /// This is needed if the provider is reached at a different URL than the one it puts into
+    /// tokens, e.g. an internal address.
+    pub fn required_issuer(&self) -> Option<&str> {
+        self.auth_required_issuer.as_deref()
+    }
+
+    /// Get the configured audiences of which access tokens must contain at least one.
+    ///
+    /// They are read as comma-separated list and replace the default audiences of the provider.
+    pub fn audiences(&self) -> Option<Vec<String>> {
+        self.auth_audiences.as_deref().map(|audiences| {
+            audiences
+                .split(',')
+                .map(str::trim)
+                .filter(|audience| !audience.is_empty())
+                .map(ToString::to_string)
+                .collect()
+        })
+    }
+
+    /// Get the leeway in seconds for the expiry of tokens, which allows for clock skew.
+    pub fn leeway(&self) -> u64 {
+        self.auth_leeway.unwrap()
+    }
+
+    /// Get how the public endpoint treats bearer tokens.
+    pub fn public_mode(&self) -> PublicAuthMode {
+        self.auth_public_mode.unwrap()
+    }
+
+    /// Get the roles of every request to the public endpoint, whether it is signed in or not.
+    ///
+    /// They are read as comma-separated list. The roles of a token add to them.
+    pub fn public_roles(&self) -> impl Iterator<Item = &str> {
+        self.auth_public_roles
+            .as_deref()
+            .unwrap()
+            .split(',')
+            .map(str::trim)
+            .filter(|role| !role.is_empty())
+    }
+
     pub fn realm(&self) -> &str {
         self.auth_realm.as_deref().unwrap()
     }
@@ -56,4 +163,20 @@ impl AuthConfig {
     pub fn client_id(&self) -> &str {
         self.auth_client_id.as_deref().unwrap()
     }
+
+    /// Get the path of the local JWKS file to validate tokens with, if one is configured.
+    pub fn jwks_file(&self) -> Option<&str> {
+        self.auth_jwks_file.as_deref()
+    }
+
+    /// Get the path of the local PEM file with the public key to validate tokens with, if one is
+    /// configured.
+    pub fn public_key_file(&self) -> Option<&str> {
+        self.auth_public_key_file.as_deref()
+    }
+
+    /// Get the secret to sign the log-in `state` with, if one is configured.
+    pub fn state_secret(&self) -> Option<&str> {
+        self.auth_state_secret.as_deref()
+    }
 }
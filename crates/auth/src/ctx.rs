@@ -1,34 +1,58 @@
 use crate::config::AuthConfig;
-use axum_keycloak_auth::instance::KeycloakAuthInstance;
-use axum_keycloak_auth::instance::KeycloakConfig;
-use axum_keycloak_auth::Url;
+use crate::discovery::Discovery;
+use crate::keys::StaticKeys;
+use crate::login::StateSigner;
+use crate::provider;
+use crate::token;
+use crate::token::AuthToken;
+use crate::token::TokenError;
+use crate::token::TokenPolicy;
 use std::sync::Arc;
-use std::sync::Mutex;
 
 #[derive(Clone)]
 pub struct AuthContext {
     config: AuthConfig,
-    instance: Arc<KeycloakAuthInstance>,
-    token: Arc<Mutex<Option<String>>>,
+    discovery: Arc<Discovery>,
+    static_keys: Option<StaticKeys>,
+    state_signer: StateSigner,
+    token_policy: TokenPolicy,
 }
 
 impl AuthContext {
-    pub fn new() -> anyhow::Result<Self> {
-        let config = AuthConfig::from_env()?;
-        let instance = KeycloakAuthInstance::new(
-            KeycloakConfig::builder()
-                .server(
-                    Url::parse(format!("http://{}", config.address()).as_str())
-                        .expect("url should be parsable"),
-                )
-                .realm(config.realm().to_string())
-                .build(),
+    pub fn new(config: AuthConfig) -> anyhow::Result<Self> {
+        let provider = provider::from_config(&config)?;
+        tracing::info!(
+            "Using {} provider with issuer {}",
+            provider.name(),
+            provider.issuer()
         );
+        let token_policy = TokenPolicy::new(&config, provider.as_ref());
+        tracing::info!(
+            "Accepting tokens of issuer {} for audiences {:?}",
+            token_policy.issuer(),
+            token_policy.audiences()
+        );
+        let discovery = Discovery::new(provider);
+        let static_keys = match (config.jwks_file(), config.public_key_file()) {
+            (Some(path), _) => Some(StaticKeys::from_jwks_file(path)?),
+            (None, Some(path)) => Some(StaticKeys::from_pem_file(path)?),
+            (None, None) => None,
+        };
+        if static_keys.is_some() {
+            tracing::info!("Validating tokens with the signing keys from a local file");
+        }
+
+        if config.state_secret().is_none() {
+            tracing::warn!("No AUTH_STATE_SECRET configured, using a random key to sign log-ins");
+        }
+        let state_signer = StateSigner::new(config.state_secret());
 
         Ok(Self {
             config,
-            instance: Arc::new(instance),
-            token: Arc::new(Mutex::new(None)),
+            discovery: Arc::new(discovery),
+            static_keys,
+            state_signer,
+            token_policy,
         })
     }
 
@@ -36,16 +60,49 @@ impl AuthContext {
         &self.config
     }
 
-    pub fn instance(&self) -> Arc<KeycloakAuthInstance> {
-        self.instance.clone()
+    pub fn discovery(&self) -> &Discovery {
+        &self.discovery
+    }
+
+    pub fn state_signer(&self) -> &StateSigner {
+        &self.state_signer
     }
 
-    pub fn token(&self) -> Option<String> {
-        self.token.lock().expect("token should be lockable").clone()
+    pub fn token_policy(&self) -> &TokenPolicy {
+        &self.token_policy
     }
 
-    pub fn set_token(&mut self, token: Option<String>) {
-        let mut guard = self.token.lock().expect("token should be lockable");
-        *guard = token;
+    /// Check that the signing keys to validate tokens with are available, returning their number.
+    pub async fn check_keys(&self) -> anyhow::Result<usize> {
+        match &self.static_keys {
+            Some(static_keys) => Ok(static_keys.len()),
+            None => self.discovery.check_keys().await,
+        }
+    }
+
+    /// Validate the bearer `token` of a request.
+    ///
+    /// The signing keys come from the local file, if one is configured, otherwise from the
+    /// provider.
+    pub async fn validate(&self, token: &str) -> Result<AuthToken, TokenError> {
+        let header = token::decode_header(token)?;
+        let key_id = header.kid.as_deref();
+        let key = match &self.static_keys {
+            Some(static_keys) => static_keys.find(key_id),
+            None => self
+                .discovery
+                .decoding_key(key_id)
+                .await
+                .map_err(|e| TokenError::Unavailable(e.to_string()))?,
+        }
+        .ok_or_else(|| TokenError::Invalid(String::from("Unknown signing key")))?;
+
+        token::validate(
+            self.discovery.provider(),
+            &self.token_policy,
+            token,
+            &header,
+            &key,
+        )
     }
 }
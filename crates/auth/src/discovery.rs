@@ -0,0 +1,260 @@
+//! OpenID Connect discovery of the [Provider] endpoints and signing keys.
+
+use crate::login::Pkce;
+use crate::provider::Provider;
+use anyhow::anyhow;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::DecodingKey;
+use reqwest::Url;
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+use tokio::sync::RwLock;
+
+/// The path of the discovery document relative to the issuer URL.
+const DISCOVERY_PATH: &str = "/.well-known/openid-configuration";
+
+/// The time after which cached signing keys are fetched again.
+const KEYS_MAX_AGE: Duration = Duration::from_secs(600);
+
+/// The minimal time between two fetches of the signing keys.
+///
+/// Tokens with an unknown key id trigger a fetch, since the provider may have rotated its keys.
+/// This limits how often invalid tokens can make us fetch the keys.
+const KEYS_MIN_REFRESH: Duration = Duration::from_secs(30);
+
+/// The metadata of a [Provider] from its discovery document.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+    end_session_endpoint: Option<String>,
+    revocation_endpoint: Option<String>,
+}
+
+impl ProviderMetadata {
+    pub fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
+    pub fn authorization_endpoint(&self) -> &str {
+        &self.authorization_endpoint
+    }
+
+    pub fn token_endpoint(&self) -> &str {
+        &self.token_endpoint
+    }
+
+    pub fn jwks_uri(&self) -> &str {
+        &self.jwks_uri
+    }
+
+    pub fn end_session_endpoint(&self) -> Option<&str> {
+        self.end_session_endpoint.as_deref()
+    }
+
+    pub fn revocation_endpoint(&self) -> Option<&str> {
+        self.revocation_endpoint.as_deref()
+    }
+
+    /// Get the URL of the authorization endpoint for a log-in with the authorization code flow.
+    ///
+    /// The provider redirects back to the `redirect_uri` with a code for the `state`, which can only
+    /// be exchanged with the verifier of the [Pkce].
+    pub fn authorization_url(
+        &self,
+        client_id: &str,
+        redirect_uri: &str,
+        state: &str,
+        pkce: &Pkce,
+    ) -> anyhow::Result<Url> {
+        Ok(Url::parse_with_params(
+            &self.authorization_endpoint,
+            &[
+                ("scope", "openid"),
+                ("response_type", "code"),
+                ("client_id", client_id),
+                ("redirect_uri", redirect_uri),
+                ("state", state),
+                ("code_challenge", &pkce.challenge()),
+                ("code_challenge_method", Pkce::METHOD),
+            ],
+        )?)
+    }
+
+    /// Get the URL of the end-session endpoint for an RP-initiated logout.
+    ///
+    /// The provider ends the session of the `id_token_hint` and redirects back to the
+    /// `post_logout_redirect_uri`, which must be registered for the client. Returns [None] if the
+    /// provider has no end-session endpoint.
+    pub fn end_session_url(
+        &self,
+        id_token_hint: &str,
+        client_id: &str,
+        post_logout_redirect_uri: &str,
+    ) -> anyhow::Result<Option<Url>> {
+        self.end_session_endpoint
+            .as_deref()
+            .map(|endpoint| {
+                Url::parse_with_params(
+                    endpoint,
+                    &[
+                        ("id_token_hint", id_token_hint),
+                        ("client_id", client_id),
+                        ("post_logout_redirect_uri", post_logout_redirect_uri),
+                    ],
+                )
+            })
+            .transpose()
+            .map_err(|e| e.into())
+    }
+}
+
+/// The signing keys of the [Provider] with the time they were fetched.
+struct CachedKeys {
+    keys: JwkSet,
+    fetched: Instant,
+}
+
+/// Discovers and caches the [ProviderMetadata] and signing keys of a [Provider].
+///
+/// Nothing is fetched before it is first needed, so the server can start while the provider is
+/// not reachable yet.
+pub struct Discovery {
+    provider: Arc<dyn Provider>,
+    client: reqwest::Client,
+    metadata: RwLock<Option<Arc<ProviderMetadata>>>,
+    keys: RwLock<Option<CachedKeys>>,
+}
+
+impl Discovery {
+    /// Construct a new [Discovery] for the [Provider].
+    pub fn new(provider: Arc<dyn Provider>) -> Self {
+        Self {
+            provider,
+            client: reqwest::Client::new(),
+            metadata: RwLock::new(None),
+            keys: RwLock::new(None),
+        }
+    }
+
+    /// Get the [Provider].
+    pub fn provider(&self) -> &dyn Provider {
+        self.provider.as_ref()
+    }
+
+    /// Get the [ProviderMetadata], which is fetched on first use.
+    pub async fn metadata(&self) -> anyhow::Result<Arc<ProviderMetadata>> {
+        if let Some(metadata) = self.metadata.read().await.as_ref() {
+            return Ok(metadata.clone());
+        }
+
+        let mut cached = self.metadata.write().await;
+        if let Some(metadata) = cached.as_ref() {
+            return Ok(metadata.clone());
+        }
+        let url = format!("{}{DISCOVERY_PATH}", self.provider.issuer());
+        tracing::info!("Discovering {} provider at {url}", self.provider.name());
+        let metadata: ProviderMetadata = self
+            .client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        // The issuer must match exactly, otherwise the tokens would fail validation later on
+        if metadata.issuer != self.provider.issuer() {
+            return Err(anyhow!(
+                "The discovered issuer {} does not match the configured issuer {}",
+                metadata.issuer,
+                self.provider.issuer()
+            ));
+        }
+
+        let metadata = Arc::new(metadata);
+        *cached = Some(metadata.clone());
+        Ok(metadata)
+    }
+
+    /// Get the signing key with the `key_id`.
+    ///
+    /// Without a `key_id`, the provider must have exactly one key. The keys are fetched again if
+    /// they are outdated or the key is unknown. Returns [None] if there is no such key.
+    pub async fn decoding_key(&self, key_id: Option<&str>) -> anyhow::Result<Option<DecodingKey>> {
+        if let Some(cached) = self.keys.read().await.as_ref() {
+            if cached.fetched.elapsed() < KEYS_MAX_AGE {
+                if let Some(key) = find_key(&cached.keys, key_id)? {
+                    return Ok(Some(key));
+                }
+                if cached.fetched.elapsed() < KEYS_MIN_REFRESH {
+                    return Ok(None);
+                }
+            }
+        }
+
+        let mut cached = self.keys.write().await;
+        // Another request may have fetched the keys in the meantime
+        if let Some(cached) = cached.as_ref() {
+            if cached.fetched.elapsed() < KEYS_MIN_REFRESH {
+                return find_key(&cached.keys, key_id);
+            }
+        }
+        let keys = self.fetch_keys().await?;
+        let key = find_key(&keys, key_id)?;
+        *cached = Some(CachedKeys {
+            keys,
+            fetched: Instant::now(),
+        });
+        Ok(key)
+    }
+
+    /// Check that the signing keys are available, returning their number.
+    ///
+    /// The keys are only fetched if they are outdated.
+    pub async fn check_keys(&self) -> anyhow::Result<usize> {
+        if let Some(cached) = self.keys.read().await.as_ref() {
+            if cached.fetched.elapsed() < KEYS_MAX_AGE {
+                return Ok(cached.keys.keys.len());
+            }
+        }
+
+        let mut cached = self.keys.write().await;
+        let keys = self.fetch_keys().await?;
+        let count = keys.keys.len();
+        *cached = Some(CachedKeys {
+            keys,
+            fetched: Instant::now(),
+        });
+        Ok(count)
+    }
+
+    /// Fetch the signing keys from the JWKS endpoint of the provider.
+    async fn fetch_keys(&self) -> anyhow::Result<JwkSet> {
+        let metadata = self.metadata().await?;
+        tracing::debug!("Fetching signing keys from {}", metadata.jwks_uri());
+        Ok(self
+            .client
+            .get(metadata.jwks_uri())
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+}
+
+/// Find the key with the `key_id` in the [JwkSet].
+fn find_key(keys: &JwkSet, key_id: Option<&str>) -> anyhow::Result<Option<DecodingKey>> {
+    let jwk = match key_id {
+        Some(key_id) => keys.find(key_id),
+        None if keys.keys.len() == 1 => keys.keys.first(),
+        None => None,
+    };
+    jwk.map(DecodingKey::from_jwk)
+        .transpose()
+        .map_err(|e| e.into())
+}
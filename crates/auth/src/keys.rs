@@ -0,0 +1,81 @@
+//! Signing keys loaded from a local file instead of the provider's JWKS endpoint.
+//!
+//! With [StaticKeys], tokens are validated without any request to the provider, which allows to
+//! run the secured endpoint in tests and air-gapped setups.
+
+use anyhow::anyhow;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::DecodingKey;
+use std::path::Path;
+
+/// A signing key with its optional key id.
+#[derive(Clone)]
+struct StaticKey {
+    id: Option<String>,
+    key: DecodingKey,
+}
+
+/// Signing keys from a local JWKS or PEM file.
+#[derive(Clone)]
+pub struct StaticKeys {
+    keys: Vec<StaticKey>,
+}
+
+impl StaticKeys {
+    /// Load the keys from the JWKS file at `path`.
+    pub fn from_jwks_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let jwks: JwkSet = serde_json::from_slice(&std::fs::read(path)?)?;
+        let keys = jwks
+            .keys
+            .iter()
+            .map(|jwk| {
+                Ok(StaticKey {
+                    id: jwk.common.key_id.clone(),
+                    key: DecodingKey::from_jwk(jwk)?,
+                })
+            })
+            .collect::<anyhow::Result<Vec<StaticKey>>>()?;
+
+        Ok(Self { keys })
+    }
+
+    /// Load the public key from the PEM file at `path`.
+    ///
+    /// RSA, EC and Ed25519 keys are supported. The key matches tokens with any key id.
+    pub fn from_pem_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let pem = std::fs::read(path)?;
+        let key = DecodingKey::from_rsa_pem(&pem)
+            .or_else(|_| DecodingKey::from_ec_pem(&pem))
+            .or_else(|_| DecodingKey::from_ed_pem(&pem))
+            .map_err(|_| anyhow!("The PEM file should contain an RSA, EC or Ed25519 public key"))?;
+
+        Ok(Self {
+            keys: vec![StaticKey { id: None, key }],
+        })
+    }
+
+    /// Get the number of keys.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Check if there are no keys.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Get the key with the `key_id`.
+    ///
+    /// Keys without id match any `key_id`. Without a `key_id`, there must be exactly one key.
+    pub fn find(&self, key_id: Option<&str>) -> Option<DecodingKey> {
+        match key_id {
+            Some(key_id) => self
+                .keys
+                .iter()
+                .find(|key| key.id.as_deref().is_none_or(|id| id == key_id)),
+            None if self.keys.len() == 1 => self.keys.first(),
+            None => None,
+        }
+        .map(|key| key.key.clone())
+    }
+}
@@ -1,17 +1,9 @@
-use std::sync::Arc;
-
-use axum_keycloak_auth::{
-    instance::KeycloakAuthInstance, layer::KeycloakAuthLayer, PassthroughMode,
-};
-
 pub mod config;
 pub mod ctx;
+pub mod discovery;
+pub mod keys;
+pub mod login;
+pub mod provider;
+pub mod token;
 
-pub fn keycloak_auth_layer(instance: Arc<KeycloakAuthInstance>) -> KeycloakAuthLayer<String> {
-    KeycloakAuthLayer::<String>::builder()
-        .instance(instance)
-        .passthrough_mode(PassthroughMode::Block)
-        .persist_raw_claims(false)
-        .expected_audiences(vec![String::from("account")])
-        .build()
-}
+pub use token::AuthToken;
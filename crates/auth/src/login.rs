@@ -0,0 +1,132 @@
+//! Protection of the authorization code flow against CSRF and code injection.
+//!
+//! Every log-in gets a [Pkce] code verifier, which stays in the browser, and a `state` signed by
+//! the [StateSigner]. The signature covers the code verifier, so a `state` is only accepted from
+//! the browser that started the log-in. The `state` also carries the path to return to after the
+//! log-in.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hmac::Hmac;
+use hmac::Mac;
+use rand::RngCore;
+use sha2::Digest;
+use sha2::Sha256;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+/// The byte length of the random code verifiers and signing keys.
+const RANDOM_LENGTH: usize = 32;
+
+/// The time a log-in can take until its `state` is rejected.
+pub const LOGIN_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Get `length` random bytes.
+fn random_bytes(length: usize) -> Vec<u8> {
+    let mut bytes = vec![0_u8; length];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes
+}
+
+/// A PKCE code verifier with its `S256` code challenge.
+#[derive(Clone, Debug)]
+pub struct Pkce {
+    verifier: String,
+}
+
+impl Pkce {
+    /// The code challenge method of [Pkce::challenge].
+    pub const METHOD: &'static str = "S256";
+
+    /// Construct a new [Pkce] with a random code verifier.
+    pub fn new() -> Self {
+        Self {
+            verifier: URL_SAFE_NO_PAD.encode(random_bytes(RANDOM_LENGTH)),
+        }
+    }
+
+    /// Get the code verifier.
+    pub fn verifier(&self) -> &str {
+        &self.verifier
+    }
+
+    /// Get the code challenge of the code verifier.
+    pub fn challenge(&self) -> String {
+        URL_SAFE_NO_PAD.encode(Sha256::digest(self.verifier.as_bytes()))
+    }
+}
+
+impl Default for Pkce {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Signs and verifies the `state` of the authorization code flow.
+#[derive(Clone)]
+pub struct StateSigner {
+    key: Vec<u8>,
+}
+
+impl StateSigner {
+    /// Construct a new [StateSigner] with the `secret` or a random key, if there is none.
+    ///
+    /// With a random key, log-ins started before a restart can not be completed.
+    pub fn new(secret: Option<&str>) -> Self {
+        Self {
+            key: secret.map_or_else(|| random_bytes(RANDOM_LENGTH), |s| s.as_bytes().to_vec()),
+        }
+    }
+
+    /// Sign a `state` carrying the `return_path` for the log-in with the code `verifier`.
+    ///
+    /// Returns [None] if the `return_path` is not a local path.
+    pub fn sign(&self, return_path: &str, verifier: &str) -> Option<String> {
+        if !is_local_path(return_path) {
+            return None;
+        }
+        let expires = (SystemTime::now() + LOGIN_TIMEOUT)
+            .duration_since(UNIX_EPOCH)
+            .ok()?
+            .as_secs();
+        let payload = format!("{expires}|{return_path}");
+        let signature = self.mac(&payload, verifier).finalize().into_bytes();
+
+        Some(format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(payload),
+            URL_SAFE_NO_PAD.encode(signature)
+        ))
+    }
+
+    /// Verify the `state` for the log-in with the code `verifier` and get its return path.
+    ///
+    /// Returns [None] if the `state` is malformed, expired or was not signed for the `verifier`.
+    pub fn verify(&self, state: &str, verifier: &str) -> Option<String> {
+        let (payload, signature) = state.split_once('.')?;
+        let payload = String::from_utf8(URL_SAFE_NO_PAD.decode(payload).ok()?).ok()?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+        self.mac(&payload, verifier).verify_slice(&signature).ok()?;
+
+        let (expires, return_path) = payload.split_once('|')?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+        (expires.parse::<u64>().ok()? >= now && is_local_path(return_path))
+            .then(|| return_path.to_string())
+    }
+
+    /// Get the MAC over the `payload` and the code `verifier`.
+    fn mac(&self, payload: &str, verifier: &str) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC should accept any key length");
+        mac.update(payload.as_bytes());
+        mac.update(b"|");
+        mac.update(verifier.as_bytes());
+        mac
+    }
+}
+
+/// Check if the `path` is a path on this server, so redirecting to it is not an open redirect.
+fn is_local_path(path: &str) -> bool {
+    path.starts_with('/') && !path.starts_with("//") && !path.contains('\\')
+}
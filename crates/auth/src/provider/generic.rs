@@ -0,0 +1,52 @@
+//! The [Provider] implementation for any OpenID Connect provider, like Dex.
+
+use super::claim_strings;
+use super::Provider;
+use crate::config::AuthConfig;
+use anyhow::anyhow;
+use serde_json::Map;
+use serde_json::Value;
+
+/// An OpenID Connect provider, which is only known by its issuer URL.
+pub struct GenericOidc {
+    issuer: String,
+    audiences: Vec<String>,
+}
+
+impl GenericOidc {
+    /// Construct a new [GenericOidc] provider for the issuer URL of the [AuthConfig].
+    ///
+    /// Access tokens must be issued for the configured client.
+    pub fn new(config: &AuthConfig) -> anyhow::Result<Self> {
+        let issuer = config
+            .issuer_url()
+            .ok_or(anyhow!("AUTH_ISSUER_URL is required for the oidc provider"))?;
+
+        Ok(Self {
+            issuer: issuer.trim_end_matches('/').to_string(),
+            audiences: vec![config.client_id().to_string()],
+        })
+    }
+}
+
+impl Provider for GenericOidc {
+    fn name(&self) -> &'static str {
+        "oidc"
+    }
+
+    fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
+    fn audiences(&self) -> &[String] {
+        &self.audiences
+    }
+
+    /// Get the roles from the `roles` claim, which many providers use, and the `groups` claim,
+    /// which is used by e.g. Dex.
+    fn roles(&self, claims: &Map<String, Value>) -> Vec<String> {
+        let mut roles = claim_strings(claims, &["roles"]);
+        roles.extend(claim_strings(claims, &["groups"]));
+        roles
+    }
+}
@@ -0,0 +1,59 @@
+//! The [Provider] implementation for a Keycloak realm.
+
+use super::claim_strings;
+use super::Provider;
+use crate::config::AuthConfig;
+use serde_json::Map;
+use serde_json::Value;
+
+/// The audience Keycloak sets in access tokens by default.
+const ACCOUNT_AUDIENCE: &str = "account";
+
+/// A Keycloak realm.
+pub struct Keycloak {
+    issuer: String,
+    audiences: Vec<String>,
+    client_id: String,
+}
+
+impl Keycloak {
+    /// Construct a new [Keycloak] provider for the realm of the [AuthConfig].
+    ///
+    /// Without a configured issuer URL, the realm is reached over `http` at the configured address.
+    pub fn new(config: &AuthConfig) -> Self {
+        let issuer = config.issuer_url().map_or_else(
+            || format!("http://{}/realms/{}", config.address(), config.realm()),
+            |issuer| issuer.trim_end_matches('/').to_string(),
+        );
+
+        Self {
+            issuer,
+            audiences: vec![String::from(ACCOUNT_AUDIENCE)],
+            client_id: config.client_id().to_string(),
+        }
+    }
+}
+
+impl Provider for Keycloak {
+    fn name(&self) -> &'static str {
+        "keycloak"
+    }
+
+    fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
+    fn audiences(&self) -> &[String] {
+        &self.audiences
+    }
+
+    /// Get the realm roles and the client roles of the configured client.
+    fn roles(&self, claims: &Map<String, Value>) -> Vec<String> {
+        let mut roles = claim_strings(claims, &["realm_access", "roles"]);
+        roles.extend(claim_strings(
+            claims,
+            &["resource_access", &self.client_id, "roles"],
+        ));
+        roles
+    }
+}
@@ -0,0 +1,58 @@
+//! The OpenID Connect providers, which issue the tokens accepted by the secured endpoint.
+//!
+//! A [Provider] only knows its issuer URL and what it puts into tokens. Everything else, like the
+//! endpoints and the signing keys, is read from the
+//! [discovery document](crate::discovery::ProviderMetadata) of the issuer.
+
+use crate::config::AuthConfig;
+use crate::config::AuthProviderKind;
+use serde_json::Map;
+use serde_json::Value;
+use std::sync::Arc;
+
+pub mod generic;
+pub mod keycloak;
+
+/// An OpenID Connect provider.
+pub trait Provider: Send + Sync {
+    /// Get the name for log messages.
+    fn name(&self) -> &'static str;
+
+    /// Get the issuer URL, which is also the base URL of the discovery document.
+    fn issuer(&self) -> &str;
+
+    /// Get the audiences of which access tokens must contain at least one.
+    fn audiences(&self) -> &[String];
+
+    /// Get the roles granted by the `claims` of an access token.
+    fn roles(&self, claims: &Map<String, Value>) -> Vec<String>;
+}
+
+/// Get the strings of the array at the `path` of keys in the `claims`.
+///
+/// Returns an empty list if there is no such array.
+pub(crate) fn claim_strings(claims: &Map<String, Value>, path: &[&str]) -> Vec<String> {
+    let Some((first, rest)) = path.split_first() else {
+        return vec![];
+    };
+    claims
+        .get(*first)
+        .and_then(|claim| rest.iter().try_fold(claim, |claim, key| claim.get(key)))
+        .and_then(Value::as_array)
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(Value::as_str)
+                .map(ToString::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Get the [Provider] of the [AuthConfig].
+pub fn from_config(config: &AuthConfig) -> anyhow::Result<Arc<dyn Provider>> {
+    Ok(match config.provider() {
+        AuthProviderKind::Keycloak => Arc::new(keycloak::Keycloak::new(config)),
+        AuthProviderKind::Oidc => Arc::new(generic::GenericOidc::new(config)?),
+    })
+}
@@ -0,0 +1,161 @@
+//! Validation of the bearer tokens of requests to the secured endpoint.
+
+use crate::config::AuthConfig;
+use crate::provider::Provider;
+use axum::http::header::AUTHORIZATION;
+use axum::http::HeaderMap;
+use axum::http::StatusCode;
+use jsonwebtoken::Algorithm;
+use jsonwebtoken::DecodingKey;
+use jsonwebtoken::Header;
+use jsonwebtoken::Validation;
+use serde::Deserialize;
+use serde_json::Map;
+use serde_json::Value;
+
+/// The signature algorithms accepted for access tokens.
+///
+/// Only asymmetric algorithms are accepted, since the keys come from the provider's public JWKS.
+const ALGORITHMS: [Algorithm; 7] = [
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::ES256,
+    Algorithm::ES384,
+    Algorithm::EdDSA,
+];
+
+/// The validated token, which is added to the extensions of authorized requests.
+#[derive(Clone, Debug, Deserialize)]
+pub struct AuthToken {
+    /// The subject, which identifies the user.
+    #[serde(rename = "sub")]
+    pub subject: String,
+    /// The expiry as seconds since the Unix epoch.
+    #[serde(rename = "exp")]
+    pub expires: u64,
+    /// The roles granted by the claims, as read by the [Provider].
+    #[serde(skip)]
+    pub roles: Vec<String>,
+    /// All other claims.
+    #[serde(flatten)]
+    pub claims: Map<String, Value>,
+}
+
+/// The claims access tokens must have to be accepted.
+#[derive(Clone, Debug)]
+pub struct TokenPolicy {
+    issuer: String,
+    audiences: Vec<String>,
+    leeway: u64,
+}
+
+impl TokenPolicy {
+    /// Construct a new [TokenPolicy] from the [AuthConfig].
+    ///
+    /// Without a configured required issuer or audiences, the ones of the [Provider] are used.
+    pub fn new(config: &AuthConfig, provider: &dyn Provider) -> Self {
+        Self {
+            issuer: config
+                .required_issuer()
+                .unwrap_or(provider.issuer())
+                .to_string(),
+            audiences: config
+                .audiences()
+                .unwrap_or_else(|| provider.audiences().to_vec()),
+            leeway: config.leeway(),
+        }
+    }
+
+    /// Get the issuer of accepted tokens.
+    pub fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
+    /// Get the audiences of which accepted tokens must contain at least one.
+    pub fn audiences(&self) -> &[String] {
+        &self.audiences
+    }
+
+    /// Get the leeway in seconds for the expiry of tokens.
+    pub fn leeway(&self) -> u64 {
+        self.leeway
+    }
+}
+
+/// The reason a request was not authorized.
+#[derive(Debug)]
+pub enum TokenError {
+    /// The request has no bearer token.
+    Missing,
+    /// The token is malformed, expired or has an invalid signature or claims.
+    Invalid(String),
+    /// The token could not be validated, since the provider is not reachable.
+    Unavailable(String),
+}
+
+impl TokenError {
+    /// Get the [StatusCode] of the response to an unauthorized request.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            TokenError::Missing | TokenError::Invalid(_) => StatusCode::UNAUTHORIZED,
+            TokenError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    /// Get the message of the response to an unauthorized request.
+    pub fn message(&self) -> &str {
+        match self {
+            TokenError::Missing => "Missing bearer token",
+            TokenError::Invalid(_) => "Invalid bearer token",
+            TokenError::Unavailable(_) => "Authentication provider unavailable",
+        }
+    }
+}
+
+/// Get the bearer token from the [AUTHORIZATION] header.
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+}
+
+/// Decode the header of the `token` and check that it uses one of the [ALGORITHMS].
+pub(crate) fn decode_header(token: &str) -> Result<Header, TokenError> {
+    let header =
+        jsonwebtoken::decode_header(token).map_err(|e| TokenError::Invalid(e.to_string()))?;
+    if !ALGORITHMS.contains(&header.alg) {
+        return Err(TokenError::Invalid(format!(
+            "Unsupported algorithm {:?}",
+            header.alg
+        )));
+    }
+    Ok(header)
+}
+
+/// Validate the `token` with the `key` against the [TokenPolicy].
+///
+/// The roles of the token are read by the [Provider].
+pub(crate) fn validate(
+    provider: &dyn Provider,
+    policy: &TokenPolicy,
+    token: &str,
+    header: &Header,
+    key: &DecodingKey,
+) -> Result<AuthToken, TokenError> {
+    let mut validation = Validation::new(header.alg);
+    validation.set_issuer(&[policy.issuer()]);
+    validation.set_audience(policy.audiences());
+    validation.leeway = policy.leeway();
+    validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+    let mut token = jsonwebtoken::decode::<AuthToken>(token, key, &validation)
+        .map(|data| data.claims)
+        .map_err(|e| TokenError::Invalid(e.to_string()))?;
+    token.roles = provider.roles(&token.claims);
+    Ok(token)
+}
@@ -0,0 +1,190 @@
+//! Personal access tokens for scripts and CI, which can not log in interactively.
+//!
+//! A signed-in user creates a [PersonalAccessToken] with a subset of the own permissions as scopes.
+//! Only the SHA-256 hash of the token is stored, the token itself is only returned on creation.
+
+use crate::db::collections::PERSONAL_ACCESS_TOKENS;
+use crate::permission::Permission;
+use crate::permission::Permissions;
+use crate::service::get_many_by_filter_and_sort;
+use async_graphql::InputObject;
+use async_graphql::SimpleObject;
+use bson::doc;
+use bson::oid::ObjectId;
+use bson::DateTime;
+use qm::mongodb::options::ReturnDocument;
+use rand::RngCore;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+
+/// The prefix of all personal access tokens, which tells them apart from JWTs.
+pub const TOKEN_PREFIX: &str = "qgt_pat_";
+
+/// The byte length of the random part of the tokens.
+const TOKEN_LENGTH: usize = 32;
+
+/// Database representation of a personal access token.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct PersonalAccessToken {
+    created: DateTime,
+    /// The expiry, if the token expires.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires: Option<DateTime>,
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    id: Option<ObjectId>,
+    /// The last time the token was used.
+    last_used: Option<DateTime>,
+    name: String,
+    /// The subject of the user, who created the token.
+    owner: String,
+    /// The permissions granted to requests with the token.
+    scopes: Vec<Permission>,
+    #[graphql(skip)]
+    token_hash: String,
+}
+
+impl PersonalAccessToken {
+    /// Get the subject of the owner.
+    pub fn owner_subject(&self) -> &str {
+        &self.owner
+    }
+
+    /// Get the [Permissions] of the scopes.
+    pub fn permissions(&self) -> Permissions {
+        Permissions::from_roles(self.scopes.iter().map(Permission::role))
+    }
+
+    /// Get the expiry as seconds since the Unix epoch, if the token expires.
+    pub fn expires_timestamp(&self) -> Option<i64> {
+        self.expires
+            .map(|expires| expires.timestamp_millis() / 1000)
+    }
+}
+
+/// The marker of requests, which are authenticated with a personal access token.
+///
+/// Such requests can not create personal access tokens, since those could outlive the token of
+/// the request.
+#[derive(Clone, Copy, Debug)]
+pub struct PersonalAccessTokenAuth;
+
+/// The GraphQL input for creating a personal access token.
+#[derive(Debug, InputObject)]
+pub(crate) struct CreatePersonalAccessTokenInput {
+    /// A name to recognize the token by.
+    pub name: String,
+    /// The permissions granted to requests with the token.
+    pub scopes: Vec<Permission>,
+    /// The expiry, or [None] for a token that does not expire.
+    pub expires: Option<DateTime>,
+}
+
+/// A created personal access token with the token itself, which is only returned once.
+#[derive(Debug, SimpleObject)]
+pub(crate) struct CreatedPersonalAccessToken {
+    /// The token to send as bearer token.
+    token: String,
+    personal_access_token: PersonalAccessToken,
+}
+
+/// Get the hash of the `token`, which is stored instead of the token itself.
+///
+/// The tokens are random, so a fast hash does not make them easier to guess.
+fn hash(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// The store for [PersonalAccessTokens](PersonalAccessToken) in the database.
+///
+/// Expired tokens are removed by the database.
+#[derive(Clone)]
+pub struct AccessTokenStore {
+    db: crate::db::DB,
+}
+
+impl AccessTokenStore {
+    /// Construct a new [AccessTokenStore].
+    pub(crate) fn new(db: crate::db::DB) -> Self {
+        Self { db }
+    }
+
+    /// Create a new [PersonalAccessToken] for the `owner` from the `input`.
+    pub(crate) async fn create(
+        &self,
+        owner: String,
+        input: CreatePersonalAccessTokenInput,
+    ) -> anyhow::Result<CreatedPersonalAccessToken> {
+        let mut random = [0_u8; TOKEN_LENGTH];
+        rand::thread_rng().fill_bytes(&mut random);
+        let token = format!("{TOKEN_PREFIX}{}", hex::encode(random));
+        let mut personal_access_token = PersonalAccessToken {
+            created: DateTime::now(),
+            expires: input.expires,
+            id: None,
+            last_used: None,
+            name: input.name,
+            owner,
+            scopes: input.scopes,
+            token_hash: hash(&token),
+        };
+        let result = self
+            .db
+            .get()
+            .collection::<PersonalAccessToken>(PERSONAL_ACCESS_TOKENS)
+            .insert_one(&personal_access_token)
+            .await?;
+        personal_access_token.id = result.inserted_id.as_object_id();
+
+        Ok(CreatedPersonalAccessToken {
+            token,
+            personal_access_token,
+        })
+    }
+
+    /// Get all [PersonalAccessTokens](PersonalAccessToken) of the `owner`, ordered by creation.
+    pub(crate) async fn list(&self, owner: &str) -> anyhow::Result<Vec<PersonalAccessToken>> {
+        get_many_by_filter_and_sort(
+            &self.db.get(),
+            PERSONAL_ACCESS_TOKENS,
+            doc! { "owner": owner },
+            doc! { "_id": 1 },
+        )
+        .await
+    }
+
+    /// Revoke the [PersonalAccessToken] with the `id` of the `owner`.
+    ///
+    /// Returns `false` if the `owner` has no such token.
+    pub(crate) async fn revoke(&self, owner: &str, id: &ObjectId) -> anyhow::Result<bool> {
+        let result = self
+            .db
+            .get()
+            .collection::<PersonalAccessToken>(PERSONAL_ACCESS_TOKENS)
+            .delete_one(doc! { "_id": id, "owner": owner })
+            .await?;
+
+        Ok(result.deleted_count > 0)
+    }
+
+    /// Get the [PersonalAccessToken] of the `token`, if it exists and is not expired.
+    ///
+    /// Marks the token as used.
+    pub async fn verify(&self, token: &str) -> anyhow::Result<Option<PersonalAccessToken>> {
+        let now = DateTime::now();
+        Ok(self
+            .db
+            .get()
+            .collection::<PersonalAccessToken>(PERSONAL_ACCESS_TOKENS)
+            .find_one_and_update(
+                doc! {
+                    "token_hash": hash(token),
+                    "$or": [{ "expires": null }, { "expires": { "$gt": now } }],
+                },
+                doc! { "$set": { "last_used": now } },
+            )
+            .return_document(ReturnDocument::After)
+            .await?)
+    }
+}
@@ -3,18 +3,45 @@
 //! The main app container which provides global data structures and initialization functionality.
 //!
 //! # Examples
-//! ```rust
-//! let app = qgt_domain::app::App::new().await?;
+//! ```rust,no_run
+//! # async fn run() -> anyhow::Result<()> {
+//! let config = qgt_domain::config::Config::load(None)?;
+//! let app = qgt_domain::app::App::new(config).await?;
+//! # Ok(())
+//! # }
 //! ```
 
+use crate::access_token::AccessTokenStore;
+use crate::config::Config;
+use crate::config::FeatureConfig;
+use crate::config::ServerConfig;
+use crate::db::connect;
 use crate::db::setup_database;
+use crate::event::change_stream;
+use crate::event::EventBus;
+use crate::event::EventSource;
+use crate::session::SessionStore;
 use qgt_auth::ctx::AuthContext;
 use std::sync::Arc;
+use tokio::task::JoinHandle;
 
 struct AppInner {
+    access_tokens: AccessTokenStore,
     auth_ctx: AuthContext,
-    db: qm::mongodb::DB,
-    server_config: qm::server::ServerConfig,
+    db: crate::db::DB,
+    events: EventBus,
+    event_watcher: Option<JoinHandle<()>>,
+    features: FeatureConfig,
+    server_config: ServerConfig,
+    sessions: SessionStore,
+}
+
+impl Drop for AppInner {
+    fn drop(&mut self) {
+        if let Some(event_watcher) = &self.event_watcher {
+            event_watcher.abort();
+        }
+    }
 }
 
 /// The app state.
@@ -24,46 +51,99 @@ pub struct App {
 }
 
 impl App {
-    /// Construct a new [App].
+    /// Construct a new [App] with the [Config].
     ///
     /// Will initialize
-    /// - [qm::server::ServerConfig]
-    /// - [qm::mongodb::DB]
+    /// - [crate::db::DB]
     /// - [qgt_auth::ctx::AuthContext]
-    pub async fn new() -> anyhow::Result<Self> {
-        // Uses defaults from the qm server crate.
-        // Can be configured with environment variables with prefix 'SERVER_'.
-        let server_config = qm::server::ServerConfig::new()?;
-
-        // Uses defaults from the qm mongodb crate.
-        // Can be configured with environment variables with prefix 'MONGODB_'.
-        let db =
-            qm::mongodb::DB::new(server_config.app_name(), &qm::mongodb::DbConfig::new()?).await?;
+    /// - [SessionStore]
+    /// - [AccessTokenStore]
+    /// - [EventBus], which reads the events from MongoDB change streams if they are supported
+    pub async fn new(config: Config) -> anyhow::Result<Self> {
+        let Config {
+            server: server_config,
+            mongodb: db_config,
+            auth: auth_config,
+            features,
+            ..
+        } = config;
+
+        let db = connect(server_config.app_name(), &db_config).await?;
         // Set up the MongoDB for qgt
         setup_database(&db).await?;
 
+        // Set up the domain events
+        let events = EventBus::new(
+            if features.change_streams() && change_stream::is_supported(&db).await? {
+                EventSource::ChangeStream
+            } else {
+                EventSource::InProcess
+            },
+        );
+        tracing::info!("Using domain event source {:?}", events.source());
+        if events.source() == EventSource::ChangeStream {
+            change_stream::enable_pre_images(&db).await;
+        }
+        let event_watcher = (events.source() == EventSource::ChangeStream).then(|| {
+            change_stream::spawn(
+                db.clone(),
+                events.clone(),
+                server_config.app_name().to_string(),
+            )
+        });
+
+        // Set up the sessions of the secured endpoint
+        let sessions = SessionStore::new(db.clone());
+        // Set up the personal access tokens
+        let access_tokens = AccessTokenStore::new(db.clone());
+
         // Set up the auth context
-        let auth_ctx = AuthContext::new()?;
+        let auth_ctx = AuthContext::new(auth_config)?;
 
         Ok(Self {
             inner: Arc::new(AppInner {
+                access_tokens,
                 auth_ctx,
                 db,
+                events,
+                event_watcher,
+                features,
+                sessions,
                 server_config,
             }),
         })
     }
 
     /// Get the server configuration.
-    pub fn server_config(&self) -> &qm::server::ServerConfig {
+    pub fn server_config(&self) -> &ServerConfig {
         &self.inner.server_config
     }
 
+    /// Get the feature toggles.
+    pub fn features(&self) -> &FeatureConfig {
+        &self.inner.features
+    }
+
     /// Get the database.
-    pub fn db(&self) -> &qm::mongodb::DB {
+    pub fn db(&self) -> &crate::db::DB {
         &self.inner.db
     }
 
+    /// Get the [EventBus] for domain events.
+    pub(crate) fn events(&self) -> &EventBus {
+        &self.inner.events
+    }
+
+    /// Get the [SessionStore].
+    pub fn sessions(&self) -> &SessionStore {
+        &self.inner.sessions
+    }
+
+    /// Get the [AccessTokenStore].
+    pub fn access_tokens(&self) -> &AccessTokenStore {
+        &self.inner.access_tokens
+    }
+
     /// Get the [AuthContext].
     pub fn auth_ctx(&self) -> AuthContext {
         self.inner.auth_ctx.clone()
@@ -0,0 +1,499 @@
+//! The configuration of the application.
+//!
+//! The configuration is read from environment variables and an optional TOML file. The keys of the
+//! file are the environment variables without their prefix, grouped in a table per prefix, e.g.
+//! `SERVER_PORT` is `port` in the `[server]` table. Environment variables take precedence over the
+//! file.
+//!
+//! All values are validated before the app is set up, and every invalid value is reported at once.
+//!
+//! # Examples
+//! ```toml
+//! [server]
+//! host = "0.0.0.0"
+//! port = 3000
+//! shutdown_timeout = 30
+//!
+//! [mongodb]
+//! database = "qgt"
+//!
+//! [auth]
+//! audiences = ["account", "qgt"]
+//!
+//! [features]
+//! graphiql = false
+//!
+//! [telemetry]
+//! otlp_endpoint = "http://localhost:4318"
+//! ```
+
+use qgt_auth::config::AuthConfig;
+use qgt_telemetry::config::TelemetryConfig;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::Path;
+use std::time::Duration;
+use toml_edit::DocumentMut;
+
+/// The tables of the configuration file and the prefix of their environment variables.
+const SECTIONS: [(&str, &str); 5] = [
+    ("server", "SERVER_"),
+    ("mongodb", "MONGODB_"),
+    ("auth", "AUTH_"),
+    ("features", "FEATURES_"),
+    ("telemetry", "TELEMETRY_"),
+];
+
+/// The environment variables of the configuration and the kind of their values.
+const KEYS: [(&str, Kind); 33] = [
+    ("SERVER_APP_NAME", Kind::Text),
+    ("SERVER_HOST", Kind::Text),
+    ("SERVER_PORT", Kind::Port),
+    ("SERVER_SHUTDOWN_TIMEOUT", Kind::Seconds),
+    ("MONGODB_HOST", Kind::Text),
+    ("MONGODB_PORT", Kind::Port),
+    ("MONGODB_USERNAME", Kind::Text),
+    ("MONGODB_PASSWORD", Kind::Secret),
+    ("MONGODB_DATABASE", Kind::Text),
+    ("MONGODB_ROOT_USERNAME", Kind::Text),
+    ("MONGODB_ROOT_PASSWORD", Kind::Secret),
+    ("MONGODB_ROOT_DATABASE", Kind::Text),
+    ("MONGODB_SHARDED", Kind::Bool),
+    ("AUTH_PROVIDER", Kind::OneOf(&["keycloak", "oidc"])),
+    ("AUTH_HOST", Kind::Text),
+    ("AUTH_PORT", Kind::Port),
+    ("AUTH_ADDRESS", Kind::Text),
+    ("AUTH_ISSUER_URL", Kind::Text),
+    ("AUTH_REQUIRED_ISSUER", Kind::Text),
+    ("AUTH_AUDIENCES", Kind::Text),
+    ("AUTH_LEEWAY", Kind::Seconds),
+    ("AUTH_PUBLIC_MODE", Kind::OneOf(&["anonymous", "optional"])),
+    ("AUTH_PUBLIC_ROLES", Kind::Text),
+    ("AUTH_REALM", Kind::Text),
+    ("AUTH_CLIENT_ID", Kind::Text),
+    ("AUTH_STATE_SECRET", Kind::Secret),
+    ("AUTH_JWKS_FILE", Kind::File),
+    ("AUTH_PUBLIC_KEY_FILE", Kind::File),
+    ("FEATURES_CHANGE_STREAMS", Kind::Bool),
+    ("FEATURES_GRAPHIQL", Kind::Bool),
+    ("TELEMETRY_LOG_FORMAT", Kind::OneOf(&["text", "json"])),
+    ("TELEMETRY_OTLP_ENDPOINT", Kind::Text),
+    ("TELEMETRY_SERVICE_NAME", Kind::Text),
+];
+
+/// The value printed instead of secrets.
+const REDACTED: &str = "<redacted>";
+
+/// The kind of value of a configuration key.
+#[derive(Clone, Copy)]
+enum Kind {
+    Text,
+    /// A text, which is never printed.
+    Secret,
+    Port,
+    Bool,
+    Seconds,
+    /// The path of an existing file.
+    File,
+    OneOf(&'static [&'static str]),
+}
+
+impl Kind {
+    /// Check the `value`, returning the problem if it is invalid.
+    fn check(self, value: &str) -> Option<String> {
+        let problem = match self {
+            Kind::Port if value.parse::<u16>().is_err() => String::from("is not a port"),
+            Kind::Bool if value.parse::<bool>().is_err() => {
+                String::from("is not 'true' or 'false'")
+            }
+            Kind::Seconds if value.parse::<u64>().is_err() => {
+                String::from("is not a number of seconds")
+            }
+            Kind::File if !Path::new(value).is_file() => String::from("is not a file"),
+            Kind::OneOf(values) if !values.contains(&value) => {
+                format!("is not one of {}", values.join(", "))
+            }
+            _ => return None,
+        };
+        Some(format!("'{value}' {problem}"))
+    }
+
+    /// Get the `value` for the configuration file.
+    fn toml_value(self, value: &str) -> toml_edit::Item {
+        match self {
+            Kind::Secret => toml_edit::value(REDACTED),
+            Kind::Port | Kind::Seconds => value
+                .parse::<i64>()
+                .map_or_else(|_| toml_edit::value(value), toml_edit::value),
+            Kind::Bool => value
+                .parse::<bool>()
+                .map_or_else(|_| toml_edit::value(value), toml_edit::value),
+            _ => toml_edit::value(value),
+        }
+    }
+}
+
+/// Get the table and key in the configuration file of the environment variable `key`.
+fn file_key(key: &str) -> (&'static str, String) {
+    SECTIONS
+        .iter()
+        .find_map(|(section, prefix)| {
+            key.strip_prefix(prefix)
+                .map(|name| (*section, name.to_lowercase()))
+        })
+        .expect("every key should have a section")
+}
+
+/// Get the name of the environment variable `key` for messages.
+fn display_name(key: &str) -> String {
+    let (section, name) = file_key(key);
+    format!("{section}.{name} ({key})")
+}
+
+/// Feature toggles.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct FeatureConfig {
+    change_streams: Option<bool>,
+    graphiql: Option<bool>,
+}
+
+impl FeatureConfig {
+    /// Check if domain events are read from MongoDB change streams, if the database supports them.
+    ///
+    /// Without change streams, only the changes made through the same server instance are
+    /// published to subscriptions.
+    pub fn change_streams(&self) -> bool {
+        self.change_streams.unwrap_or(true)
+    }
+
+    /// Check if the GraphiQL pages are served.
+    pub fn graphiql(&self) -> bool {
+        self.graphiql.unwrap_or(true)
+    }
+}
+
+/// The name and address of the server.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ServerConfig {
+    app_name: Option<String>,
+    host: Option<String>,
+    port: Option<u16>,
+    #[serde(skip)]
+    address: String,
+}
+
+impl ServerConfig {
+    const DEFAULT_APP_NAME: &'static str = "quick-microservice";
+    const DEFAULT_HOST: &'static str = "127.0.0.1";
+    const DEFAULT_PORT: u16 = 3000;
+
+    /// Get a server config instance with the `values` of env variables by their names.
+    fn from_values(values: impl IntoIterator<Item = (String, String)>) -> envy::Result<Self> {
+        let mut cfg: Self = envy::prefixed("SERVER_").from_iter(values)?;
+        cfg.address = format!(
+            "{}:{}",
+            cfg.host.as_deref().unwrap_or(Self::DEFAULT_HOST),
+            cfg.port.unwrap_or(Self::DEFAULT_PORT)
+        );
+        Ok(cfg)
+    }
+
+    /// Get the name the server connects to the database with.
+    pub fn app_name(&self) -> &str {
+        self.app_name.as_deref().unwrap_or(Self::DEFAULT_APP_NAME)
+    }
+
+    /// Get the address the server listens on, like `127.0.0.1:3000`.
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+}
+
+/// The connection to MongoDB.
+///
+/// The user of the app is created with the root user, if both have credentials.
+#[derive(Clone, Default, Deserialize)]
+pub struct DbConfig {
+    host: Option<String>,
+    port: Option<u16>,
+    username: Option<String>,
+    password: Option<String>,
+    database: Option<String>,
+    root_username: Option<String>,
+    root_password: Option<String>,
+    root_database: Option<String>,
+    sharded: Option<bool>,
+}
+
+impl DbConfig {
+    const DEFAULT_HOST: &'static str = "127.0.0.1";
+    const DEFAULT_PORT: u16 = 27017;
+    const DEFAULT_DATABASE: &'static str = "test";
+    const DEFAULT_ROOT_DATABASE: &'static str = "admin";
+
+    /// Get a database config instance with the `values` of env variables by their names.
+    fn from_values(values: impl IntoIterator<Item = (String, String)>) -> envy::Result<Self> {
+        envy::prefixed("MONGODB_").from_iter(values)
+    }
+
+    /// Get the name of the app database.
+    pub fn database(&self) -> &str {
+        self.database.as_deref().unwrap_or(Self::DEFAULT_DATABASE)
+    }
+
+    /// Get the name of the root database.
+    pub fn root_database(&self) -> &str {
+        self.root_database
+            .as_deref()
+            .unwrap_or(Self::DEFAULT_ROOT_DATABASE)
+    }
+
+    /// Check if the database is sharded.
+    pub fn sharded(&self) -> bool {
+        self.sharded.unwrap_or(false)
+    }
+
+    /// Get the username and password of the app user, if both are configured.
+    pub(crate) fn credentials(&self) -> Option<(&str, &str)> {
+        self.username.as_deref().zip(self.password.as_deref())
+    }
+
+    /// Get the connection string of the app database.
+    pub(crate) fn address(&self) -> String {
+        self.connection_string(self.credentials(), self.database())
+    }
+
+    /// Get the connection string of the root database.
+    pub(crate) fn root_address(&self) -> String {
+        self.connection_string(
+            self.root_username
+                .as_deref()
+                .zip(self.root_password.as_deref()),
+            self.root_database(),
+        )
+    }
+
+    /// Get the connection string of the `database` with the optional username and password.
+    fn connection_string(&self, credentials: Option<(&str, &str)>, database: &str) -> String {
+        let host = self.host.as_deref().unwrap_or(Self::DEFAULT_HOST);
+        let port = self.port.unwrap_or(Self::DEFAULT_PORT);
+        match credentials {
+            Some((username, password)) => {
+                format!("mongodb://{username}:{password}@{host}:{port}/{database}")
+            }
+            None => format!("mongodb://{host}:{port}/{database}"),
+        }
+    }
+}
+
+/// The graceful shutdown of the server.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ShutdownConfig {
+    shutdown_timeout: Option<u64>,
+}
+
+impl ShutdownConfig {
+    const DEFAULT_TIMEOUT: u64 = 30;
+
+    /// Get the time in-flight requests get to finish after a shutdown signal.
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.shutdown_timeout.unwrap_or(Self::DEFAULT_TIMEOUT))
+    }
+}
+
+/// The invalid values of a configuration.
+#[derive(Debug)]
+pub struct ConfigError(Vec<String>);
+
+impl ConfigError {
+    /// Get the messages of all invalid values.
+    pub fn errors(&self) -> &[String] {
+        &self.0
+    }
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid configuration:")?;
+        for error in &self.0 {
+            write!(f, "\n  - {error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// The validated configuration of the application.
+pub struct Config {
+    pub(crate) server: ServerConfig,
+    pub(crate) mongodb: DbConfig,
+    pub(crate) auth: AuthConfig,
+    pub(crate) features: FeatureConfig,
+    shutdown: ShutdownConfig,
+    telemetry: TelemetryConfig,
+    values: BTreeMap<&'static str, String>,
+}
+
+impl Config {
+    /// Load the configuration from the environment and the TOML file at `path`, if there is one.
+    pub fn load(path: Option<&Path>) -> Result<Self, ConfigError> {
+        let mut errors = vec![];
+        let file_values = match path {
+            Some(path) => read_file(path, &mut errors)?,
+            None => BTreeMap::new(),
+        };
+        let env_values: BTreeMap<&'static str, String> = KEYS
+            .iter()
+            .filter_map(|(key, _)| std::env::var(key).ok().map(|value| (*key, value)))
+            .collect();
+        let mut values = file_values;
+        values.extend(env_values);
+
+        validate(&values, &mut errors);
+        if !errors.is_empty() {
+            return Err(ConfigError(errors));
+        }
+
+        // The configurations of the components are built from the values, so loading does not
+        // change the environment
+        let pairs = || {
+            values
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.clone()))
+        };
+        let build_error = |e: envy::Error| ConfigError(vec![e.to_string()]);
+
+        Ok(Self {
+            server: ServerConfig::from_values(pairs()).map_err(build_error)?,
+            mongodb: DbConfig::from_values(pairs()).map_err(build_error)?,
+            auth: AuthConfig::from_values(pairs()).map_err(build_error)?,
+            features: envy::prefixed("FEATURES_")
+                .from_iter(pairs())
+                .map_err(build_error)?,
+            shutdown: envy::prefixed("SERVER_")
+                .from_iter(pairs())
+                .map_err(build_error)?,
+            telemetry: TelemetryConfig::from_values(pairs()).map_err(build_error)?,
+            values,
+        })
+    }
+
+    /// Get the shutdown configuration, which is needed outside of the app.
+    pub fn shutdown(&self) -> &ShutdownConfig {
+        &self.shutdown
+    }
+
+    /// Get the telemetry configuration, which is needed before the app is set up.
+    pub fn telemetry(&self) -> &TelemetryConfig {
+        &self.telemetry
+    }
+
+    /// Get the configured values as TOML, with secrets redacted.
+    ///
+    /// Keys without a configured value use their defaults and are left out.
+    pub fn redacted(&self) -> String {
+        let mut document = DocumentMut::new();
+        for (section, _) in SECTIONS {
+            document[section] = toml_edit::table();
+        }
+        for (key, kind) in KEYS {
+            if let Some(value) = self.values.get(key) {
+                let (section, name) = file_key(key);
+                document[section][name.as_str()] = kind.toml_value(value);
+            }
+        }
+        document.to_string()
+    }
+}
+
+/// Read the values of the configuration file at `path`.
+///
+/// Unknown keys and unsupported values are added to the `errors`.
+fn read_file(
+    path: &Path,
+    errors: &mut Vec<String>,
+) -> Result<BTreeMap<&'static str, String>, ConfigError> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        ConfigError(vec![format!(
+            "The configuration file '{}' could not be read: {e}",
+            path.display()
+        )])
+    })?;
+    let document = content.parse::<DocumentMut>().map_err(|e| {
+        ConfigError(vec![format!(
+            "The configuration file '{}' is not valid TOML: {e}",
+            path.display()
+        )])
+    })?;
+
+    let mut values = BTreeMap::new();
+    for (section, item) in document.iter() {
+        let Some((_, prefix)) = SECTIONS.iter().find(|(name, _)| *name == section) else {
+            errors.push(format!("{section}: unknown table"));
+            continue;
+        };
+        let Some(table) = item.as_table_like() else {
+            errors.push(format!("{section}: must be a table"));
+            continue;
+        };
+        for (name, item) in table.iter() {
+            let env_key = format!("{prefix}{}", name.to_uppercase());
+            let Some((key, _)) = KEYS.iter().find(|(key, _)| *key == env_key) else {
+                errors.push(format!("{section}.{name}: unknown key"));
+                continue;
+            };
+            match item.as_value().and_then(file_value) {
+                Some(value) => {
+                    values.insert(*key, value);
+                }
+                None => errors.push(format!("{}: unsupported value", display_name(key))),
+            }
+        }
+    }
+
+    Ok(values)
+}
+
+/// Get a value of the configuration file like it would be set as environment variable.
+///
+/// Arrays of strings are joined by commas.
+fn file_value(value: &toml_edit::Value) -> Option<String> {
+    match value {
+        toml_edit::Value::String(value) => Some(value.value().clone()),
+        toml_edit::Value::Integer(value) => Some(value.value().to_string()),
+        toml_edit::Value::Boolean(value) => Some(value.value().to_string()),
+        toml_edit::Value::Array(values) => values
+            .iter()
+            .map(|value| value.as_str().map(ToString::to_string))
+            .collect::<Option<Vec<String>>>()
+            .map(|values| values.join(",")),
+        _ => None,
+    }
+}
+
+/// Validate the `values`, adding the problems to the `errors`.
+fn validate(values: &BTreeMap<&'static str, String>, errors: &mut Vec<String>) {
+    for (key, kind) in KEYS {
+        if let Some(problem) = values.get(key).and_then(|value| kind.check(value)) {
+            errors.push(format!("{}: {problem}", display_name(key)));
+        }
+    }
+
+    if values.get("AUTH_PROVIDER").map(String::as_str) == Some("oidc")
+        && !values.contains_key("AUTH_ISSUER_URL")
+    {
+        errors.push(format!(
+            "{}: is required for the oidc provider",
+            display_name("AUTH_ISSUER_URL")
+        ));
+    }
+    if values.contains_key("AUTH_JWKS_FILE") && values.contains_key("AUTH_PUBLIC_KEY_FILE") {
+        errors.push(format!(
+            "{}: can't be used together with {}",
+            display_name("AUTH_PUBLIC_KEY_FILE"),
+            display_name("AUTH_JWKS_FILE")
+        ));
+    }
+}
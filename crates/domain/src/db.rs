@@ -1,12 +1,170 @@
+use crate::config::DbConfig;
 use anyhow::anyhow;
 use bson::DateTime;
 use qm::mongodb::bson::doc;
 use qm::mongodb::bson::Document;
-use qm::mongodb::DB;
+use qm::mongodb::options::ClientOptions;
+use qm::mongodb::options::IndexOptions;
+use qm::mongodb::Client;
+use qm::mongodb::ClientSession;
+use qm::mongodb::Database;
+use qm::mongodb::IndexModel;
+use std::sync::Arc;
+use std::time::Duration;
 
 pub mod collections {
     pub const TODOS: &str = "todos";
     pub const TAGS: &str = "tags";
+    pub const RESUME_TOKENS: &str = "resume_tokens";
+    pub const SESSIONS: &str = "sessions";
+    pub const PERSONAL_ACCESS_TOKENS: &str = "personal_access_tokens";
+}
+
+/// The number of attempts to connect to the database at start-up.
+const CONNECT_ATTEMPTS: u32 = 6;
+
+/// The delay before the first retry to connect to the database, which doubles with every retry.
+const CONNECT_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// The maximal delay between two attempts to connect to the database.
+const CONNECT_RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// The clients of the app database and the root database.
+///
+/// Like the DB of the qm crate, but built from a [DbConfig] instead of the environment.
+#[derive(Clone)]
+pub struct DB {
+    client: Client,
+    admin: Client,
+    database: Arc<str>,
+    root_database: Arc<str>,
+}
+
+impl DB {
+    /// Connect to the database of the [DbConfig].
+    ///
+    /// Creates the configured user for the database with the root user, if it does not exist yet.
+    async fn new(app_name: &str, config: &DbConfig) -> qm::mongodb::error::Result<Self> {
+        tracing::info!(
+            "'{app_name}' -> connects to mongodb '{}'",
+            config.database()
+        );
+        let admin = client(app_name, &config.root_address()).await?;
+        if let Some((username, password)) = config.credentials() {
+            create_user(&admin, config.database(), username, password).await?;
+        }
+        let db = Self {
+            client: client(app_name, &config.address()).await?,
+            admin,
+            database: Arc::from(config.database()),
+            root_database: Arc::from(config.root_database()),
+        };
+        if config.sharded() {
+            db.get_admin()
+                .run_command(doc! { "enableSharding": config.database() })
+                .await?;
+        }
+        Ok(db)
+    }
+
+    /// Get the app database.
+    pub fn get(&self) -> Database {
+        self.client.database(&self.database)
+    }
+
+    /// Get the root database.
+    pub fn get_admin(&self) -> Database {
+        self.admin.database(&self.root_database)
+    }
+
+    /// Start a session on the app database, e.g. for a transaction.
+    pub async fn session(&self) -> qm::mongodb::error::Result<ClientSession> {
+        self.client.start_session().await
+    }
+
+    /// Delete the documents of all collections of the app database, e.g. between tests.
+    pub async fn cleanup(&self) -> qm::mongodb::error::Result<()> {
+        let database = self.admin.database(&self.database);
+        for collection in database.list_collection_names().await? {
+            database
+                .collection::<Document>(&collection)
+                .delete_many(doc! {})
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Get a client for the MongoDB `address`, which identifies itself with the `app_name`.
+async fn client(app_name: &str, address: &str) -> qm::mongodb::error::Result<Client> {
+    let mut options = ClientOptions::parse(address).await?;
+    options.app_name = Some(app_name.to_string());
+    Client::with_options(options)
+}
+
+/// Create the user with read and write access to the `database`, if it does not exist yet.
+async fn create_user(
+    admin: &Client,
+    database: &str,
+    username: &str,
+    password: &str,
+) -> qm::mongodb::error::Result<()> {
+    let db = admin.database(database);
+    let users = db
+        .run_command(doc! {
+            "usersInfo": [{ "db": database, "user": username }],
+            "showPrivileges": false,
+            "showCredentials": false,
+        })
+        .await?;
+    if users
+        .get_array("users")
+        .is_ok_and(|users| !users.is_empty())
+    {
+        return Ok(());
+    }
+
+    tracing::info!("Creating the user '{username}' for the database '{database}'");
+    db.run_command(doc! {
+        "createUser": username,
+        "pwd": password,
+        "roles": [{ "role": "readWrite", "db": database }],
+    })
+    .await?;
+    Ok(())
+}
+
+/// Connect to the database.
+///
+/// Retries with exponential backoff while the database is not reachable, e.g. because it is
+/// started at the same time as the server.
+pub(crate) async fn connect(app_name: &str, config: &DbConfig) -> anyhow::Result<DB> {
+    let mut delay = CONNECT_RETRY_DELAY;
+    let mut attempt = 1;
+    loop {
+        match DB::new(app_name, config).await {
+            Ok(db) => return Ok(db),
+            Err(e) if attempt < CONNECT_ATTEMPTS => {
+                tracing::warn!(
+                    "Connecting to the database failed (attempt {attempt} of {CONNECT_ATTEMPTS}), retrying in {delay:?}: {e}"
+                );
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(CONNECT_RETRY_MAX_DELAY);
+                attempt += 1;
+            }
+            Err(e) => {
+                return Err(anyhow!(
+                    "Connecting to the database failed after {CONNECT_ATTEMPTS} attempts: {e}"
+                ))
+            }
+        }
+    }
+}
+
+/// Check that the database is reachable.
+pub async fn ping(db: &DB) -> anyhow::Result<()> {
+    db.get().run_command(doc! { "ping": 1 }).await?;
+    Ok(())
 }
 
 /// Set up the database.
@@ -15,9 +173,31 @@ pub mod collections {
 /// some test [Tags](qgt_domain::model::tag::Tag).
 pub(crate) async fn setup_database(db: &DB) -> anyhow::Result<()> {
     init_collection(db, collections::TODOS, vec![]).await?;
-    init_collection(db, collections::TAGS, vec![(doc! { "name": 1 }, true)]).await?;
+    // Tag names are unique per owner
+    init_collection(
+        db,
+        collections::TAGS,
+        vec![(doc! { "owner": 1, "name": 1 }, true)],
+    )
+    .await?;
+    migrate_tag_name_index(db).await?;
+    init_collection(db, collections::SESSIONS, vec![]).await?;
+    // Expired sessions are removed by the database
+    create_expiry_index(db, collections::SESSIONS).await?;
+    // Personal access tokens are looked up by their hash and listed by owner
+    init_collection(
+        db,
+        collections::PERSONAL_ACCESS_TOKENS,
+        vec![
+            (doc! { "token_hash": 1 }, true),
+            (doc! { "owner": 1 }, false),
+        ],
+    )
+    .await?;
+    // Expired personal access tokens are removed by the database
+    create_expiry_index(db, collections::PERSONAL_ACCESS_TOKENS).await?;
 
-    // Initialize example tags
+    // Initialize example tags without an owner
     let docs = vec![
         doc! { "name": "private", "created": DateTime::now() },
         doc! { "name": "social:instagram", "created": DateTime::now() },
@@ -29,9 +209,10 @@ pub(crate) async fn setup_database(db: &DB) -> anyhow::Result<()> {
     for doc in docs {
         let col = db.get().collection::<Document>(collections::TAGS);
         let cnt = col
-            .count_documents(
-                doc! { "name": doc.get("name").expect("the tag name should be in the document") },
-            )
+            .count_documents(doc! {
+                "name": doc.get("name").expect("the tag name should be in the document"),
+                "owner": null,
+            })
             .await?;
         if cnt == 0 {
             db.get()
@@ -51,22 +232,59 @@ pub(crate) async fn setup_database(db: &DB) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Create an index on the `expires` field of the collection, which makes the database remove
+/// documents once they expired.
+///
+/// Documents without the field never expire.
+async fn create_expiry_index(db: &DB, collection_name: &str) -> anyhow::Result<()> {
+    db.get()
+        .collection::<Document>(collection_name)
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "expires": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .expire_after(std::time::Duration::ZERO)
+                        .build(),
+                )
+                .build(),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Replace the former tag name index, which was unique for all owners, with the tag name index
+/// unique per owner.
+async fn migrate_tag_name_index(db: &DB) -> anyhow::Result<()> {
+    let tags = db.get().collection::<Document>(collections::TAGS);
+    if tags
+        .list_index_names()
+        .await?
+        .iter()
+        .any(|name| name == "name_1")
+    {
+        tracing::info!("Replacing the tag name index with an index unique per owner");
+        tags.drop_index("name_1").await?;
+        tags.create_index(
+            IndexModel::builder()
+                .keys(doc! { "owner": 1, "name": 1 })
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
 /// Initialize a specific collection.
 async fn init_collection(
     db: &DB,
     collection_name: &str,
     indexes: Vec<(Document, bool)>,
 ) -> anyhow::Result<()> {
-    let collections: Vec<String> = db
-        .collections()
-        .await
-        .iter()
-        .map(|c| c.to_string())
-        .collect();
-    match db
-        .ensure_collection_with_indexes(&collections, collection_name, indexes)
-        .await
-    {
+    match ensure_collection_with_indexes(db, collection_name, indexes).await {
         Ok(created) => {
             if !created {
                 tracing::info!("Collection and indexes not created for '{collection_name}'.")
@@ -78,3 +296,36 @@ async fn init_collection(
         )),
     }
 }
+
+/// Create the collection with the indexes and their uniqueness, if it does not exist yet.
+///
+/// Returns if the collection was created.
+async fn ensure_collection_with_indexes(
+    db: &DB,
+    collection_name: &str,
+    indexes: Vec<(Document, bool)>,
+) -> qm::mongodb::error::Result<bool> {
+    let database = db.get();
+    if database
+        .list_collection_names()
+        .await?
+        .iter()
+        .any(|name| name == collection_name)
+    {
+        return Ok(false);
+    }
+
+    database.create_collection(collection_name).await?;
+    for (keys, unique) in indexes {
+        database
+            .collection::<Document>(collection_name)
+            .create_index(
+                IndexModel::builder()
+                    .keys(keys)
+                    .options(IndexOptions::builder().unique(unique).build())
+                    .build(),
+            )
+            .await?;
+    }
+    Ok(true)
+}
@@ -0,0 +1,103 @@
+//! Typed errors for the GraphQL API.
+
+use async_graphql::ErrorExtensions;
+use qm::mongodb::error::ErrorKind;
+use qm::mongodb::error::WriteFailure;
+
+/// The MongoDB error code for a duplicate key.
+const DUPLICATE_KEY_CODE: i32 = 11000;
+
+/// An error which is returned to GraphQL clients.
+///
+/// It converts to an [async_graphql::Error] with the [code](DomainError::code) set as
+/// `extensions.code`, so clients can branch on the kind of error.
+///
+/// ## Note
+/// This type intentionally does not implement [std::fmt::Display], since async-graphql converts
+/// any displayable type into an [async_graphql::Error] without extensions.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DomainError {
+    /// The requested object does not exist.
+    NotFound(String),
+    /// The provided input is invalid.
+    ValidationFailed(String),
+    /// The operation conflicts with the current state of the data.
+    Conflict(String),
+    /// The name is already used by another object.
+    DuplicateName(String),
+    /// The caller is not permitted to perform the operation.
+    Forbidden(String),
+    /// An unexpected error occurred. The details are only logged, not returned.
+    Internal(String),
+}
+
+impl DomainError {
+    /// Get the code which is set as `extensions.code`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            DomainError::NotFound(_) => "NOT_FOUND",
+            DomainError::ValidationFailed(_) => "VALIDATION_FAILED",
+            DomainError::Conflict(_) => "CONFLICT",
+            DomainError::DuplicateName(_) => "DUPLICATE_NAME",
+            DomainError::Forbidden(_) => "FORBIDDEN",
+            DomainError::Internal(_) => "INTERNAL",
+        }
+    }
+
+    /// Get the message which is returned to clients.
+    pub fn message(&self) -> &str {
+        match self {
+            DomainError::NotFound(message)
+            | DomainError::ValidationFailed(message)
+            | DomainError::Conflict(message)
+            | DomainError::DuplicateName(message)
+            | DomainError::Forbidden(message) => message,
+            DomainError::Internal(_) => "Internal error",
+        }
+    }
+}
+
+impl ErrorExtensions for DomainError {
+    fn extend(&self) -> async_graphql::Error {
+        async_graphql::Error::new(self.message()).extend_with(|_, e| e.set("code", self.code()))
+    }
+}
+
+impl From<DomainError> for async_graphql::Error {
+    fn from(error: DomainError) -> Self {
+        error.extend()
+    }
+}
+
+impl From<qm::mongodb::error::Error> for DomainError {
+    /// Converts duplicate key errors to [DomainError::DuplicateName] and every other error to
+    /// [DomainError::Internal].
+    fn from(error: qm::mongodb::error::Error) -> Self {
+        if let ErrorKind::Write(WriteFailure::WriteError(write_error)) = error.kind.as_ref() {
+            if write_error.code == DUPLICATE_KEY_CODE {
+                // The server message ends with the duplicate key, e.g. `dup key: { name: "x" }`
+                let key = write_error
+                    .message
+                    .split_once("dup key: ")
+                    .map(|(_, key)| key)
+                    .unwrap_or("unknown");
+                return DomainError::DuplicateName(format!("Duplicate value for {key}"));
+            }
+        }
+
+        tracing::error!("Database error: {error}");
+        DomainError::Internal(error.to_string())
+    }
+}
+
+impl From<anyhow::Error> for DomainError {
+    fn from(error: anyhow::Error) -> Self {
+        match error.downcast::<qm::mongodb::error::Error>() {
+            Ok(error) => error.into(),
+            Err(error) => {
+                tracing::error!("Unexpected error: {error:#}");
+                DomainError::Internal(error.to_string())
+            }
+        }
+    }
+}
@@ -0,0 +1,217 @@
+//! Reads [DomainEvents](DomainEvent) from MongoDB change streams.
+//!
+//! The resume token of the last sent event is kept in memory, so a failed change stream continues
+//! after the last sent event instead of missing changes. It is also stored in the [RESUME_TOKENS]
+//! collection at most every [RESUME_TOKEN_STORE_INTERVAL], so a restarted instance continues from
+//! there. Changes which can not be converted to a [DomainEvent] are logged and skipped.
+//!
+//! Removal events require the pre-images of the removed documents, which are enabled for the
+//! collections with [enable_pre_images].
+
+use super::DomainEvent;
+use super::EventBus;
+use crate::db::collections::RESUME_TOKENS;
+use crate::db::collections::TAGS;
+use crate::db::collections::TODOS;
+use async_graphql::futures_util::StreamExt;
+use bson::doc;
+use bson::DateTime;
+use bson::Document;
+use qm::mongodb::change_stream::event::ChangeStreamEvent;
+use qm::mongodb::change_stream::event::OperationType;
+use qm::mongodb::change_stream::event::ResumeToken;
+use qm::mongodb::change_stream::ChangeStream;
+use qm::mongodb::options::FullDocumentBeforeChangeType;
+use qm::mongodb::options::FullDocumentType;
+use std::time::Duration;
+use std::time::Instant;
+use tokio::task::JoinHandle;
+
+/// The delay before a failed change stream is restarted.
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+
+/// The minimal interval between two writes of the resume token to the database.
+///
+/// All instances share the stored token, so writing it after every event would write to the same
+/// document once per event and instance. The events contain the whole documents, so changes sent
+/// again after a restart only repeat their current state to the subscribers.
+const RESUME_TOKEN_STORE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Check if the MongoDB deployment supports change streams.
+///
+/// Change streams are only available for replica sets and sharded clusters.
+pub(crate) async fn is_supported(db: &crate::db::DB) -> anyhow::Result<bool> {
+    let hello = db.get_admin().run_command(doc! { "hello": 1 }).await?;
+
+    Ok(hello.contains_key("setName") || hello.get_str("msg") == Ok("isdbgrid"))
+}
+
+/// Enable the pre-images of changed documents for the [Tag](crate::model::tag::Tag) and
+/// [Todo](crate::model::todo::Todo) collections.
+///
+/// Requires at least MongoDB 6.0. Without pre-images, removals are not sent.
+pub(crate) async fn enable_pre_images(db: &crate::db::DB) {
+    for collection in [TAGS, TODOS] {
+        if let Err(e) = db
+            .get()
+            .run_command(doc! {
+                "collMod": collection,
+                "changeStreamPreAndPostImages": { "enabled": true },
+            })
+            .await
+        {
+            tracing::warn!("Pre-images could not be enabled for '{collection}': {e}");
+        }
+    }
+}
+
+/// Spawn a task sending the changes of all [Tags](crate::model::tag::Tag) and
+/// [Todos](crate::model::todo::Todo) to the [EventBus].
+///
+/// The resume token is stored with the `key`. All instances of the same application share the
+/// key, since a change stream can be resumed with the token of any instance.
+///
+/// The change stream is restarted if it fails, after the last sent event.
+pub(crate) fn spawn(db: crate::db::DB, bus: EventBus, key: String) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut resume_token = None;
+        loop {
+            if let Err(e) = watch(&db, &bus, &key, &mut resume_token).await {
+                tracing::error!("The change stream failed and is restarted: {e}");
+            }
+            tokio::time::sleep(RESTART_DELAY).await;
+        }
+    })
+}
+
+/// Watch the change stream and send the events to the [EventBus].
+///
+/// Starts after the `resume_token`, which is loaded from the database if there is none yet, and
+/// updates it with every sent event.
+async fn watch(
+    db: &crate::db::DB,
+    bus: &EventBus,
+    key: &str,
+    resume_token: &mut Option<ResumeToken>,
+) -> anyhow::Result<()> {
+    if resume_token.is_none() {
+        *resume_token = load_resume_token(db, key).await?;
+    }
+    let mut stream = match open(db, resume_token.clone()).await {
+        Ok(stream) => stream,
+        Err(e) if resume_token.is_some() => {
+            tracing::warn!("The change stream could not be resumed and starts from now: {e}");
+            open(db, None).await?
+        }
+        Err(e) => return Err(e.into()),
+    };
+    tracing::info!("Watching the change stream for domain events");
+
+    let mut stored = Instant::now();
+    while let Some(event) = stream.next().await.transpose()? {
+        // Skip invalid changes, since failing would resume the stream before them again and again
+        match domain_event(event) {
+            Ok(Some(event)) => bus.send(event),
+            Ok(None) => {}
+            Err(e) => {
+                tracing::error!("A change is skipped, since it is no valid domain event: {e}")
+            }
+        }
+        if let Some(token) = stream.resume_token() {
+            if stored.elapsed() >= RESUME_TOKEN_STORE_INTERVAL {
+                store_resume_token(db, key, &token).await?;
+                stored = Instant::now();
+            }
+            *resume_token = Some(token);
+        }
+    }
+
+    Ok(())
+}
+
+/// Open a change stream for the [Tag](crate::model::tag::Tag) and
+/// [Todo](crate::model::todo::Todo) collections.
+async fn open(
+    db: &crate::db::DB,
+    resume_token: Option<ResumeToken>,
+) -> qm::mongodb::error::Result<ChangeStream<ChangeStreamEvent<Document>>> {
+    db.get()
+        .watch()
+        .pipeline([doc! { "$match": { "ns.coll": { "$in": [TAGS, TODOS] } } }])
+        .full_document(FullDocumentType::UpdateLookup)
+        .full_document_before_change(FullDocumentBeforeChangeType::WhenAvailable)
+        .resume_after(resume_token)
+        .await
+}
+
+/// Convert a change stream event to a [DomainEvent].
+///
+/// Returns [None] for events without a domain meaning, e.g. dropped collections, and for removals
+/// without a pre-image.
+fn domain_event(event: ChangeStreamEvent<Document>) -> anyhow::Result<Option<DomainEvent>> {
+    let collection = event.ns.and_then(|ns| ns.coll);
+    if event.operation_type == OperationType::Delete && event.full_document_before_change.is_none()
+    {
+        tracing::warn!("Removal in '{collection:?}' skipped, since it has no pre-image");
+    }
+
+    Ok(match (event.operation_type, collection.as_deref()) {
+        (OperationType::Insert | OperationType::Update | OperationType::Replace, Some(TAGS)) => {
+            event
+                .full_document
+                .map(bson::from_document)
+                .transpose()?
+                .map(DomainEvent::TagChanged)
+        }
+        (OperationType::Insert | OperationType::Update | OperationType::Replace, Some(TODOS)) => {
+            event
+                .full_document
+                .map(bson::from_document)
+                .transpose()?
+                .map(DomainEvent::TodoChanged)
+        }
+        (OperationType::Delete, Some(TAGS)) => event
+            .full_document_before_change
+            .map(bson::from_document)
+            .transpose()?
+            .map(DomainEvent::TagRemoved),
+        (OperationType::Delete, Some(TODOS)) => event
+            .full_document_before_change
+            .map(bson::from_document)
+            .transpose()?
+            .map(DomainEvent::TodoRemoved),
+        _ => None,
+    })
+}
+
+/// Load the stored resume token for the `key`.
+async fn load_resume_token(db: &crate::db::DB, key: &str) -> anyhow::Result<Option<ResumeToken>> {
+    let stored = db
+        .get()
+        .collection::<Document>(RESUME_TOKENS)
+        .find_one(doc! { "_id": key })
+        .await?;
+
+    Ok(stored
+        .and_then(|stored| stored.get("token").cloned())
+        .map(bson::from_bson)
+        .transpose()?)
+}
+
+/// Store the resume token for the `key`.
+async fn store_resume_token(
+    db: &crate::db::DB,
+    key: &str,
+    resume_token: &ResumeToken,
+) -> anyhow::Result<()> {
+    db.get()
+        .collection::<Document>(RESUME_TOKENS)
+        .update_one(
+            doc! { "_id": key },
+            doc! { "$set": { "token": bson::to_bson(resume_token)?, "modified": DateTime::now() } },
+        )
+        .upsert(true)
+        .await?;
+
+    Ok(())
+}
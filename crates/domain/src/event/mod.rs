@@ -0,0 +1,94 @@
+//! Domain events, which are published on every change of [Tags](Tag) and [Todos](Todo).
+//!
+//! The [EventBus] is part of the [App](crate::app::App) and is used by the GraphQL subscriptions.
+//! Depending on the [EventSource], the events are either published by the mutations of this
+//! instance or read from MongoDB change streams, which include the changes of all instances.
+
+use crate::model::tag::Tag;
+use crate::model::todo::Todo;
+use async_graphql::futures_util::Stream;
+use async_graphql::futures_util::StreamExt;
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+
+pub(crate) mod change_stream;
+
+/// The count of events a subscriber can lag behind before it misses events.
+const EVENT_BUS_CAPACITY: usize = 1024;
+
+/// A change of a [Tag] or [Todo].
+#[derive(Clone, Debug)]
+pub(crate) enum DomainEvent {
+    /// A [Tag] was created or updated.
+    TagChanged(Tag),
+    /// A [Tag] was removed.
+    TagRemoved(Tag),
+    /// A [Todo] was created or updated.
+    TodoChanged(Todo),
+    /// A [Todo] was removed.
+    TodoRemoved(Todo),
+}
+
+/// The source of the [DomainEvents](DomainEvent) distributed by the [EventBus].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum EventSource {
+    /// The events are published by the mutations of this instance.
+    ///
+    /// Used for standalone MongoDB deployments, which do not support change streams.
+    InProcess,
+    /// The events are read from MongoDB change streams.
+    ChangeStream,
+}
+
+/// A bus distributing [DomainEvents](DomainEvent) to all subscribers of this instance.
+#[derive(Clone)]
+pub(crate) struct EventBus {
+    sender: broadcast::Sender<DomainEvent>,
+    source: EventSource,
+}
+
+impl EventBus {
+    /// Construct a new [EventBus] for the [EventSource].
+    pub fn new(source: EventSource) -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUS_CAPACITY);
+        Self { sender, source }
+    }
+
+    /// Get the [EventSource].
+    pub fn source(&self) -> EventSource {
+        self.source
+    }
+
+    /// Publish an event for a change made by this instance.
+    ///
+    /// Ignored for [EventSource::ChangeStream], since the change stream delivers the change.
+    pub fn publish(&self, event: DomainEvent) {
+        if self.source == EventSource::InProcess {
+            self.send(event);
+        }
+    }
+
+    /// Send an event to all current subscribers.
+    ///
+    /// Events sent while there are no subscribers are dropped.
+    fn send(&self, event: DomainEvent) {
+        // Sending only fails if there are no subscribers
+        let _ = self.sender.send(event);
+    }
+
+    /// Get a stream of all events published from now on.
+    ///
+    /// A subscriber which lags behind more than the bus capacity skips the missed events.
+    pub fn subscribe(&self) -> impl Stream<Item = DomainEvent> {
+        BroadcastStream::new(self.sender.subscribe()).filter_map(|event| async move {
+            match event {
+                Ok(event) => Some(event),
+                Err(BroadcastStreamRecvError::Lagged(count)) => {
+                    tracing::warn!("Event subscriber lagged behind and missed {count} events");
+                    None
+                }
+            }
+        })
+    }
+}
@@ -1,6 +1,28 @@
+pub mod access_token;
 pub mod app;
+pub mod config;
 pub mod db;
+pub mod error;
+mod event;
 mod model;
+pub mod owner;
+pub mod permission;
 pub mod private_schema;
+pub mod request_id;
 pub mod schema;
 mod service;
+pub mod session;
+
+/// The todos and the service functions to manage them, which are shared with the REST API.
+pub mod todo {
+    pub use crate::model::todo::CreateTodoInput;
+    pub use crate::model::todo::Todo;
+    pub use crate::model::todo::UpdateTodoInput;
+    pub use crate::service::todo::create_todo;
+    pub use crate::service::todo::get_todo;
+    pub use crate::service::todo::list_todos;
+    pub use crate::service::todo::remove_all_todos;
+    pub use crate::service::todo::remove_todos_by_id;
+    pub use crate::service::todo::todo_not_found;
+    pub use crate::service::todo::update_todo;
+}
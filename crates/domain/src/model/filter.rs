@@ -0,0 +1,34 @@
+//! Common GraphQL inputs for filtering and sorting.
+
+use async_graphql::Enum;
+use async_graphql::InputObject;
+use bson::DateTime;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// The GraphQL input for filtering by a date range.
+///
+/// Both bounds are inclusive and optional.
+#[derive(Debug, Deserialize, InputObject, Serialize)]
+pub(crate) struct DateTimeRange {
+    pub from: Option<DateTime>,
+    pub to: Option<DateTime>,
+}
+
+/// The direction to sort by.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Enum, Eq, PartialEq, Serialize)]
+pub(crate) enum SortDirection {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl SortDirection {
+    /// Get the MongoDB sort value for the direction.
+    pub fn value(&self) -> i32 {
+        match self {
+            SortDirection::Asc => 1,
+            SortDirection::Desc => -1,
+        }
+    }
+}
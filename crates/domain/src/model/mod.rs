@@ -1,4 +1,5 @@
 //! This module contains all the models for the application.
 
+pub(crate) mod filter;
 pub(crate) mod tag;
 pub(crate) mod todo;
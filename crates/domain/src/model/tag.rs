@@ -1,4 +1,6 @@
+use async_graphql::dataloader::DataLoader;
 use async_graphql::ComplexObject;
+use async_graphql::Enum;
 use async_graphql::InputObject;
 use async_graphql::SimpleObject;
 use bson::doc;
@@ -8,12 +10,11 @@ use qm::mongodb::options::UpdateModifications;
 use serde::Deserialize;
 use serde::Serialize;
 
-use crate::db::collections::TODOS;
-
-use super::todo::Todo;
+use crate::error::DomainError;
+use crate::schema::loader::TodoCountByTagLoader;
 
 /// Database representation of a tag.
-#[derive(Debug, Deserialize, Serialize, SimpleObject)]
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
 #[graphql(complex)]
 pub(crate) struct Tag {
     created: DateTime,
@@ -21,18 +22,21 @@ pub(crate) struct Tag {
     id: Option<ObjectId>,
     modified: Option<DateTime>,
     name: String,
+    /// The subject of the owner, if the tag was created by a signed-in user.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    owner: Option<String>,
 }
 
 #[ComplexObject]
 impl Tag {
+    /// The count of [Todos](super::todo::Todo) with this tag.
     async fn count(&self, ctx: &async_graphql::Context<'_>) -> async_graphql::Result<u64> {
-        let app = ctx.data::<crate::app::App>()?;
-        app.db()
-            .get()
-            .collection::<Todo>(TODOS)
-            .count_documents(doc! {"tags": self.id.expect("todo id should exist")})
-            .await
-            .map_err(|e| e.into())
+        let loader = ctx.data::<DataLoader<TodoCountByTagLoader>>()?;
+        let id = self
+            .id
+            .ok_or_else(|| DomainError::Internal(String::from("a stored tag should have an id")))?;
+        let count = loader.load_one(id).await?;
+        Ok(count.unwrap_or(0))
     }
 }
 
@@ -42,18 +46,29 @@ pub(crate) struct CreateTagInput {
     name: String,
 }
 
-impl From<CreateTagInput> for Tag {
-    /// This allows for converting the GraphQL [CreateTagInput] to a database [Tag].
+impl Tag {
+    /// Create a database [Tag] from the GraphQL [CreateTagInput] with the given `owner`.
     ///
     /// Will hard-coded set the [`created`](Tag) field to the current UTC date.
-    fn from(input: CreateTagInput) -> Self {
+    pub(crate) fn new(input: CreateTagInput, owner: Option<String>) -> Self {
         Tag {
             created: DateTime::now(),
             id: None,
             modified: None,
             name: input.name,
+            owner,
         }
     }
+
+    /// Get the id.
+    pub(crate) fn object_id(&self) -> Option<ObjectId> {
+        self.id
+    }
+
+    /// Get the subject of the owner.
+    pub(crate) fn owner_subject(&self) -> Option<&str> {
+        self.owner.as_deref()
+    }
 }
 
 /// The GraphQL input for updating a tag.
@@ -80,3 +95,13 @@ impl From<&UpdateTagInput> for UpdateModifications {
         UpdateModifications::Document(doc)
     }
 }
+
+/// How todos referencing a removed tag are handled.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Enum, Eq, PartialEq, Serialize)]
+pub(crate) enum TagRemovalMode {
+    /// Remove the tag ids from all todos.
+    #[default]
+    Pull,
+    /// Refuse to remove tags which are still referenced by todos.
+    Restrict,
+}
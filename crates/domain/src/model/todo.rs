@@ -1,6 +1,9 @@
+use async_graphql::dataloader::DataLoader;
 use async_graphql::ComplexObject;
+use async_graphql::Enum;
 use async_graphql::InputObject;
 use async_graphql::MaybeUndefined;
+use async_graphql::OneofObject;
 use async_graphql::SimpleObject;
 use bson::doc;
 use bson::oid::ObjectId;
@@ -9,21 +12,26 @@ use qm::mongodb::options::UpdateModifications;
 use serde::Deserialize;
 use serde::Serialize;
 
-use crate::db::collections::TAGS;
-use crate::service::get_many_by_filter;
+use crate::error::DomainError;
+use crate::schema::loader::TagLoader;
 
+use super::filter::DateTimeRange;
+use super::filter::SortDirection;
 use super::tag::Tag;
 
 /// Database representation of a todo.
-#[derive(Debug, Deserialize, Serialize, SimpleObject)]
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
 #[graphql(complex)]
-pub(crate) struct Todo {
+pub struct Todo {
     created: DateTime,
     completed: bool,
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
     id: Option<ObjectId>,
     modified: Option<DateTime>,
     order: u64,
+    /// The subject of the owner, if the todo was created by a signed-in user.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    owner: Option<String>,
     #[graphql(skip)]
     tags: Option<Vec<ObjectId>>,
     title: String,
@@ -36,54 +44,145 @@ impl Todo {
         ctx: &async_graphql::Context<'_>,
     ) -> async_graphql::Result<Option<Vec<Tag>>> {
         if let Some(tag_ids) = &self.tags {
-            let app = ctx.data::<crate::app::App>()?;
-            let result =
-                get_many_by_filter(&app.db().get(), TAGS, doc! { "_id": { "$in": tag_ids } })
-                    .await
-                    .map_err(|e| e.into());
-            result.map(|v| if v.is_empty() { None } else { Some(v) })
+            let loader = ctx.data::<DataLoader<TagLoader>>()?;
+            let mut tags = loader.load_many(tag_ids.iter().copied()).await?;
+            // Keep the order of the tag ids
+            let result: Vec<Tag> = tag_ids.iter().filter_map(|id| tags.remove(id)).collect();
+            Ok(if result.is_empty() {
+                None
+            } else {
+                Some(result)
+            })
         } else {
             Ok(None)
         }
     }
 }
 
-/// The GraphQL input for creating a todo.
-#[derive(Debug, Deserialize, InputObject, Serialize)]
-pub(crate) struct CreateTodoInput {
-    completed: bool,
-    order: u64,
-    tags: Option<Vec<ObjectId>>,
-    title: String,
-}
-
-impl From<CreateTodoInput> for Todo {
-    /// This allows for converting the GraphQL [CreateTodoInput] to a database [Todo].
+impl Todo {
+    /// Create a database [Todo] from the GraphQL [CreateTodoInput] with the given `order` and
+    /// `owner`.
     ///
     /// Will hard-coded set the [created](Todo) field to the current UTC date.
-    fn from(input: CreateTodoInput) -> Self {
+    pub(crate) fn new(input: CreateTodoInput, order: u64, owner: Option<String>) -> Self {
         Todo {
             created: DateTime::now(),
             completed: input.completed,
             id: None,
             modified: None,
-            order: input.order,
+            order,
+            owner,
             tags: input.tags,
             title: input.title,
         }
     }
+
+    /// Get the id.
+    pub fn object_id(&self) -> Option<ObjectId> {
+        self.id
+    }
+
+    /// Get the title.
+    pub fn title_str(&self) -> &str {
+        &self.title
+    }
+
+    /// Get the completion state.
+    pub fn is_completed(&self) -> bool {
+        self.completed
+    }
+
+    /// Get the order.
+    pub fn sort_order(&self) -> u64 {
+        self.order
+    }
+
+    /// Get the subject of the owner.
+    pub(crate) fn owner_subject(&self) -> Option<&str> {
+        self.owner.as_deref()
+    }
+}
+
+/// The GraphQL input for creating a todo.
+///
+/// The [order](Todo) is assigned by the server, appending the todo to the end.
+#[derive(Debug, Deserialize, InputObject, Serialize)]
+pub struct CreateTodoInput {
+    completed: bool,
+    /// The order set by [CreateTodoInput::with_order] instead of appending the todo.
+    #[graphql(skip)]
+    #[serde(skip)]
+    pub(crate) order: Option<u64>,
+    pub tags: Option<Vec<ObjectId>>,
+    title: String,
+}
+
+impl CreateTodoInput {
+    /// Construct a new [CreateTodoInput] without tags.
+    pub fn new(title: String, completed: bool) -> Self {
+        Self {
+            completed,
+            order: None,
+            tags: None,
+            title,
+        }
+    }
+
+    /// Set the `order` of the todo, for clients which manage the orders themselves.
+    pub fn with_order(mut self, order: u64) -> Result<Self, DomainError> {
+        stored_order(order)?;
+        self.order = Some(order);
+        Ok(self)
+    }
+}
+
+/// The GraphQL input for the position to move a todo to.
+#[derive(Debug, OneofObject)]
+pub(crate) enum TodoPosition {
+    /// Directly before the todo with this id.
+    Before(ObjectId),
+    /// Directly after the todo with this id.
+    After(ObjectId),
 }
 
 /// The GraphQL input for updating a todo.
 #[derive(Debug, Deserialize, InputObject, Serialize)]
-pub(crate) struct UpdateTodoInput {
+pub struct UpdateTodoInput {
     completed: Option<bool>,
     pub id: ObjectId,
-    order: Option<u32>,
-    tags: MaybeUndefined<Vec<ObjectId>>,
+    /// The stored order set by [UpdateTodoInput::with_order].
+    #[graphql(skip)]
+    #[serde(skip)]
+    order: Option<i64>,
+    pub tags: MaybeUndefined<Vec<ObjectId>>,
     title: Option<String>,
 }
 
+impl UpdateTodoInput {
+    /// Construct a new [UpdateTodoInput] for the todo with the `id`, which keeps the tags.
+    pub fn new(id: ObjectId, title: Option<String>, completed: Option<bool>) -> Self {
+        Self {
+            completed,
+            id,
+            order: None,
+            tags: MaybeUndefined::Undefined,
+            title,
+        }
+    }
+
+    /// Set the `order` of the todo, for clients which manage the orders themselves.
+    pub fn with_order(mut self, order: u64) -> Result<Self, DomainError> {
+        self.order = Some(stored_order(order)?);
+        Ok(self)
+    }
+}
+
+/// Get the `order` as it is stored in MongoDB, which has no unsigned 64-bit integers.
+pub(crate) fn stored_order(order: u64) -> Result<i64, DomainError> {
+    i64::try_from(order)
+        .map_err(|_| DomainError::ValidationFailed(format!("The order {order} is too large")))
+}
+
 impl From<&UpdateTodoInput> for UpdateModifications {
     /// Converter to create a update document for MongoDB.
     ///
@@ -102,7 +201,7 @@ impl From<&UpdateTodoInput> for UpdateModifications {
         match &input.tags {
             MaybeUndefined::Undefined => {}
             MaybeUndefined::Null => {
-                unsets.insert("$unset", doc! {"tags": ""});
+                unsets.insert("tags", "");
             }
             MaybeUndefined::Value(tags) => {
                 sets.insert("tags", tags);
@@ -114,8 +213,82 @@ impl From<&UpdateTodoInput> for UpdateModifications {
 
         // The document with all update operations combined
         let mut doc = doc! { "$set": sets };
-        doc.insert("$unset", unsets);
+        if !unsets.is_empty() {
+            doc.insert("$unset", unsets);
+        }
 
         UpdateModifications::Document(doc)
     }
 }
+
+/// The GraphQL input for filtering todos.
+///
+/// All provided conditions must match.
+#[derive(Debug, Default, Deserialize, InputObject, Serialize)]
+pub(crate) struct TodoFilter {
+    /// Only todos with this completion state.
+    pub completed: Option<bool>,
+    /// Only todos having any of the tags with these ids.
+    pub tag_ids: Option<Vec<ObjectId>>,
+    /// Only todos having any of the tags with these names.
+    pub tag_names: Option<Vec<String>>,
+    /// Only todos created in this range.
+    pub created: Option<DateTimeRange>,
+    /// Only todos modified in this range.
+    pub modified: Option<DateTimeRange>,
+    /// Only todos with a title containing this value, ignoring case.
+    pub title_contains: Option<String>,
+}
+
+/// The GraphQL input for filtering changed todos of a subscription.
+///
+/// All provided conditions must match.
+#[derive(Debug, Default, InputObject)]
+pub(crate) struct TodoChangedFilter {
+    /// Only todos with this completion state.
+    pub completed: Option<bool>,
+    /// Only todos having the tag with this id.
+    pub tag_id: Option<ObjectId>,
+}
+
+impl TodoChangedFilter {
+    /// Check if the [Todo] matches all conditions.
+    pub fn matches(&self, todo: &Todo) -> bool {
+        self.completed
+            .is_none_or(|completed| todo.completed == completed)
+            && self.tag_id.is_none_or(|tag_id| {
+                todo.tags
+                    .as_ref()
+                    .is_some_and(|tags| tags.contains(&tag_id))
+            })
+    }
+}
+
+/// The fields todos can be sorted by.
+#[derive(Clone, Copy, Debug, Deserialize, Enum, Eq, PartialEq, Serialize)]
+pub(crate) enum TodoSortField {
+    Order,
+    Created,
+    Modified,
+    Title,
+}
+
+impl TodoSortField {
+    /// Get the database field name.
+    pub fn field(&self) -> &'static str {
+        match self {
+            TodoSortField::Order => "order",
+            TodoSortField::Created => "created",
+            TodoSortField::Modified => "modified",
+            TodoSortField::Title => "title",
+        }
+    }
+}
+
+/// The GraphQL input for sorting todos.
+#[derive(Debug, Deserialize, InputObject, Serialize)]
+pub(crate) struct TodoSort {
+    pub field: TodoSortField,
+    #[graphql(default)]
+    pub direction: SortDirection,
+}
@@ -0,0 +1,60 @@
+//! The ownership of [Tags](crate::model::tag::Tag) and [Todos](crate::model::todo::Todo).
+//!
+//! The server adds the [Owner] of a request to the GraphQL request data. Every tag and todo
+//! created by the request is stamped with the owner and all other operations are scoped to the
+//! tags and todos of the owner.
+
+use async_graphql::Context;
+use bson::Document;
+
+/// The owner of a request, identified by the subject of the caller's token.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Owner(String);
+
+impl Owner {
+    /// Construct a new [Owner] with the `subject` of the caller's token.
+    pub fn new(subject: impl Into<String>) -> Self {
+        Self(subject.into())
+    }
+
+    /// Get the subject.
+    pub fn subject(&self) -> &str {
+        &self.0
+    }
+}
+
+/// The tags and todos a request can access.
+///
+/// Requests with an [Owner] can only access the tags and todos of that owner. Requests without an
+/// owner can only access the tags and todos without an owner.
+#[derive(Clone, Debug, Default)]
+pub struct OwnerScope(Option<String>);
+
+impl OwnerScope {
+    /// Get the [OwnerScope] of a request with the optional `owner`.
+    pub fn new(owner: Option<&Owner>) -> Self {
+        Self(owner.map(|owner| owner.subject().to_string()))
+    }
+
+    /// Get the [OwnerScope] of the request.
+    pub fn from_context(ctx: &Context<'_>) -> Self {
+        Self::new(ctx.data_opt::<Owner>())
+    }
+
+    /// Get the owner to stamp on created tags and todos.
+    pub fn owner(&self) -> Option<String> {
+        self.0.clone()
+    }
+
+    /// Restrict the MongoDB `filter` to the documents in this scope.
+    pub fn filter(&self, mut filter: Document) -> Document {
+        // `null` also matches documents without the field
+        filter.insert("owner", self.0.clone());
+        filter
+    }
+
+    /// Check if a tag or todo with the `owner` is in this scope.
+    pub fn contains(&self, owner: Option<&str>) -> bool {
+        self.0.as_deref() == owner
+    }
+}
@@ -0,0 +1,130 @@
+//! The permissions of a request, which guard the mutations of the GraphQL API.
+//!
+//! The server maps the roles of the caller's token to [Permissions] and adds them to the GraphQL
+//! request data. Mutations are guarded by a [PermissionGuard] for the [Permission] they require.
+
+use crate::error::DomainError;
+use async_graphql::Context;
+use async_graphql::Enum;
+use async_graphql::Guard;
+use qgt_auth::config::AuthConfig;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+/// A permission, which is granted by the role with the same name.
+#[derive(Clone, Copy, Debug, Deserialize, Enum, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum Permission {
+    /// Create, update, move and remove todos.
+    #[serde(rename = "todo:write")]
+    TodoWrite,
+    /// Create and update tags.
+    #[serde(rename = "tag:write")]
+    TagWrite,
+    /// Remove tags, which also changes the todos using them.
+    #[serde(rename = "tag:admin")]
+    TagAdmin,
+}
+
+impl Permission {
+    /// All permissions.
+    pub const ALL: [Permission; 3] = [
+        Permission::TodoWrite,
+        Permission::TagWrite,
+        Permission::TagAdmin,
+    ];
+
+    /// Get the name of the role, which grants this permission.
+    pub fn role(&self) -> &'static str {
+        match self {
+            Permission::TodoWrite => "todo:write",
+            Permission::TagWrite => "tag:write",
+            Permission::TagAdmin => "tag:admin",
+        }
+    }
+
+    /// Get the permission granted by the `role`, if any.
+    pub fn from_role(role: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|permission| permission.role() == role)
+    }
+}
+
+/// The set of [Permissions](Permission) of a request.
+///
+/// Requests without [Permissions] in the request data get the permissions of the schema data.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Permissions(BTreeSet<Permission>);
+
+impl Permissions {
+    /// Get the [Permissions] granted by the `roles`.
+    ///
+    /// Roles which do not grant a permission are ignored.
+    pub fn from_roles<'r>(roles: impl IntoIterator<Item = &'r str>) -> Self {
+        Self(
+            roles
+                .into_iter()
+                .filter_map(Permission::from_role)
+                .collect(),
+        )
+    }
+
+    /// Get the [Permissions] of a request to the public endpoint with the roles of its optional
+    /// token.
+    ///
+    /// Every request gets the permissions of the [public roles](AuthConfig::public_roles) and the
+    /// roles of a token add to them, so signing in never narrows the permissions.
+    pub fn public<'r>(
+        config: &'r AuthConfig,
+        token_roles: impl IntoIterator<Item = &'r str>,
+    ) -> Self {
+        Self::from_roles(config.public_roles().chain(token_roles))
+    }
+
+    /// Check if the `permission` is granted.
+    pub fn contains(&self, permission: Permission) -> bool {
+        self.0.contains(&permission)
+    }
+
+    /// Check that the `permission` is granted.
+    ///
+    /// Fails with [DomainError::Forbidden] otherwise.
+    pub fn require(&self, permission: Permission) -> Result<(), DomainError> {
+        if self.contains(permission) {
+            Ok(())
+        } else {
+            Err(DomainError::Forbidden(format!(
+                "Missing permission {}",
+                permission.role()
+            )))
+        }
+    }
+
+    /// Iterate over the granted [Permissions](Permission) in a stable order.
+    pub fn iter(&self) -> impl Iterator<Item = Permission> + '_ {
+        self.0.iter().copied()
+    }
+}
+
+/// Guards a field with the [Permission] it requires.
+///
+/// Denied access fails with [DomainError::Forbidden].
+pub(crate) struct PermissionGuard(Permission);
+
+impl PermissionGuard {
+    /// Construct a new [PermissionGuard] requiring the `permission`.
+    pub fn new(permission: Permission) -> Self {
+        Self(permission)
+    }
+}
+
+impl Guard for PermissionGuard {
+    async fn check(&self, ctx: &Context<'_>) -> async_graphql::Result<()> {
+        let none = Permissions::default();
+        Ok(ctx
+            .data_opt::<Permissions>()
+            .unwrap_or(&none)
+            .require(self.0)?)
+    }
+}
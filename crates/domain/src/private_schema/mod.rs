@@ -1,9 +1,14 @@
 use crate::app::App;
+use crate::request_id::RequestIdErrors;
+use crate::schema::loader::TagLoader;
+use crate::schema::loader::TodoCountByTagLoader;
 use crate::schema::mutation::DomainMutationRoot;
 use crate::schema::query::DomainQueryRoot;
-use async_graphql::EmptySubscription;
+use crate::schema::SubscriptionRoot;
+use async_graphql::extensions::Tracing;
 use async_graphql::MergedObject;
 use mutation::PrivateDomainMutationRoot;
+use qgt_metrics::graphql::GraphQLMetrics;
 use query::PrivateDomainQueryRoot;
 
 mod mutation;
@@ -11,7 +16,7 @@ mod query;
 
 /// The base private schema type for the application.
 pub type PrivateSchema =
-    async_graphql::Schema<PrivateQueryRoot, PrivateMutationRoot, EmptySubscription>;
+    async_graphql::Schema<PrivateQueryRoot, PrivateMutationRoot, SubscriptionRoot>;
 
 /// The global query root, which combines alls sub-schemas.
 #[derive(Default, MergedObject)]
@@ -30,9 +35,14 @@ impl SchemaBuilder {
         async_graphql::Schema::build(
             PrivateQueryRoot::default(),
             PrivateMutationRoot::default(),
-            EmptySubscription,
+            SubscriptionRoot::default(),
         )
+        .data(TagLoader::data_loader(app.db().clone()))
+        .data(TodoCountByTagLoader::data_loader(app.db().clone()))
         .data(app.clone())
+        .extension(GraphQLMetrics::new("private"))
+        .extension(Tracing)
+        .extension(RequestIdErrors)
         .finish()
     }
 }
@@ -1,5 +1,97 @@
-use async_graphql::MergedObject;
+use crate::access_token::CreatePersonalAccessTokenInput;
+use crate::access_token::CreatedPersonalAccessToken;
+use crate::access_token::PersonalAccessTokenAuth;
+use crate::error::DomainError;
+use crate::owner::Owner;
+use crate::permission::Permissions;
+use async_graphql::Context;
+use async_graphql::Object;
+use bson::oid::ObjectId;
+use bson::DateTime;
 
 /// Additional mutations for a secured API
-#[derive(Default, MergedObject)]
-pub(crate) struct PrivateDomainMutationRoot;
+#[derive(Default)]
+pub(crate) struct PrivateDomainMutationRoot {}
+
+#[Object]
+impl PrivateDomainMutationRoot {
+    /// Create a personal access token for the caller.
+    ///
+    /// The `scopes` must be a subset of the caller's permissions. Callers authenticated with a
+    /// personal access token are forbidden, so a token can not create a longer-lived one. The token
+    /// is only returned in this response.
+    async fn create_personal_access_token(
+        &self,
+        ctx: &Context<'_>,
+        input: CreatePersonalAccessTokenInput,
+    ) -> async_graphql::Result<CreatedPersonalAccessToken> {
+        let app = ctx.data::<crate::app::App>()?;
+        let owner = ctx.data::<Owner>()?;
+        if ctx.data_opt::<PersonalAccessTokenAuth>().is_some() {
+            return Err(DomainError::Forbidden(String::from(
+                "Personal access tokens can not create personal access tokens",
+            ))
+            .into());
+        }
+        if input.name.trim().is_empty() {
+            return Err(
+                DomainError::ValidationFailed(String::from("The name must not be empty")).into(),
+            );
+        }
+        if input.scopes.is_empty() {
+            return Err(DomainError::ValidationFailed(String::from(
+                "The scopes must not be empty",
+            ))
+            .into());
+        }
+        if input
+            .expires
+            .is_some_and(|expires| expires <= DateTime::now())
+        {
+            return Err(DomainError::ValidationFailed(String::from(
+                "The expiry must be in the future",
+            ))
+            .into());
+        }
+        let permissions = ctx.data_opt::<Permissions>().cloned().unwrap_or_default();
+        if let Some(scope) = input
+            .scopes
+            .iter()
+            .find(|scope| !permissions.contains(**scope))
+        {
+            return Err(DomainError::Forbidden(format!(
+                "The scope {} exceeds the caller's permissions",
+                scope.role()
+            ))
+            .into());
+        }
+
+        app.access_tokens()
+            .create(owner.subject().to_string(), input)
+            .await
+            .map_err(|e| DomainError::from(e).into())
+    }
+
+    /// Revoke a personal access token of the caller by `id`.
+    async fn revoke_personal_access_token(
+        &self,
+        ctx: &Context<'_>,
+        id: ObjectId,
+    ) -> async_graphql::Result<bool> {
+        let app = ctx.data::<crate::app::App>()?;
+        let owner = ctx.data::<Owner>()?;
+        if app
+            .access_tokens()
+            .revoke(owner.subject(), &id)
+            .await
+            .map_err(DomainError::from)?
+        {
+            Ok(true)
+        } else {
+            Err(
+                DomainError::NotFound(format!("No personal access token found for id '{id}'"))
+                    .into(),
+            )
+        }
+    }
+}
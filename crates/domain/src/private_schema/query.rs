@@ -1,5 +1,34 @@
-use async_graphql::MergedObject;
+use crate::access_token::PersonalAccessToken;
+use crate::error::DomainError;
+use crate::owner::Owner;
+use crate::permission::Permission;
+use crate::permission::Permissions;
+use async_graphql::Context;
+use async_graphql::Object;
 
 /// Additional queries for a secured API
-#[derive(Default, MergedObject)]
-pub(crate) struct PrivateDomainQueryRoot;
+#[derive(Default)]
+pub(crate) struct PrivateDomainQueryRoot {}
+
+#[Object]
+impl PrivateDomainQueryRoot {
+    /// Get the [Permissions](Permission) granted to the caller by the roles of the token.
+    async fn permissions(&self, ctx: &Context<'_>) -> Vec<Permission> {
+        ctx.data_opt::<Permissions>()
+            .map(|permissions| permissions.iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// Get the personal access tokens of the caller, ordered by creation.
+    async fn personal_access_tokens(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Vec<PersonalAccessToken>> {
+        let app = ctx.data::<crate::app::App>()?;
+        let owner = ctx.data::<Owner>()?;
+        app.access_tokens()
+            .list(owner.subject())
+            .await
+            .map_err(|e| DomainError::from(e).into())
+    }
+}
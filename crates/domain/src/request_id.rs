@@ -0,0 +1,117 @@
+//! The ids of requests, which correlate log lines and errors with a request.
+//!
+//! The server takes the [RequestId] from the `X-Request-Id` header of a request, or generates one,
+//! and adds it to the data of the GraphQL request. The [RequestIdErrors] extension adds it to the
+//! extensions of every error of the response as `requestId`.
+
+use async_graphql::extensions::Extension;
+use async_graphql::extensions::ExtensionContext;
+use async_graphql::extensions::ExtensionFactory;
+use async_graphql::extensions::NextPrepareRequest;
+use async_graphql::extensions::NextRequest;
+use async_graphql::Request;
+use async_graphql::Response;
+use async_graphql::ServerResult;
+use rand::RngCore;
+use std::any::TypeId;
+use std::fmt::Display;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+
+/// The length of generated request ids in bytes.
+const REQUEST_ID_LENGTH: usize = 16;
+
+/// The maximum length of request ids sent by clients.
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+/// The id of a request.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RequestId(String);
+
+impl RequestId {
+    /// Get the [RequestId] of an `id` sent by a client.
+    ///
+    /// Returns [None] if the `id` is empty, too long or contains other than visible ASCII
+    /// characters, so it can be logged and returned as header safely.
+    pub fn parse(id: &str) -> Option<Self> {
+        (!id.is_empty()
+            && id.len() <= MAX_REQUEST_ID_LENGTH
+            && id.chars().all(|c| c.is_ascii_graphic()))
+        .then(|| Self(id.to_string()))
+    }
+
+    /// Generate a new random [RequestId].
+    pub fn generate() -> Self {
+        let mut id = [0_u8; REQUEST_ID_LENGTH];
+        rand::thread_rng().fill_bytes(&mut id);
+        Self(hex::encode(id))
+    }
+
+    /// Get the id.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Adds the [RequestId] of the request data to the extensions of all errors of the response.
+///
+/// This includes the errors of parsing and validating the request.
+pub struct RequestIdErrors;
+
+impl ExtensionFactory for RequestIdErrors {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(RequestIdErrorsExtension::default())
+    }
+}
+
+#[derive(Default)]
+struct RequestIdErrorsExtension {
+    /// The [RequestId] of the request data, which is not available to the request hook.
+    request_id: Mutex<Option<RequestId>>,
+}
+
+impl RequestIdErrorsExtension {
+    /// Get the [RequestId] of the request.
+    fn request_id(&self) -> MutexGuard<'_, Option<RequestId>> {
+        self.request_id
+            .lock()
+            .expect("the request id should not be poisoned")
+    }
+}
+
+#[async_trait::async_trait]
+impl Extension for RequestIdErrorsExtension {
+    async fn request(&self, ctx: &ExtensionContext<'_>, next: NextRequest<'_>) -> Response {
+        let mut response = next.run(ctx).await;
+        if let Some(request_id) = self.request_id().as_ref() {
+            for error in &mut response.errors {
+                error
+                    .extensions
+                    .get_or_insert_with(Default::default)
+                    .set("requestId", request_id.as_str());
+            }
+        }
+        response
+    }
+
+    async fn prepare_request(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        request: Request,
+        next: NextPrepareRequest<'_>,
+    ) -> ServerResult<Request> {
+        *self.request_id() = request
+            .data
+            .get(&TypeId::of::<RequestId>())
+            .and_then(|data| data.downcast_ref::<RequestId>())
+            .cloned();
+        next.run(ctx, request).await
+    }
+}
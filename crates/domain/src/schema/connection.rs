@@ -0,0 +1,93 @@
+//! Helpers for Relay-style cursor connections.
+
+use crate::error::DomainError;
+use crate::service::get_page_by_keyset;
+use crate::service::valid_keyset_values;
+use crate::service::KeysetKey;
+use crate::service::PageArgs;
+use async_graphql::connection::Connection;
+use async_graphql::connection::Edge;
+use async_graphql::connection::OpaqueCursor;
+use async_graphql::OutputType;
+use async_graphql::SimpleObject;
+use bson::Bson;
+use bson::Document;
+use serde::de::DeserializeOwned;
+
+/// The page size used if neither `first` nor `last` are provided.
+const DEFAULT_PAGE_SIZE: usize = 20;
+/// The maximum page size which can be requested.
+const MAX_PAGE_SIZE: usize = 100;
+
+/// The opaque cursor containing the keyset values of a node.
+pub(crate) type KeysetCursor = OpaqueCursor<Vec<Bson>>;
+
+/// A connection with keyset cursors and the [ConnectionFields].
+pub(crate) type KeysetConnection<T> = Connection<KeysetCursor, T, ConnectionFields>;
+
+/// Additional fields available on every connection.
+#[derive(SimpleObject)]
+pub(crate) struct ConnectionFields {
+    /// The count of all nodes, regardless of the requested page.
+    total_count: u64,
+}
+
+/// Resolve a connection for objects of type `T` in `collection` matching the provided filter.
+///
+/// The nodes are ordered ascending by `keys`, see
+/// [get_page_by_keyset](crate::service::get_page_by_keyset).
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn keyset_connection<T>(
+    db: &qm::mongodb::Database,
+    collection: &str,
+    filter: Document,
+    keys: &[KeysetKey],
+    after: Option<String>,
+    before: Option<String>,
+    first: Option<i32>,
+    last: Option<i32>,
+) -> async_graphql::Result<KeysetConnection<T>>
+where
+    T: DeserializeOwned + OutputType + Send + Sync,
+{
+    async_graphql::connection::query(
+        after,
+        before,
+        first,
+        last,
+        |after: Option<KeysetCursor>, before: Option<KeysetCursor>, first, last| async move {
+            if [&after, &before]
+                .into_iter()
+                .flatten()
+                .any(|cursor| !valid_keyset_values(keys, cursor))
+            {
+                return Err(DomainError::ValidationFailed(String::from("Invalid cursor")).into());
+            }
+            let args = PageArgs {
+                after: after.map(|c| c.0),
+                before: before.map(|c| c.0),
+                first: first.map(|f: usize| f.min(MAX_PAGE_SIZE)),
+                last: last.map(|l: usize| l.min(MAX_PAGE_SIZE)),
+            };
+            let page =
+                get_page_by_keyset::<T>(db, collection, filter, keys, args, DEFAULT_PAGE_SIZE)
+                    .await
+                    .map_err(DomainError::from)?;
+
+            let mut connection = Connection::with_additional_fields(
+                page.has_previous_page,
+                page.has_next_page,
+                ConnectionFields {
+                    total_count: page.total_count,
+                },
+            );
+            connection.edges.extend(
+                page.items
+                    .into_iter()
+                    .map(|(values, node)| Edge::new(OpaqueCursor(values), node)),
+            );
+            Ok::<_, async_graphql::Error>(connection)
+        },
+    )
+    .await
+}
@@ -0,0 +1,101 @@
+//! [DataLoaders](async_graphql::dataloader::DataLoader) to batch database queries of resolvers.
+
+use crate::db::collections::TAGS;
+use crate::db::collections::TODOS;
+use crate::error::DomainError;
+use crate::model::tag::Tag;
+use async_graphql::dataloader::DataLoader;
+use async_graphql::dataloader::Loader;
+use async_graphql::futures_util::TryStreamExt;
+use bson::doc;
+use bson::oid::ObjectId;
+use bson::Document;
+use std::collections::HashMap;
+
+/// Loads [Tags](Tag) by id.
+pub(crate) struct TagLoader {
+    db: crate::db::DB,
+}
+
+impl TagLoader {
+    /// Create a [DataLoader] for the [TagLoader].
+    pub fn data_loader(db: crate::db::DB) -> DataLoader<Self> {
+        DataLoader::new(Self { db }, tokio::spawn)
+    }
+}
+
+impl Loader<ObjectId> for TagLoader {
+    type Value = Tag;
+    type Error = DomainError;
+
+    async fn load(&self, keys: &[ObjectId]) -> Result<HashMap<ObjectId, Self::Value>, Self::Error> {
+        let docs: Vec<Document> = self
+            .db
+            .get()
+            .collection::<Document>(TAGS)
+            .find(doc! { "_id": { "$in": keys } })
+            .await?
+            .try_collect()
+            .await?;
+
+        docs.into_iter()
+            .map(|doc| {
+                let id = doc.get_object_id("_id").map_err(anyhow::Error::from)?;
+                let tag = bson::from_document(doc).map_err(anyhow::Error::from)?;
+                Ok((id, tag))
+            })
+            .collect()
+    }
+}
+
+/// Loads the count of [Todos](crate::model::todo::Todo) by tag id.
+pub(crate) struct TodoCountByTagLoader {
+    db: crate::db::DB,
+}
+
+impl TodoCountByTagLoader {
+    /// Create a [DataLoader] for the [TodoCountByTagLoader].
+    pub fn data_loader(db: crate::db::DB) -> DataLoader<Self> {
+        DataLoader::new(Self { db }, tokio::spawn)
+    }
+}
+
+impl Loader<ObjectId> for TodoCountByTagLoader {
+    type Value = u64;
+    type Error = DomainError;
+
+    async fn load(&self, keys: &[ObjectId]) -> Result<HashMap<ObjectId, Self::Value>, Self::Error> {
+        // Tags without todos are not part of the aggregation result
+        let mut counts: HashMap<ObjectId, u64> = keys.iter().map(|key| (*key, 0)).collect();
+
+        let pipeline = vec![
+            doc! { "$match": { "tags": { "$in": keys } } },
+            // Count a todo only once, even if it has a tag multiple times
+            doc! { "$project": { "tags": { "$setUnion": ["$tags", []] } } },
+            doc! { "$unwind": "$tags" },
+            doc! { "$match": { "tags": { "$in": keys } } },
+            doc! { "$group": { "_id": "$tags", "count": { "$sum": 1 } } },
+        ];
+        let groups: Vec<Document> = self
+            .db
+            .get()
+            .collection::<Document>(TODOS)
+            .aggregate(pipeline)
+            .await?
+            .try_collect()
+            .await?;
+
+        for group in groups {
+            let id = group.get_object_id("_id").map_err(anyhow::Error::from)?;
+            let count = match group.get("count") {
+                Some(bson::Bson::Int32(count)) => u64::try_from(*count),
+                Some(bson::Bson::Int64(count)) => u64::try_from(*count),
+                _ => Ok(0),
+            }
+            .map_err(anyhow::Error::from)?;
+            counts.insert(id, count);
+        }
+
+        Ok(counts)
+    }
+}
@@ -1,16 +1,26 @@
 //! This module collects all GraphQL Schemas and provides a builder for it.
 
 use crate::app::App;
-use async_graphql::EmptySubscription;
+use crate::permission::Permissions;
+use crate::request_id::RequestIdErrors;
+use async_graphql::extensions::Tracing;
 use async_graphql::MergedObject;
+use async_graphql::MergedSubscription;
+use loader::TagLoader;
+use loader::TodoCountByTagLoader;
 use mutation::DomainMutationRoot;
+use qgt_metrics::graphql::GraphQLMetrics;
 use query::DomainQueryRoot;
+use subscription::DomainSubscriptionRoot;
 
+pub(crate) mod connection;
+pub(crate) mod loader;
 pub(crate) mod mutation;
 pub(crate) mod query;
+pub(crate) mod subscription;
 
 /// The base schema type for the application.
-pub type Schema = async_graphql::Schema<QueryRoot, MutationRoot, EmptySubscription>;
+pub type Schema = async_graphql::Schema<QueryRoot, MutationRoot, SubscriptionRoot>;
 
 /// The global query root, which combines alls sub-schemas.
 #[derive(Default, MergedObject)]
@@ -20,6 +30,10 @@ pub struct QueryRoot(DomainQueryRoot);
 #[derive(Default, MergedObject)]
 pub struct MutationRoot(DomainMutationRoot);
 
+/// The global subscription root, which combines alls sub-schemas.
+#[derive(Default, MergedSubscription)]
+pub struct SubscriptionRoot(DomainSubscriptionRoot);
+
 /// The schema builder for the GraphQL Schema.
 #[derive(Default)]
 pub struct SchemaBuilder {}
@@ -29,9 +43,16 @@ impl SchemaBuilder {
         async_graphql::Schema::build(
             QueryRoot::default(),
             MutationRoot::default(),
-            EmptySubscription,
+            SubscriptionRoot::default(),
         )
+        .data(TagLoader::data_loader(app.db().clone()))
+        .data(TodoCountByTagLoader::data_loader(app.db().clone()))
         .data(app.clone())
+        // Anonymous requests have the permissions of the public roles
+        .data(Permissions::public(app.auth_ctx().config(), []))
+        .extension(GraphQLMetrics::new("public"))
+        .extension(Tracing)
+        .extension(RequestIdErrors)
         .finish()
     }
 }
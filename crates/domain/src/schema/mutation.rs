@@ -1,15 +1,19 @@
-use crate::db::collections::TAGS;
-use crate::db::collections::TODOS;
+use crate::error::DomainError;
 use crate::model::tag::CreateTagInput;
 use crate::model::tag::Tag;
+use crate::model::tag::TagRemovalMode;
 use crate::model::tag::UpdateTagInput;
 use crate::model::todo::CreateTodoInput;
 use crate::model::todo::Todo;
+use crate::model::todo::TodoPosition;
 use crate::model::todo::UpdateTodoInput;
-use crate::service::get_one_by_id;
+use crate::owner::OwnerScope;
+use crate::permission::Permission;
+use crate::permission::PermissionGuard;
+use crate::service::tag;
+use crate::service::todo;
 use async_graphql::Context;
 use async_graphql::Object;
-use bson::doc;
 use qm::mongodb::bson::oid::ObjectId;
 
 #[derive(Default)]
@@ -18,170 +22,133 @@ pub(crate) struct DomainMutationRoot {}
 #[Object]
 impl DomainMutationRoot {
     /// Create a new [Tag].
+    #[graphql(guard = "PermissionGuard::new(Permission::TagWrite)")]
     async fn create_tag(
         &self,
         ctx: &Context<'_>,
         input: CreateTagInput,
     ) -> async_graphql::Result<Tag> {
         let app = ctx.data::<crate::app::App>()?;
-        let result = app
-            .db()
-            .get()
-            .collection::<Tag>(TAGS)
-            .insert_one(Tag::from(input))
-            .await?;
-
-        get_one_by_id(
-            &app.db().get(),
-            TAGS,
-            &result
-                .inserted_id
-                .as_object_id()
-                .expect("inserted id should be an ObjectId"),
-        )
-        .await
-        .map_err(|e| e.into())
-        .map(|t| {
-            t.unwrap_or_else(|| {
-                panic!(
-                    "the inserted tag should exist for id '{}'",
-                    &result.inserted_id
-                )
-            })
-        })
+        let scope = OwnerScope::from_context(ctx);
+
+        Ok(tag::create_tag(app, &scope, input).await?)
     }
 
     /// Update an existing [Tag].
+    #[graphql(guard = "PermissionGuard::new(Permission::TagWrite)")]
     async fn update_tag(
         &self,
         ctx: &Context<'_>,
         input: UpdateTagInput,
     ) -> async_graphql::Result<Tag> {
         let app = ctx.data::<crate::app::App>()?;
-        let result = app
-            .db()
-            .get()
-            .collection::<Tag>(TAGS)
-            .update_one(doc! { "_id": &input.id }, &input)
-            .await?;
-
-        if result.modified_count != 1 {
-            tracing::warn!(
-                "Unexpected modified count of '{}' for tag update with id '{}'",
-                &result.modified_count,
-                &input.id
-            )
-        }
-
-        get_one_by_id(&app.db().get(), TAGS, &input.id)
-            .await
-            .map_err(|e| e.into())
-            .map(|t| {
-                t.unwrap_or_else(|| panic!("the updated tag should exist for id '{}'", &input.id))
-            })
+        let scope = OwnerScope::from_context(ctx);
+
+        Ok(tag::update_tag(app, &scope, &input).await?)
     }
 
     /// Delete multiple [Tags](Tag) by id.
+    ///
+    /// The `mode` defines how [Todos](Todo) referencing the [Tags](Tag) are handled.
+    #[graphql(guard = "PermissionGuard::new(Permission::TagAdmin)")]
     async fn remove_tags_by_id(
         &self,
         ctx: &Context<'_>,
         ids: Vec<ObjectId>,
+        #[graphql(default)] mode: TagRemovalMode,
     ) -> async_graphql::Result<usize> {
         let app = ctx.data::<crate::app::App>()?;
-        let result = app
-            .db()
-            .get()
-            .collection::<Tag>(TAGS)
-            .delete_many(doc! { "_id": { "$in": &ids } })
-            .await?;
-
-        Ok(result
-            .deleted_count
-            .try_into()
-            .expect("the deleted count should fit"))
+        let scope = OwnerScope::from_context(ctx);
+        let deleted_count = tag::remove_tags_by_id(app, &scope, &ids, mode).await?;
+
+        Ok(count_of(deleted_count)?)
     }
 
     /// Create a new [Todo].
+    #[graphql(guard = "PermissionGuard::new(Permission::TodoWrite)")]
     async fn create_todo(
         &self,
         ctx: &Context<'_>,
         input: CreateTodoInput,
     ) -> async_graphql::Result<Todo> {
         let app = ctx.data::<crate::app::App>()?;
-        let result = app
-            .db()
-            .get()
-            .collection::<Todo>(TODOS)
-            .insert_one(Todo::from(input))
-            .await?;
-
-        get_one_by_id(
-            &app.db().get(),
-            TODOS,
-            &result
-                .inserted_id
-                .as_object_id()
-                .expect("inserted id should be an ObjectId"),
-        )
-        .await
-        .map_err(|e| e.into())
-        .map(|t| {
-            t.unwrap_or_else(|| {
-                panic!(
-                    "the inserted todo should exist for id '{}'",
-                    &result.inserted_id
-                )
-            })
-        })
+        let scope = OwnerScope::from_context(ctx);
+
+        Ok(todo::create_todo(app, &scope, input).await?)
     }
 
     /// Update an existing [Todo].
+    #[graphql(guard = "PermissionGuard::new(Permission::TodoWrite)")]
     async fn update_todo(
         &self,
         ctx: &Context<'_>,
         input: UpdateTodoInput,
     ) -> async_graphql::Result<Todo> {
         let app = ctx.data::<crate::app::App>()?;
-        let result = app
-            .db()
-            .get()
-            .collection::<Todo>(TODOS)
-            .update_one(doc! { "_id": &input.id }, &input)
-            .await?;
-
-        if result.modified_count != 1 {
-            tracing::warn!(
-                "Unexpected modified count of '{}' for todo update with id '{}'",
-                &result.modified_count,
-                &input.id
-            )
-        }
-
-        get_one_by_id(&app.db().get(), TODOS, &input.id)
-            .await
-            .map_err(|e| e.into())
-            .map(|t| {
-                t.unwrap_or_else(|| panic!("the updated todo should exist for id '{}'", &input.id))
-            })
+        let scope = OwnerScope::from_context(ctx);
+
+        Ok(todo::update_todo(app, &scope, &input).await?)
+    }
+
+    /// Move a [Todo] to a new position.
+    ///
+    /// Only the moved [Todo] is updated, unless the orders of all [Todos](Todo) must be renormalised
+    /// to make room at the position.
+    #[graphql(guard = "PermissionGuard::new(Permission::TodoWrite)")]
+    async fn move_todo(
+        &self,
+        ctx: &Context<'_>,
+        id: ObjectId,
+        position: TodoPosition,
+    ) -> async_graphql::Result<Todo> {
+        let app = ctx.data::<crate::app::App>()?;
+        let scope = OwnerScope::from_context(ctx);
+
+        Ok(todo::move_todo(app, &scope, &id, &position).await?)
     }
 
     /// Delete multiple [Todos](Todo) by id.
+    #[graphql(guard = "PermissionGuard::new(Permission::TodoWrite)")]
     async fn remove_todos_by_id(
         &self,
         ctx: &Context<'_>,
         ids: Vec<ObjectId>,
     ) -> async_graphql::Result<usize> {
         let app = ctx.data::<crate::app::App>()?;
-        let result = app
-            .db()
-            .get()
-            .collection::<Todo>(TODOS)
-            .delete_many(doc! { "_id": { "$in": &ids } })
-            .await?;
-
-        Ok(result
-            .deleted_count
-            .try_into()
-            .expect("the deleted count should fit"))
+        let scope = OwnerScope::from_context(ctx);
+        let deleted_count = todo::remove_todos_by_id(app, &scope, &ids).await?;
+
+        Ok(count_of(deleted_count)?)
     }
+
+    /// Set the completion state of all [Todos](Todo).
+    ///
+    /// Returns the [Todos](Todo) which changed their completion state.
+    #[graphql(guard = "PermissionGuard::new(Permission::TodoWrite)")]
+    async fn toggle_all_todos(
+        &self,
+        ctx: &Context<'_>,
+        completed: bool,
+    ) -> async_graphql::Result<Vec<Todo>> {
+        let app = ctx.data::<crate::app::App>()?;
+        let scope = OwnerScope::from_context(ctx);
+
+        Ok(todo::toggle_all_todos(app, &scope, completed).await?)
+    }
+
+    /// Delete all completed [Todos](Todo).
+    #[graphql(guard = "PermissionGuard::new(Permission::TodoWrite)")]
+    async fn clear_completed_todos(&self, ctx: &Context<'_>) -> async_graphql::Result<usize> {
+        let app = ctx.data::<crate::app::App>()?;
+        let scope = OwnerScope::from_context(ctx);
+        let deleted_count = todo::clear_completed_todos(app, &scope).await?;
+
+        Ok(count_of(deleted_count)?)
+    }
+}
+
+/// Get the `count` of a database operation as GraphQL count.
+fn count_of(count: u64) -> Result<usize, DomainError> {
+    usize::try_from(count)
+        .map_err(|_| DomainError::Internal(format!("the count {count} should fit into usize")))
 }
@@ -1,10 +1,19 @@
 use crate::db::collections::TAGS;
 use crate::db::collections::TODOS;
+use crate::error::DomainError;
 use crate::model::tag::Tag;
 use crate::model::todo::Todo;
+use crate::model::todo::TodoFilter;
+use crate::model::todo::TodoSort;
+use crate::owner::OwnerScope;
+use crate::schema::connection::keyset_connection;
+use crate::schema::connection::KeysetConnection;
 use crate::service::get_many_by_filter;
+use crate::service::get_many_by_filter_and_sort;
 use crate::service::get_one_by_filter;
-use crate::service::get_one_by_id;
+use crate::service::todo::todo_filter;
+use crate::service::todo::todo_sort;
+use crate::service::KeysetKey;
 use async_graphql::Context;
 use async_graphql::Object;
 use bson::doc;
@@ -18,9 +27,34 @@ impl DomainQueryRoot {
     /// Get all [Tags](Tag).
     async fn tags(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Tag>> {
         let app = ctx.data::<crate::app::App>()?;
-        get_many_by_filter(&app.db().get(), TAGS, doc! {})
+        let scope = OwnerScope::from_context(ctx);
+        get_many_by_filter(&app.db().get(), TAGS, scope.filter(doc! {}))
             .await
-            .map_err(|e| e.into())
+            .map_err(|e| DomainError::from(e).into())
+    }
+
+    /// Get [Tags](Tag) as a paginated connection, ordered by creation.
+    async fn tags_connection(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> async_graphql::Result<KeysetConnection<Tag>> {
+        let app = ctx.data::<crate::app::App>()?;
+        let scope = OwnerScope::from_context(ctx);
+        keyset_connection(
+            &app.db().get(),
+            TAGS,
+            scope.filter(doc! {}),
+            &[KeysetKey::Id],
+            after,
+            before,
+            first,
+            last,
+        )
+        .await
     }
 
     /// Get a [Tag] by `id`.
@@ -30,29 +64,73 @@ impl DomainQueryRoot {
         id: ObjectId,
     ) -> async_graphql::Result<Option<Tag>> {
         let app = ctx.data::<crate::app::App>()?;
-        get_one_by_id(&app.db().get(), TAGS, &id)
+        let scope = OwnerScope::from_context(ctx);
+        get_one_by_filter(&app.db().get(), TAGS, scope.filter(doc! { "_id": id }))
             .await
-            .map_err(|e| e.into())
+            .map_err(|e| DomainError::from(e).into())
     }
 
-    /// Get a [Tag] by `name``.
+    /// Get a [Tag] by `name`.
     async fn tag_by_name(
         &self,
         ctx: &Context<'_>,
         name: String,
     ) -> async_graphql::Result<Option<Tag>> {
         let app = ctx.data::<crate::app::App>()?;
-        get_one_by_filter(&app.db().get(), TAGS, doc! { "name": name })
+        let scope = OwnerScope::from_context(ctx);
+        get_one_by_filter(&app.db().get(), TAGS, scope.filter(doc! { "name": name }))
             .await
-            .map_err(|e| e.into())
+            .map_err(|e| DomainError::from(e).into())
     }
 
     /// Get [Todos](Todo).
-    async fn todos(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Todo>> {
+    ///
+    /// Without a `sort`, the todos are returned in their natural order.
+    async fn todos(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<TodoFilter>,
+        sort: Option<TodoSort>,
+    ) -> async_graphql::Result<Vec<Todo>> {
+        let app = ctx.data::<crate::app::App>()?;
+        let db = app.db().get();
+        let scope = OwnerScope::from_context(ctx);
+        let filter = todo_filter(&db, &scope, &filter.unwrap_or_default())
+            .await
+            .map_err(DomainError::from)?;
+        let sort = sort.as_ref().map(todo_sort).unwrap_or_default();
+        get_many_by_filter_and_sort(&db, TODOS, filter, sort)
+            .await
+            .map_err(|e| DomainError::from(e).into())
+    }
+
+    /// Get [Todos](Todo) as a paginated connection, ordered by `order`.
+    async fn todos_connection(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<TodoFilter>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> async_graphql::Result<KeysetConnection<Todo>> {
         let app = ctx.data::<crate::app::App>()?;
-        get_many_by_filter(&app.db().get(), TODOS, doc! {})
+        let db = app.db().get();
+        let scope = OwnerScope::from_context(ctx);
+        let filter = todo_filter(&db, &scope, &filter.unwrap_or_default())
             .await
-            .map_err(|e| e.into())
+            .map_err(DomainError::from)?;
+        keyset_connection(
+            &db,
+            TODOS,
+            filter,
+            &[KeysetKey::Order, KeysetKey::Id],
+            after,
+            before,
+            first,
+            last,
+        )
+        .await
     }
 
     /// Get a [Todo] by `id`.
@@ -62,8 +140,9 @@ impl DomainQueryRoot {
         id: ObjectId,
     ) -> async_graphql::Result<Option<Todo>> {
         let app = ctx.data::<crate::app::App>()?;
-        get_one_by_id(&app.db().get(), TODOS, &id)
+        let scope = OwnerScope::from_context(ctx);
+        get_one_by_filter(&app.db().get(), TODOS, scope.filter(doc! { "_id": id }))
             .await
-            .map_err(|e| e.into())
+            .map_err(|e| DomainError::from(e).into())
     }
 }
@@ -0,0 +1,87 @@
+use crate::event::DomainEvent;
+use crate::model::tag::Tag;
+use crate::model::todo::Todo;
+use crate::model::todo::TodoChangedFilter;
+use crate::owner::OwnerScope;
+use async_graphql::futures_util::future::ready;
+use async_graphql::futures_util::Stream;
+use async_graphql::futures_util::StreamExt;
+use async_graphql::Context;
+use async_graphql::Subscription;
+use bson::oid::ObjectId;
+
+#[derive(Default)]
+pub(crate) struct DomainSubscriptionRoot {}
+
+#[Subscription]
+impl DomainSubscriptionRoot {
+    /// Get every created or updated [Tag].
+    async fn tag_changed(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<impl Stream<Item = Tag>> {
+        let app = ctx.data::<crate::app::App>()?;
+        let scope = OwnerScope::from_context(ctx);
+        Ok(app.events().subscribe().filter_map(move |event| {
+            ready(match event {
+                DomainEvent::TagChanged(tag) if scope.contains(tag.owner_subject()) => Some(tag),
+                _ => None,
+            })
+        }))
+    }
+
+    /// Get the id of every removed [Tag].
+    async fn tag_removed(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<impl Stream<Item = ObjectId>> {
+        let app = ctx.data::<crate::app::App>()?;
+        let scope = OwnerScope::from_context(ctx);
+        Ok(app.events().subscribe().filter_map(move |event| {
+            ready(match event {
+                DomainEvent::TagRemoved(tag) if scope.contains(tag.owner_subject()) => {
+                    tag.object_id()
+                }
+                _ => None,
+            })
+        }))
+    }
+
+    /// Get every created or updated [Todo] matching the optional `filter`.
+    async fn todo_changed(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<TodoChangedFilter>,
+    ) -> async_graphql::Result<impl Stream<Item = Todo>> {
+        let app = ctx.data::<crate::app::App>()?;
+        let scope = OwnerScope::from_context(ctx);
+        let filter = filter.unwrap_or_default();
+        Ok(app.events().subscribe().filter_map(move |event| {
+            ready(match event {
+                DomainEvent::TodoChanged(todo)
+                    if scope.contains(todo.owner_subject()) && filter.matches(&todo) =>
+                {
+                    Some(todo)
+                }
+                _ => None,
+            })
+        }))
+    }
+
+    /// Get the id of every removed [Todo].
+    async fn todo_removed(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<impl Stream<Item = ObjectId>> {
+        let app = ctx.data::<crate::app::App>()?;
+        let scope = OwnerScope::from_context(ctx);
+        Ok(app.events().subscribe().filter_map(move |event| {
+            ready(match event {
+                DomainEvent::TodoRemoved(todo) if scope.contains(todo.owner_subject()) => {
+                    todo.object_id()
+                }
+                _ => None,
+            })
+        }))
+    }
+}
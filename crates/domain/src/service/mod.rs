@@ -0,0 +1,267 @@
+//! Service with convenience functions for database access.
+
+use crate::error::DomainError;
+use anyhow::anyhow;
+use async_graphql::futures_util::TryStreamExt;
+use bson::doc;
+use bson::oid::ObjectId;
+use bson::Bson;
+use bson::Document;
+use serde::de::DeserializeOwned;
+
+pub(crate) mod tag;
+pub(crate) mod todo;
+
+/// Get the [ObjectId] of an inserted document.
+pub(crate) fn inserted_object_id(inserted_id: &Bson) -> Result<ObjectId, DomainError> {
+    inserted_id.as_object_id().ok_or_else(|| {
+        DomainError::Internal(format!("inserted id '{inserted_id}' should be an ObjectId"))
+    })
+}
+
+/// Get the ids of all documents in the collection matching the filter.
+pub(crate) async fn existing_ids(
+    db: &qm::mongodb::Database,
+    collection: &str,
+    filter: Document,
+) -> Result<Vec<ObjectId>, DomainError> {
+    Ok(get_many_by_filter::<Document>(db, collection, filter)
+        .await?
+        .iter()
+        .filter_map(|document| document.get_object_id("_id").ok())
+        .collect())
+}
+
+/// Get one object of type `T` by id.
+///
+/// This is a convenience function to not require getting a filter [Document].\
+/// The default filter [Document] used is `{"_id": id}`.
+pub(crate) async fn get_one_by_id<T>(
+    db: &qm::mongodb::Database,
+    collection: &str,
+    id: &ObjectId,
+) -> anyhow::Result<Option<T>>
+where
+    T: DeserializeOwned + Send + Sync,
+{
+    get_one_by_filter(db, collection, doc! { "_id": id }).await
+}
+
+/// Get one object of type `T` with provided filter [Document].
+pub(crate) async fn get_one_by_filter<T>(
+    db: &qm::mongodb::Database,
+    collection: &str,
+    filter: Document,
+) -> anyhow::Result<Option<T>>
+where
+    T: DeserializeOwned + Send + Sync,
+{
+    db.collection::<T>(collection)
+        .find_one(filter)
+        .await
+        .map_err(|e| e.into())
+}
+
+/// Get many objects of type `T` with provided filter [Document].
+pub(crate) async fn get_many_by_filter<T>(
+    db: &qm::mongodb::Database,
+    collection: &str,
+    filter: Document,
+) -> anyhow::Result<Vec<T>>
+where
+    T: DeserializeOwned + Send + Sync,
+{
+    let cursor = db.collection::<T>(collection).find(filter).await?;
+    cursor.try_collect().await.map_err(|e| e.into())
+}
+
+/// Get many objects of type `T` with provided filter and sort [Documents](Document).
+pub(crate) async fn get_many_by_filter_and_sort<T>(
+    db: &qm::mongodb::Database,
+    collection: &str,
+    filter: Document,
+    sort: Document,
+) -> anyhow::Result<Vec<T>>
+where
+    T: DeserializeOwned + Send + Sync,
+{
+    let cursor = db
+        .collection::<T>(collection)
+        .find(filter)
+        .sort(sort)
+        .await?;
+    cursor.try_collect().await.map_err(|e| e.into())
+}
+
+/// Arguments for a keyset paginated request.
+///
+/// The cursors contain the values of the keyset fields, in the same order as the keys.
+#[derive(Debug, Default)]
+pub(crate) struct PageArgs {
+    pub after: Option<Vec<Bson>>,
+    pub before: Option<Vec<Bson>>,
+    pub first: Option<usize>,
+    pub last: Option<usize>,
+}
+
+/// A page of objects of type `T` retrieved with keyset pagination.
+#[derive(Debug)]
+pub(crate) struct Page<T> {
+    /// The objects of the page together with their keyset values, in ascending keyset order.
+    pub items: Vec<(Vec<Bson>, T)>,
+    pub has_previous_page: bool,
+    pub has_next_page: bool,
+    /// The count of all objects matching the filter, regardless of the page.
+    pub total_count: u64,
+}
+
+/// A key by which objects are sorted for keyset pagination.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum KeysetKey {
+    /// The `_id` with [ObjectId] values.
+    Id,
+    /// The `order` with integer values.
+    Order,
+}
+
+impl KeysetKey {
+    /// Get the name of the field.
+    pub fn field(&self) -> &'static str {
+        match self {
+            KeysetKey::Id => "_id",
+            KeysetKey::Order => "order",
+        }
+    }
+
+    /// Check if the `value` has the type of the field.
+    fn accepts(&self, value: &Bson) -> bool {
+        matches!(
+            (self, value),
+            (KeysetKey::Id, Bson::ObjectId(_))
+                | (KeysetKey::Order, Bson::Int32(_) | Bson::Int64(_))
+        )
+    }
+}
+
+/// Check if the keyset `values` match the `keys` in count and type.
+///
+/// The values of cursors are sent by clients, so this ensures they can not inject documents or
+/// arrays into the [keyset_filter], where they would become query operators.
+pub(crate) fn valid_keyset_values(keys: &[KeysetKey], values: &[Bson]) -> bool {
+    keys.len() == values.len()
+        && keys
+            .iter()
+            .zip(values)
+            .all(|(key, value)| key.accepts(value))
+}
+
+/// Get a page of objects of type `T` with provided filter [Document].
+///
+/// The objects are sorted ascending by the provided `keys`, which must uniquely identify an object
+/// (e.g. by having `_id` as the last key). Pages are found by comparing the keys with the cursor
+/// values, so no documents are skipped on the database side.
+///
+/// If neither `first` nor `last` are provided, `default_size` is used as page size.
+pub(crate) async fn get_page_by_keyset<T>(
+    db: &qm::mongodb::Database,
+    collection: &str,
+    filter: Document,
+    keys: &[KeysetKey],
+    args: PageArgs,
+    default_size: usize,
+) -> anyhow::Result<Page<T>>
+where
+    T: DeserializeOwned + Send + Sync,
+{
+    let col = db.collection::<Document>(collection);
+    let total_count = col.count_documents(filter.clone()).await?;
+
+    let mut conditions = vec![filter];
+    if let Some(after) = &args.after {
+        conditions.push(keyset_filter(keys, after, "$gt")?);
+    }
+    if let Some(before) = &args.before {
+        conditions.push(keyset_filter(keys, before, "$lt")?);
+    }
+    let filter = doc! { "$and": conditions };
+
+    // Paginate backwards only if `last` is requested without `first`
+    let backwards = args.first.is_none() && args.last.is_some();
+    let size = if backwards { args.last } else { args.first }.unwrap_or(default_size);
+    let direction = if backwards { -1 } else { 1 };
+    let sort: Document = keys
+        .iter()
+        .map(|k| (k.field().to_string(), Bson::Int32(direction)))
+        .collect();
+
+    // Fetch one additional document to know whether there are more
+    let mut docs: Vec<Document> = col
+        .find(filter)
+        .sort(sort)
+        .limit(i64::try_from(size)?.saturating_add(1))
+        .await?
+        .try_collect()
+        .await?;
+    let has_more = docs.len() > size;
+    docs.truncate(size);
+
+    let (mut has_previous_page, has_next_page) = if backwards {
+        docs.reverse();
+        (has_more, args.before.is_some())
+    } else {
+        (args.after.is_some(), has_more)
+    };
+
+    // Slice the end of the page if both `first` and `last` were provided
+    if let (false, Some(last)) = (backwards, args.last) {
+        if docs.len() > last {
+            docs.drain(..docs.len() - last);
+            has_previous_page = true;
+        }
+    }
+
+    let items = docs
+        .into_iter()
+        .map(|doc| {
+            let values = keys
+                .iter()
+                .map(|k| doc.get(k.field()).cloned().unwrap_or(Bson::Null))
+                .collect();
+            Ok((values, bson::from_document(doc)?))
+        })
+        .collect::<anyhow::Result<Vec<(Vec<Bson>, T)>>>()?;
+
+    Ok(Page {
+        items,
+        has_previous_page,
+        has_next_page,
+        total_count,
+    })
+}
+
+/// Create a filter [Document] which matches all documents after (`$gt`) or before (`$lt`) the
+/// provided keyset values.
+///
+/// Fails if the values do not match the keys, see [valid_keyset_values].
+fn keyset_filter(keys: &[KeysetKey], values: &[Bson], operator: &str) -> anyhow::Result<Document> {
+    if !valid_keyset_values(keys, values) {
+        return Err(anyhow!(
+            "Invalid keyset values {values:?} for keys {keys:?}"
+        ));
+    }
+
+    // For keys `[a, b]` this results in `{a > x} OR {a = x AND b > y}`
+    let branches: Vec<Document> = (0..keys.len())
+        .map(|i| {
+            let mut branch: Document = keys[..i]
+                .iter()
+                .zip(values)
+                .map(|(k, v)| (k.field().to_string(), v.clone()))
+                .collect();
+            branch.insert(keys[i].field(), doc! { operator: values[i].clone() });
+            branch
+        })
+        .collect();
+
+    Ok(doc! { "$or": branches })
+}
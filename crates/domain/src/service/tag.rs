@@ -0,0 +1,218 @@
+//! Service functions specific to [Tags](crate::model::tag::Tag).
+
+use super::existing_ids;
+use super::get_many_by_filter;
+use super::get_one_by_id;
+use super::inserted_object_id;
+use super::todo::publish_changed_todos;
+use crate::app::App;
+use crate::db::collections::TAGS;
+use crate::db::collections::TODOS;
+use crate::error::DomainError;
+use crate::event::DomainEvent;
+use crate::model::tag::CreateTagInput;
+use crate::model::tag::Tag;
+use crate::model::tag::TagRemovalMode;
+use crate::model::tag::UpdateTagInput;
+use crate::owner::OwnerScope;
+use bson::doc;
+use bson::oid::ObjectId;
+use bson::DateTime;
+use bson::Document;
+use std::collections::HashSet;
+
+/// Create a new [Tag] in the [OwnerScope].
+pub(crate) async fn create_tag(
+    app: &App,
+    scope: &OwnerScope,
+    input: CreateTagInput,
+) -> Result<Tag, DomainError> {
+    let db = app.db().get();
+    let result = db
+        .collection::<Tag>(TAGS)
+        .insert_one(Tag::new(input, scope.owner()))
+        .await?;
+    let id = inserted_object_id(&result.inserted_id)?;
+
+    let tag: Tag = get_one_by_id(&db, TAGS, &id).await?.ok_or_else(|| {
+        DomainError::Internal(format!("the inserted tag should exist for id '{id}'"))
+    })?;
+    app.events().publish(DomainEvent::TagChanged(tag.clone()));
+
+    Ok(tag)
+}
+
+/// Update an existing [Tag] of the [OwnerScope].
+pub(crate) async fn update_tag(
+    app: &App,
+    scope: &OwnerScope,
+    input: &UpdateTagInput,
+) -> Result<Tag, DomainError> {
+    let db = app.db().get();
+    let result = db
+        .collection::<Tag>(TAGS)
+        .update_one(scope.filter(doc! { "_id": &input.id }), input)
+        .await?;
+
+    if result.matched_count == 0 {
+        return Err(tag_not_found(&input.id));
+    }
+    if result.modified_count != 1 {
+        tracing::warn!(
+            "Unexpected modified count of '{}' for tag update with id '{}'",
+            &result.modified_count,
+            &input.id
+        )
+    }
+
+    let tag: Tag = get_one_by_id(&db, TAGS, &input.id)
+        .await?
+        .ok_or_else(|| tag_not_found(&input.id))?;
+    app.events().publish(DomainEvent::TagChanged(tag.clone()));
+
+    Ok(tag)
+}
+
+/// Delete the [Tags](Tag) of the [OwnerScope] with the `ids` and handle the todos referencing them
+/// with [TagRemovalMode].
+///
+/// Publishes a [DomainEvent::TagRemoved] for each deleted tag and a [DomainEvent::TodoChanged] for
+/// each todo which referenced one. Returns the count of deleted [Tags](Tag).
+pub(crate) async fn remove_tags_by_id(
+    app: &App,
+    scope: &OwnerScope,
+    ids: &[ObjectId],
+    mode: TagRemovalMode,
+) -> Result<u64, DomainError> {
+    let db = app.db().get();
+    let tags: Vec<Tag> =
+        get_many_by_filter(&db, TAGS, scope.filter(doc! { "_id": { "$in": ids } })).await?;
+    let tag_ids: Vec<ObjectId> = tags.iter().filter_map(Tag::object_id).collect();
+    let todo_ids = existing_ids(&db, TODOS, doc! { "tags": { "$in": &tag_ids } }).await?;
+    let deleted_count = remove_tags(app.db(), &tag_ids, mode).await?;
+
+    for tag in tags {
+        app.events().publish(DomainEvent::TagRemoved(tag));
+    }
+    publish_changed_todos(app, &db, &todo_ids).await?;
+
+    Ok(deleted_count)
+}
+
+/// Get the [DomainError::NotFound] for a [Tag] id.
+fn tag_not_found(id: &ObjectId) -> DomainError {
+    DomainError::NotFound(format!("No tag found for id '{id}'"))
+}
+
+/// Get the ids of all tags which do not exist in the [OwnerScope].
+pub(crate) async fn missing_tag_ids(
+    db: &qm::mongodb::Database,
+    scope: &OwnerScope,
+    ids: &[ObjectId],
+) -> anyhow::Result<Vec<ObjectId>> {
+    let ids: HashSet<&ObjectId> = ids.iter().collect();
+    let existing: HashSet<ObjectId> = db
+        .collection::<Document>(TAGS)
+        .distinct(
+            "_id",
+            scope.filter(doc! { "_id": { "$in": ids.iter().collect::<Vec<_>>() } }),
+        )
+        .await?
+        .iter()
+        .filter_map(|id| id.as_object_id())
+        .collect();
+
+    Ok(ids
+        .into_iter()
+        .filter(|id| !existing.contains(id))
+        .copied()
+        .collect())
+}
+
+/// Validate that all tags with the provided ids exist in the [OwnerScope].
+///
+/// Returns a [DomainError::ValidationFailed] listing the missing ids otherwise.
+pub(crate) async fn validate_tag_ids(
+    db: &qm::mongodb::Database,
+    scope: &OwnerScope,
+    ids: &[ObjectId],
+) -> Result<(), DomainError> {
+    let missing = missing_tag_ids(db, scope, ids).await?;
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(DomainError::ValidationFailed(format!(
+            "No tags found for ids {}",
+            missing
+                .iter()
+                .map(|id| format!("'{id}'"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )))
+    }
+}
+
+/// Remove the tags with the provided ids and handle todos referencing them with [TagRemovalMode].
+///
+/// Runs in a transaction if the deployment supports it (replica set or sharded cluster). On a
+/// standalone MongoDB the operations are executed without a transaction.
+///
+/// Returns the count of deleted tags.
+async fn remove_tags(
+    db: &crate::db::DB,
+    ids: &[ObjectId],
+    mode: TagRemovalMode,
+) -> Result<u64, DomainError> {
+    let mut session = db.session().await?;
+    let transaction = match session.start_transaction().await {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::debug!("Removing tags without transaction: {e}");
+            false
+        }
+    };
+
+    let todos = db.get().collection::<Document>(TODOS);
+    let referenced = doc! { "tags": { "$in": ids } };
+    match mode {
+        TagRemovalMode::Pull => {
+            todos
+                .update_many(
+                    referenced,
+                    doc! {
+                        "$pull": { "tags": { "$in": ids } },
+                        "$set": { "modified": DateTime::now() },
+                    },
+                )
+                .session(&mut session)
+                .await?;
+        }
+        TagRemovalMode::Restrict => {
+            let cnt = todos
+                .count_documents(referenced)
+                .session(&mut session)
+                .await?;
+            if cnt > 0 {
+                if transaction {
+                    session.abort_transaction().await?;
+                }
+                return Err(DomainError::Conflict(format!(
+                    "The tags are still used by {cnt} todos"
+                )));
+            }
+        }
+    }
+
+    let result = db
+        .get()
+        .collection::<Document>(TAGS)
+        .delete_many(doc! { "_id": { "$in": ids } })
+        .session(&mut session)
+        .await?;
+
+    if transaction {
+        session.commit_transaction().await?;
+    }
+
+    Ok(result.deleted_count)
+}
@@ -0,0 +1,509 @@
+//! Service functions specific to [Todos](crate::model::todo::Todo).
+//!
+//! The public functions are shared by the GraphQL and the REST API.
+
+use super::existing_ids;
+use super::get_many_by_filter;
+use super::get_many_by_filter_and_sort;
+use super::get_one_by_filter;
+use super::get_one_by_id;
+use super::inserted_object_id;
+use super::keyset_filter;
+use super::tag::validate_tag_ids;
+use super::KeysetKey;
+use crate::app::App;
+use crate::db::collections::TAGS;
+use crate::db::collections::TODOS;
+use crate::error::DomainError;
+use crate::event::DomainEvent;
+use crate::model::filter::DateTimeRange;
+use crate::model::todo::stored_order;
+use crate::model::todo::CreateTodoInput;
+use crate::model::todo::Todo;
+use crate::model::todo::TodoFilter;
+use crate::model::todo::TodoPosition;
+use crate::model::todo::TodoSort;
+use crate::model::todo::UpdateTodoInput;
+use crate::owner::OwnerScope;
+use anyhow::anyhow;
+use async_graphql::futures_util::TryStreamExt;
+use async_graphql::MaybeUndefined;
+use bson::doc;
+use bson::oid::ObjectId;
+use bson::Bson;
+use bson::DateTime;
+use bson::Document;
+use bson::Regex;
+
+/// The gap between the orders of neighbouring todos.
+///
+/// New todos are appended with this gap and renormalisation restores it for all todos, so moving a
+/// todo only has to update the moved todo until the gap at a position is used up.
+pub(crate) const ORDER_GAP: u64 = 1 << 16;
+
+/// Translate a [TodoFilter] into a MongoDB filter [Document] restricted to the [OwnerScope].
+///
+/// Tag names are resolved to tag ids, which requires a database lookup.
+pub(crate) async fn todo_filter(
+    db: &qm::mongodb::Database,
+    scope: &OwnerScope,
+    filter: &TodoFilter,
+) -> anyhow::Result<Document> {
+    let mut conditions: Vec<Document> = vec![];
+
+    if let Some(completed) = filter.completed {
+        conditions.push(doc! { "completed": completed });
+    }
+    if let Some(tag_ids) = &filter.tag_ids {
+        conditions.push(doc! { "tags": { "$in": tag_ids } });
+    }
+    if let Some(tag_names) = &filter.tag_names {
+        let tags: Vec<Document> = get_many_by_filter(
+            db,
+            TAGS,
+            scope.filter(doc! { "name": { "$in": tag_names } }),
+        )
+        .await?;
+        let tag_ids: Vec<ObjectId> = tags
+            .iter()
+            .filter_map(|tag| tag.get_object_id("_id").ok())
+            .collect();
+        conditions.push(doc! { "tags": { "$in": tag_ids } });
+    }
+    if let Some(created) = &filter.created {
+        conditions.push(date_time_range_filter("created", created));
+    }
+    if let Some(modified) = &filter.modified {
+        conditions.push(date_time_range_filter("modified", modified));
+    }
+    if let Some(title) = &filter.title_contains {
+        conditions.push(doc! {
+            "title": Regex {
+                pattern: escape_regex(title),
+                options: String::from("i"),
+            }
+        });
+    }
+
+    Ok(scope.filter(if conditions.is_empty() {
+        doc! {}
+    } else {
+        doc! { "$and": conditions }
+    }))
+}
+
+/// Translate a [TodoSort] into a MongoDB sort [Document].
+///
+/// The `_id` is always added as last sort key to get a stable order.
+pub(crate) fn todo_sort(sort: &TodoSort) -> Document {
+    doc! {
+        sort.field.field(): sort.direction.value(),
+        "_id": sort.direction.value(),
+    }
+}
+
+/// Create a filter [Document] for an inclusive [DateTimeRange] on `field`.
+fn date_time_range_filter(field: &str, range: &DateTimeRange) -> Document {
+    let mut bounds = doc! {};
+    if let Some(from) = range.from {
+        bounds.insert("$gte", from);
+    }
+    if let Some(to) = range.to {
+        bounds.insert("$lte", to);
+    }
+
+    if bounds.is_empty() {
+        doc! {}
+    } else {
+        doc! { field: bounds }
+    }
+}
+
+/// Escape all characters with a special meaning in a regular expression.
+fn escape_regex(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if "\\^$.|?*+()[]{}".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Get the order for a todo appended after all other todos of the [OwnerScope].
+pub(crate) async fn next_todo_order(
+    db: &qm::mongodb::Database,
+    scope: &OwnerScope,
+) -> anyhow::Result<u64> {
+    let last = db
+        .collection::<Document>(TODOS)
+        .find_one(scope.filter(doc! {}))
+        .sort(doc! { "order": -1, "_id": -1 })
+        .await?;
+
+    Ok(match last {
+        Some(todo) => order_of(&todo)?.saturating_add(ORDER_GAP),
+        None => ORDER_GAP,
+    })
+}
+
+/// Get the order to place the todo with `id` at the requested [TodoPosition].
+///
+/// The order is chosen in the middle between the neighbouring todos at the position. If there is no
+/// gap left between them, the orders of all todos of the [OwnerScope] are renormalised first.
+///
+/// This is the only renormalisation: the gaps are only used up by repeated moves into the same gap,
+/// so renormalising on demand avoids a periodic job, which every server instance would run.
+///
+/// Returns [None] if the todo referenced by the position does not exist in the [OwnerScope].
+pub(crate) async fn todo_order_at(
+    db: &qm::mongodb::Database,
+    scope: &OwnerScope,
+    id: &ObjectId,
+    position: &TodoPosition,
+) -> anyhow::Result<Option<u64>> {
+    let (TodoPosition::Before(anchor_id) | TodoPosition::After(anchor_id)) = position;
+    for _ in 0..2 {
+        let Some((lower, upper)) = neighbour_orders(db, scope, id, anchor_id, position).await?
+        else {
+            return Ok(None);
+        };
+        let upper = upper.unwrap_or(lower.saturating_add(2 * ORDER_GAP));
+        if upper.saturating_sub(lower) >= 2 {
+            return Ok(Some(lower + (upper - lower) / 2));
+        }
+
+        tracing::info!("No order gap left between {lower} and {upper}, renormalising todo orders");
+        renormalize_todo_orders(db, scope).await?;
+    }
+
+    Err(anyhow!(
+        "No order gap found after renormalising todo orders"
+    ))
+}
+
+/// Renormalise the orders of all todos of the [OwnerScope].
+///
+/// Keeps the current sequence, but sets the orders to multiples of [ORDER_GAP]. This runs as a
+/// single aggregation on the database, which requires MongoDB 5.0 or later for the
+/// `$setWindowFields` stage.
+pub(crate) async fn renormalize_todo_orders(
+    db: &qm::mongodb::Database,
+    scope: &OwnerScope,
+) -> anyhow::Result<()> {
+    let gap = i64::try_from(ORDER_GAP)?;
+    let pipeline = vec![
+        doc! { "$match": scope.filter(doc! {}) },
+        doc! { "$setWindowFields": {
+            "sortBy": { "order": 1, "_id": 1 },
+            "output": { "rank": { "$documentNumber": {} } },
+        } },
+        doc! { "$project": { "order": { "$multiply": ["$rank", gap] } } },
+        doc! { "$merge": {
+            "into": TODOS,
+            "on": "_id",
+            "whenMatched": "merge",
+            "whenNotMatched": "discard",
+        } },
+    ];
+    db.collection::<Document>(TODOS)
+        .aggregate(pipeline)
+        .await?
+        .try_collect::<Vec<Document>>()
+        .await?;
+
+    Ok(())
+}
+
+/// Get the orders of the todos directly before and after the requested [TodoPosition].
+///
+/// The lower order is `0` if there is no todo before the position and the upper order is [None] if
+/// there is no todo after it. The todo with `id` itself is ignored.
+///
+/// Returns [None] if the todo with `anchor_id` does not exist in the [OwnerScope].
+async fn neighbour_orders(
+    db: &qm::mongodb::Database,
+    scope: &OwnerScope,
+    id: &ObjectId,
+    anchor_id: &ObjectId,
+    position: &TodoPosition,
+) -> anyhow::Result<Option<(u64, Option<u64>)>> {
+    let col = db.collection::<Document>(TODOS);
+    let Some(anchor) = col
+        .find_one(scope.filter(doc! { "_id": anchor_id }))
+        .await?
+    else {
+        return Ok(None);
+    };
+    let anchor_order = order_of(&anchor)?;
+    let keys = [KeysetKey::Order, KeysetKey::Id];
+    let values = [
+        anchor.get("order").cloned().unwrap_or(Bson::Null),
+        Bson::ObjectId(*anchor_id),
+    ];
+
+    Ok(Some(match position {
+        TodoPosition::Before(_) => {
+            let filter = scope.filter(doc! { "$and": [
+                { "_id": { "$ne": id } },
+                keyset_filter(&keys, &values, "$lt")?,
+            ] });
+            let previous = col
+                .find_one(filter)
+                .sort(doc! { "order": -1, "_id": -1 })
+                .await?;
+            let lower = previous.as_ref().map(order_of).transpose()?.unwrap_or(0);
+            (lower, Some(anchor_order))
+        }
+        TodoPosition::After(_) => {
+            let filter = scope.filter(doc! { "$and": [
+                { "_id": { "$ne": id } },
+                keyset_filter(&keys, &values, "$gt")?,
+            ] });
+            let next = col
+                .find_one(filter)
+                .sort(doc! { "order": 1, "_id": 1 })
+                .await?;
+            (anchor_order, next.as_ref().map(order_of).transpose()?)
+        }
+    }))
+}
+
+/// Get the order of a todo [Document].
+fn order_of(todo: &Document) -> anyhow::Result<u64> {
+    let order = match todo.get("order") {
+        Some(Bson::Int32(order)) => i64::from(*order),
+        Some(Bson::Int64(order)) => *order,
+        _ => return Err(anyhow!("The todo order should be an integer")),
+    };
+    u64::try_from(order).map_err(|e| e.into())
+}
+
+/// Get the [Todos](Todo) of the [OwnerScope], ordered by their order.
+pub async fn list_todos(app: &App, scope: &OwnerScope) -> Result<Vec<Todo>, DomainError> {
+    Ok(get_many_by_filter_and_sort(
+        &app.db().get(),
+        TODOS,
+        scope.filter(doc! {}),
+        doc! { "order": 1, "_id": 1 },
+    )
+    .await?)
+}
+
+/// Get the [Todo] with the `id` in the [OwnerScope].
+pub async fn get_todo(app: &App, scope: &OwnerScope, id: &ObjectId) -> Result<Todo, DomainError> {
+    get_one_by_filter(&app.db().get(), TODOS, scope.filter(doc! { "_id": id }))
+        .await?
+        .ok_or_else(|| todo_not_found(id))
+}
+
+/// Create a new [Todo] in the [OwnerScope], which is appended after all other todos unless the
+/// input has an order.
+pub async fn create_todo(
+    app: &App,
+    scope: &OwnerScope,
+    input: CreateTodoInput,
+) -> Result<Todo, DomainError> {
+    let db = app.db().get();
+    if let Some(tags) = &input.tags {
+        validate_tag_ids(&db, scope, tags).await?;
+    }
+    let order = match input.order {
+        Some(order) => order,
+        None => next_todo_order(&db, scope).await?,
+    };
+    let result = db
+        .collection::<Todo>(TODOS)
+        .insert_one(Todo::new(input, order, scope.owner()))
+        .await?;
+    let id = inserted_object_id(&result.inserted_id)?;
+
+    let todo: Todo = get_one_by_id(&db, TODOS, &id).await?.ok_or_else(|| {
+        DomainError::Internal(format!("the inserted todo should exist for id '{id}'"))
+    })?;
+    app.events().publish(DomainEvent::TodoChanged(todo.clone()));
+
+    Ok(todo)
+}
+
+/// Update an existing [Todo] of the [OwnerScope].
+pub async fn update_todo(
+    app: &App,
+    scope: &OwnerScope,
+    input: &UpdateTodoInput,
+) -> Result<Todo, DomainError> {
+    let db = app.db().get();
+    if let MaybeUndefined::Value(tags) = &input.tags {
+        validate_tag_ids(&db, scope, tags).await?;
+    }
+    let result = db
+        .collection::<Todo>(TODOS)
+        .update_one(scope.filter(doc! { "_id": &input.id }), input)
+        .await?;
+
+    if result.matched_count == 0 {
+        return Err(todo_not_found(&input.id));
+    }
+    if result.modified_count != 1 {
+        tracing::warn!(
+            "Unexpected modified count of '{}' for todo update with id '{}'",
+            &result.modified_count,
+            &input.id
+        )
+    }
+
+    let todo: Todo = get_one_by_id(&db, TODOS, &input.id)
+        .await?
+        .ok_or_else(|| todo_not_found(&input.id))?;
+    app.events().publish(DomainEvent::TodoChanged(todo.clone()));
+
+    Ok(todo)
+}
+
+/// Set the `order` of an existing [Todo] of the [OwnerScope].
+async fn set_todo_order(
+    app: &App,
+    scope: &OwnerScope,
+    id: &ObjectId,
+    order: u64,
+) -> Result<Todo, DomainError> {
+    let db = app.db().get();
+    let order = stored_order(order)?;
+    let result = db
+        .collection::<Todo>(TODOS)
+        .update_one(
+            scope.filter(doc! { "_id": id }),
+            doc! { "$set": { "order": order, "modified": DateTime::now() } },
+        )
+        .await?;
+    if result.matched_count == 0 {
+        return Err(todo_not_found(id));
+    }
+
+    let todo: Todo = get_one_by_id(&db, TODOS, id)
+        .await?
+        .ok_or_else(|| todo_not_found(id))?;
+    app.events().publish(DomainEvent::TodoChanged(todo.clone()));
+
+    Ok(todo)
+}
+
+/// Move an existing [Todo] of the [OwnerScope] to the `position`.
+///
+/// Only the moved [Todo] is updated, unless the orders of all [Todos](Todo) must be renormalised
+/// to make room at the position.
+pub(crate) async fn move_todo(
+    app: &App,
+    scope: &OwnerScope,
+    id: &ObjectId,
+    position: &TodoPosition,
+) -> Result<Todo, DomainError> {
+    let db = app.db().get();
+    let (TodoPosition::Before(anchor_id) | TodoPosition::After(anchor_id)) = position;
+    if anchor_id == id {
+        return Err(DomainError::ValidationFailed(String::from(
+            "A todo can not be moved relative to itself",
+        )));
+    }
+    if get_one_by_filter::<Document>(&db, TODOS, scope.filter(doc! { "_id": id }))
+        .await?
+        .is_none()
+    {
+        return Err(todo_not_found(id));
+    }
+    let Some(order) = todo_order_at(&db, scope, id, position).await? else {
+        return Err(todo_not_found(anchor_id));
+    };
+
+    set_todo_order(app, scope, id, order).await
+}
+
+/// Set the completion state of all [Todos](Todo) of the [OwnerScope].
+///
+/// Returns the [Todos](Todo) which changed their completion state.
+pub(crate) async fn toggle_all_todos(
+    app: &App,
+    scope: &OwnerScope,
+    completed: bool,
+) -> Result<Vec<Todo>, DomainError> {
+    let db = app.db().get();
+    let ids = existing_ids(&db, TODOS, scope.filter(doc! { "completed": !completed })).await?;
+
+    db.collection::<Todo>(TODOS)
+        .update_many(
+            doc! { "_id": { "$in": &ids }, "completed": !completed },
+            doc! { "$set": { "completed": completed, "modified": DateTime::now() } },
+        )
+        .await?;
+
+    publish_changed_todos(app, &db, &ids).await
+}
+
+/// Delete the [Todos](Todo) of the [OwnerScope] with the `ids`.
+///
+/// Returns the count of deleted [Todos](Todo).
+pub async fn remove_todos_by_id(
+    app: &App,
+    scope: &OwnerScope,
+    ids: &[ObjectId],
+) -> Result<u64, DomainError> {
+    remove_todos(app, scope.filter(doc! { "_id": { "$in": ids } })).await
+}
+
+/// Delete all completed [Todos](Todo) of the [OwnerScope].
+///
+/// Returns the count of deleted [Todos](Todo).
+pub(crate) async fn clear_completed_todos(
+    app: &App,
+    scope: &OwnerScope,
+) -> Result<u64, DomainError> {
+    remove_todos(app, scope.filter(doc! { "completed": true })).await
+}
+
+/// Delete all [Todos](Todo) of the [OwnerScope].
+///
+/// Returns the count of deleted [Todos](Todo).
+pub async fn remove_all_todos(app: &App, scope: &OwnerScope) -> Result<u64, DomainError> {
+    remove_todos(app, scope.filter(doc! {})).await
+}
+
+/// Delete the [Todos](Todo) matching the filter and publish a [DomainEvent::TodoRemoved] for each.
+///
+/// Returns the count of deleted [Todos](Todo).
+async fn remove_todos(app: &App, filter: Document) -> Result<u64, DomainError> {
+    let db = app.db().get();
+    let todos: Vec<Todo> = get_many_by_filter(&db, TODOS, filter).await?;
+    let ids: Vec<ObjectId> = todos.iter().filter_map(Todo::object_id).collect();
+    let result = db
+        .collection::<Todo>(TODOS)
+        .delete_many(doc! { "_id": { "$in": &ids } })
+        .await?;
+    for todo in todos {
+        app.events().publish(DomainEvent::TodoRemoved(todo));
+    }
+
+    Ok(result.deleted_count)
+}
+
+/// Get the [Todos](Todo) with the provided ids and publish a [DomainEvent::TodoChanged] for each.
+pub(crate) async fn publish_changed_todos(
+    app: &App,
+    db: &qm::mongodb::Database,
+    ids: &[ObjectId],
+) -> Result<Vec<Todo>, DomainError> {
+    if ids.is_empty() {
+        return Ok(vec![]);
+    }
+    let todos: Vec<Todo> = get_many_by_filter(db, TODOS, doc! { "_id": { "$in": ids } }).await?;
+    for todo in &todos {
+        app.events().publish(DomainEvent::TodoChanged(todo.clone()));
+    }
+
+    Ok(todos)
+}
+
+/// Get the [DomainError::NotFound] for a [Todo] id.
+pub fn todo_not_found(id: &ObjectId) -> DomainError {
+    DomainError::NotFound(format!("No todo found for id '{id}'"))
+}
@@ -0,0 +1,199 @@
+//! Server-side sessions of the secured endpoint.
+//!
+//! Every browser gets its own [Session], which is identified by a random id in a cookie. The tokens
+//! of the session are only stored in the database and never leave the server.
+
+use crate::db::collections::SESSIONS;
+use bson::doc;
+use bson::DateTime;
+use qm::mongodb::options::ReturnDocument;
+use rand::RngCore;
+use serde::Deserialize;
+use serde::Serialize;
+use std::time::Duration;
+
+/// The byte length of the random session ids.
+const SESSION_ID_LENGTH: usize = 32;
+
+/// The time before the expiry of the access token from which on it should be refreshed.
+const REFRESH_MARGIN: Duration = Duration::from_secs(30);
+
+/// The tokens of a token response to store in a [Session].
+#[derive(Clone, Debug)]
+pub struct SessionTokens {
+    /// The access token.
+    pub access_token: String,
+    /// The time until the access token expires.
+    pub expires_in: Duration,
+    /// The refresh token, if the response contained one.
+    pub refresh_token: Option<String>,
+    /// The time until the refresh token expires, if it expires at all.
+    pub refresh_expires_in: Option<Duration>,
+    /// The ID token, if the response contained one.
+    pub id_token: Option<String>,
+}
+
+/// A session of a signed-in user.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Session {
+    #[serde(rename = "_id")]
+    id: String,
+    access_token: String,
+    access_token_expires: DateTime,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    refresh_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id_token: Option<String>,
+    created: DateTime,
+    expires: DateTime,
+}
+
+impl Session {
+    /// Get the id.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Get the access token.
+    pub fn access_token(&self) -> &str {
+        &self.access_token
+    }
+
+    /// Get the refresh token.
+    pub fn refresh_token(&self) -> Option<&str> {
+        self.refresh_token.as_deref()
+    }
+
+    /// Get the ID token, which identifies the session at the provider on logout.
+    pub fn id_token(&self) -> Option<&str> {
+        self.id_token.as_deref()
+    }
+
+    /// Get the time until the session expires.
+    pub fn expires_in(&self) -> Duration {
+        until(self.expires)
+    }
+
+    /// Check if the access token expires soon and the session has a refresh token to refresh it.
+    pub fn needs_refresh(&self) -> bool {
+        self.refresh_token.is_some() && until(self.access_token_expires) <= REFRESH_MARGIN
+    }
+}
+
+/// Get the time until `date_time`, which is zero if it already passed.
+fn until(date_time: DateTime) -> Duration {
+    Duration::from_millis(
+        u64::try_from(date_time.timestamp_millis() - DateTime::now().timestamp_millis())
+            .unwrap_or(0),
+    )
+}
+
+/// Get the [DateTime] `duration` after `date_time`.
+fn after(date_time: DateTime, duration: Duration) -> anyhow::Result<DateTime> {
+    Ok(DateTime::from_millis(
+        date_time.timestamp_millis() + i64::try_from(duration.as_millis())?,
+    ))
+}
+
+/// Get the expiry of a session with the `tokens`, issued at `issued`.
+///
+/// A session with a refresh token lives as long as the refresh token, otherwise only as long as the
+/// access token. Refresh tokens without expiry (offline tokens) are bounded by the access token
+/// expiry, so the session is not kept forever.
+fn session_expires(issued: DateTime, tokens: &SessionTokens) -> anyhow::Result<DateTime> {
+    let lifetime = match (&tokens.refresh_token, tokens.refresh_expires_in) {
+        (Some(_), Some(refresh_expires_in)) => refresh_expires_in.max(tokens.expires_in),
+        _ => tokens.expires_in,
+    };
+    after(issued, lifetime)
+}
+
+/// The store for [Sessions](Session) in the database.
+///
+/// Expired sessions are removed by the database.
+#[derive(Clone)]
+pub struct SessionStore {
+    db: crate::db::DB,
+}
+
+impl SessionStore {
+    /// Construct a new [SessionStore].
+    pub(crate) fn new(db: crate::db::DB) -> Self {
+        Self { db }
+    }
+
+    /// Create a new [Session] with the `tokens`.
+    pub async fn create(&self, tokens: SessionTokens) -> anyhow::Result<Session> {
+        let mut id = [0_u8; SESSION_ID_LENGTH];
+        rand::thread_rng().fill_bytes(&mut id);
+        let created = DateTime::now();
+        let session = Session {
+            id: hex::encode(id),
+            access_token_expires: after(created, tokens.expires_in)?,
+            expires: session_expires(created, &tokens)?,
+            access_token: tokens.access_token,
+            refresh_token: tokens.refresh_token,
+            id_token: tokens.id_token,
+            created,
+        };
+        self.db
+            .get()
+            .collection::<Session>(SESSIONS)
+            .insert_one(&session)
+            .await?;
+
+        Ok(session)
+    }
+
+    /// Get the [Session] with the `id`, if it exists and is not expired.
+    pub async fn get(&self, id: &str) -> anyhow::Result<Option<Session>> {
+        Ok(self
+            .db
+            .get()
+            .collection::<Session>(SESSIONS)
+            .find_one(doc! { "_id": id, "expires": { "$gt": DateTime::now() } })
+            .await?)
+    }
+
+    /// Replace the tokens of the [Session] with the `id` by the refreshed `tokens`.
+    ///
+    /// The refresh token and ID token are kept, if the `tokens` do not contain new ones. Returns
+    /// the updated session or [None] if it does not exist anymore.
+    pub async fn refresh(
+        &self,
+        id: &str,
+        tokens: SessionTokens,
+    ) -> anyhow::Result<Option<Session>> {
+        let now = DateTime::now();
+        let mut update = doc! {
+            "access_token": &tokens.access_token,
+            "access_token_expires": after(now, tokens.expires_in)?,
+            "expires": session_expires(now, &tokens)?,
+        };
+        if let Some(refresh_token) = &tokens.refresh_token {
+            update.insert("refresh_token", refresh_token);
+        }
+        if let Some(id_token) = &tokens.id_token {
+            update.insert("id_token", id_token);
+        }
+
+        Ok(self
+            .db
+            .get()
+            .collection::<Session>(SESSIONS)
+            .find_one_and_update(doc! { "_id": id }, doc! { "$set": update })
+            .return_document(ReturnDocument::After)
+            .await?)
+    }
+
+    /// Remove the [Session] with the `id`.
+    pub async fn remove(&self, id: &str) -> anyhow::Result<()> {
+        self.db
+            .get()
+            .collection::<Session>(SESSIONS)
+            .delete_one(doc! { "_id": id })
+            .await?;
+
+        Ok(())
+    }
+}
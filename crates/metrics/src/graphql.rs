@@ -0,0 +1,184 @@
+//! The async-graphql extension, which records metrics of operations and resolvers.
+
+use crate::GRAPHQL_OPERATIONS;
+use crate::GRAPHQL_OPERATION_DURATION;
+use crate::GRAPHQL_OPERATION_ERRORS;
+use crate::GRAPHQL_RESOLVER_DURATION;
+use crate::GRAPHQL_RESOLVER_ERRORS;
+use async_graphql::extensions::Extension;
+use async_graphql::extensions::ExtensionContext;
+use async_graphql::extensions::ExtensionFactory;
+use async_graphql::extensions::NextExecute;
+use async_graphql::extensions::NextParseQuery;
+use async_graphql::extensions::NextResolve;
+use async_graphql::extensions::ResolveInfo;
+use async_graphql::parser::types::DocumentOperations;
+use async_graphql::parser::types::ExecutableDocument;
+use async_graphql::Response;
+use async_graphql::ServerResult;
+use async_graphql::Value;
+use async_graphql::Variables;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::Instant;
+
+/// The default number of distinct operation names recorded per schema.
+pub const DEFAULT_OPERATION_NAME_LIMIT: usize = 50;
+
+/// The operation label of operations without a name.
+pub const ANONYMOUS_OPERATION: &str = "anonymous";
+
+/// The operation label of the operations, whose names exceed the limit.
+pub const OTHER_OPERATION: &str = "other";
+
+/// Records the durations and errors of the executed operations and of the resolvers of a schema.
+///
+/// Operations are recorded by schema and operation name. The names are chosen by the clients, so
+/// only the first [distinct names](GraphQLMetrics::with_operation_name_limit) get their own label
+/// value and all others are recorded as [OTHER_OPERATION]. Resolvers are recorded by the parent
+/// type and field name of the schema, e.g. `Todo` and `tags`. Introspection is not recorded.
+pub struct GraphQLMetrics {
+    schema: &'static str,
+    operation_names: Arc<OperationNames>,
+}
+
+impl GraphQLMetrics {
+    /// Construct a new [GraphQLMetrics] extension with the `schema` name for the metric labels,
+    /// which records up to [DEFAULT_OPERATION_NAME_LIMIT] operation names.
+    pub fn new(schema: &'static str) -> Self {
+        Self {
+            schema,
+            operation_names: Arc::new(OperationNames::new(DEFAULT_OPERATION_NAME_LIMIT)),
+        }
+    }
+
+    /// Set the number of distinct operation names, which are recorded with their own label value.
+    pub fn with_operation_name_limit(mut self, limit: usize) -> Self {
+        self.operation_names = Arc::new(OperationNames::new(limit));
+        self
+    }
+}
+
+impl ExtensionFactory for GraphQLMetrics {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(GraphQLMetricsExtension {
+            schema: self.schema,
+            operation_names: self.operation_names.clone(),
+            document_operation: Mutex::new(None),
+        })
+    }
+}
+
+/// The operation names with their own label value.
+struct OperationNames {
+    limit: usize,
+    names: Mutex<HashSet<String>>,
+}
+
+impl OperationNames {
+    fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            names: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Get the label value of the operation with the `name`.
+    ///
+    /// A new name is added, as long as there are less names than the limit.
+    fn label(&self, name: Option<&str>) -> String {
+        let Some(name) = name else {
+            return ANONYMOUS_OPERATION.to_string();
+        };
+        let mut names = self.names.lock().unwrap_or_else(PoisonError::into_inner);
+        if names.contains(name) || (names.len() < self.limit && names.insert(name.to_string())) {
+            name.to_string()
+        } else {
+            OTHER_OPERATION.to_string()
+        }
+    }
+}
+
+/// The extension of a single request.
+struct GraphQLMetricsExtension {
+    schema: &'static str,
+    operation_names: Arc<OperationNames>,
+    /// The name of the only operation of the parsed document, if it has one.
+    document_operation: Mutex<Option<String>>,
+}
+
+#[async_trait::async_trait]
+impl Extension for GraphQLMetricsExtension {
+    async fn parse_query(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        query: &str,
+        variables: &Variables,
+        next: NextParseQuery<'_>,
+    ) -> ServerResult<ExecutableDocument> {
+        let document = next.run(ctx, query, variables).await?;
+        // Requests only need the operation name, if the document has multiple operations
+        if let DocumentOperations::Multiple(operations) = &document.operations {
+            if let [name] = operations.keys().collect::<Vec<_>>()[..] {
+                *self
+                    .document_operation
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner) = Some(name.to_string());
+            }
+        }
+        Ok(document)
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        operation_name: Option<&str>,
+        next: NextExecute<'_>,
+    ) -> Response {
+        let start = Instant::now();
+        let response = next.run(ctx, operation_name).await;
+
+        let document_operation = self
+            .document_operation
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        let operation = self
+            .operation_names
+            .label(operation_name.or(document_operation.as_deref()));
+        let labels = [self.schema, operation.as_str()];
+        GRAPHQL_OPERATIONS.with_label_values(&labels).inc();
+        GRAPHQL_OPERATION_DURATION
+            .with_label_values(&labels)
+            .observe(start.elapsed().as_secs_f64());
+        if response.is_err() {
+            GRAPHQL_OPERATION_ERRORS.with_label_values(&labels).inc();
+        }
+        response
+    }
+
+    async fn resolve(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        info: ResolveInfo<'_>,
+        next: NextResolve<'_>,
+    ) -> ServerResult<Option<Value>> {
+        if info.is_for_introspection {
+            return next.run(ctx, info).await;
+        }
+
+        let labels = [self.schema, info.parent_type, info.name];
+        let start = Instant::now();
+        let result = next.run(ctx, info).await;
+
+        GRAPHQL_RESOLVER_DURATION
+            .with_label_values(&labels)
+            .observe(start.elapsed().as_secs_f64());
+        if result.is_err() {
+            GRAPHQL_RESOLVER_ERRORS.with_label_values(&labels).inc();
+        }
+        result
+    }
+}
@@ -0,0 +1,132 @@
+//! Prometheus metrics of the application.
+//!
+//! The metrics are global, like the tracing subscriber, since the MongoDB command metrics are
+//! recorded by a [tracing layer](mongodb::MongoDbMetricsLayer), which is installed before the app
+//! is set up. They are registered in a private [Registry] on first use.
+//!
+//! All labels have a bounded set of values. The only values chosen by the clients are the GraphQL
+//! operation names, which are [limited per schema](graphql::GraphQLMetrics).
+
+pub mod graphql;
+pub mod mongodb;
+
+use prometheus::HistogramOpts;
+use prometheus::HistogramVec;
+use prometheus::IntCounterVec;
+use prometheus::Opts;
+use prometheus::Registry;
+use prometheus::TextEncoder;
+use std::sync::LazyLock;
+
+/// The upper bounds of the histogram buckets in seconds.
+const BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// The registry of all metrics.
+static REGISTRY: LazyLock<Registry> = LazyLock::new(Registry::new);
+
+/// The number of HTTP requests by method, matched route and status code.
+pub static HTTP_REQUESTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    counter(
+        "qgt_http_requests_total",
+        "The number of HTTP requests.",
+        &["method", "route", "status"],
+    )
+});
+
+/// The durations of HTTP requests by method and matched route.
+pub static HTTP_REQUEST_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    histogram(
+        "qgt_http_request_duration_seconds",
+        "The durations of HTTP requests in seconds.",
+        &["method", "route"],
+    )
+});
+
+/// The number of executed GraphQL operations by schema and operation name.
+pub static GRAPHQL_OPERATIONS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    counter(
+        "qgt_graphql_operations_total",
+        "The number of executed GraphQL operations.",
+        &["schema", "operation"],
+    )
+});
+
+/// The number of GraphQL operations with errors by schema and operation name.
+pub static GRAPHQL_OPERATION_ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    counter(
+        "qgt_graphql_operation_errors_total",
+        "The number of executed GraphQL operations with errors.",
+        &["schema", "operation"],
+    )
+});
+
+/// The durations of GraphQL operations by schema and operation name.
+pub static GRAPHQL_OPERATION_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    histogram(
+        "qgt_graphql_operation_duration_seconds",
+        "The durations of executed GraphQL operations in seconds.",
+        &["schema", "operation"],
+    )
+});
+
+/// The number of GraphQL resolver errors by schema, parent type and field.
+pub static GRAPHQL_RESOLVER_ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    counter(
+        "qgt_graphql_resolver_errors_total",
+        "The number of GraphQL resolver errors.",
+        &["schema", "type", "field"],
+    )
+});
+
+/// The durations of GraphQL resolvers by schema, parent type and field.
+pub static GRAPHQL_RESOLVER_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    histogram(
+        "qgt_graphql_resolver_duration_seconds",
+        "The durations of GraphQL resolvers in seconds.",
+        &["schema", "type", "field"],
+    )
+});
+
+/// The durations of MongoDB commands by command name and outcome.
+pub static MONGODB_COMMAND_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    histogram(
+        "qgt_mongodb_command_duration_seconds",
+        "The durations of MongoDB commands in seconds.",
+        &["command", "outcome"],
+    )
+});
+
+/// Encode all metrics in the Prometheus text format.
+pub fn encode() -> String {
+    TextEncoder::new()
+        .encode_to_string(&REGISTRY.gather())
+        .unwrap_or_else(|error| {
+            tracing::error!("Failed to encode the metrics: {error}");
+            String::new()
+        })
+}
+
+/// Construct a counter with the names of its `labels` and register it.
+fn counter(name: &str, help: &str, labels: &[&str]) -> IntCounterVec {
+    let counter =
+        IntCounterVec::new(Opts::new(name, help), labels).expect("the counter should be valid");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("the counter should only be registered once");
+    counter
+}
+
+/// Construct a histogram with the [BUCKETS] and the names of its `labels` and register it.
+fn histogram(name: &str, help: &str, labels: &[&str]) -> HistogramVec {
+    let histogram = HistogramVec::new(
+        HistogramOpts::new(name, help).buckets(BUCKETS.to_vec()),
+        labels,
+    )
+    .expect("the histogram should be valid");
+    REGISTRY
+        .register(Box::new(histogram.clone()))
+        .expect("the histogram should only be registered once");
+    histogram
+}
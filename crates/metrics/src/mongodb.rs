@@ -0,0 +1,44 @@
+//! The tracing layer, which records the durations of MongoDB commands.
+
+use crate::MONGODB_COMMAND_DURATION;
+use qgt_telemetry::mongodb::command_filter;
+use qgt_telemetry::mongodb::CommandEvent;
+use qgt_telemetry::mongodb::CommandStage;
+use tracing::Event;
+use tracing::Subscriber;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::Layer;
+
+/// Records the durations of the commands of the MongoDB client.
+///
+/// The layer reads the [CommandEvent] of every finished command, so it must be installed with the
+/// [MongoDbMetricsLayer::filter]. The driver reports durations in whole milliseconds.
+#[derive(Default)]
+pub struct MongoDbMetricsLayer;
+
+impl MongoDbMetricsLayer {
+    /// Get the filter, which only passes the command events of the MongoDB driver, see
+    /// [command_filter].
+    pub fn filter() -> Targets {
+        command_filter()
+    }
+}
+
+impl<S: Subscriber> Layer<S> for MongoDbMetricsLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let Some(command) = CommandEvent::read(event) else {
+            return;
+        };
+        let outcome = match command.stage {
+            CommandStage::Started => return,
+            CommandStage::Succeeded => "succeeded",
+            CommandStage::Failed => "failed",
+        };
+        if let (Some(name), Some(duration)) = (command.name, command.duration) {
+            MONGODB_COMMAND_DURATION
+                .with_label_values(&[name.as_str(), outcome])
+                .observe(duration.as_secs_f64());
+        }
+    }
+}
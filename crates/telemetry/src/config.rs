@@ -0,0 +1,53 @@
+use serde::Deserialize;
+
+/// The format of the log lines.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable lines.
+    #[default]
+    Text,
+    /// A JSON object per line with the fields of the event and its spans.
+    Json,
+}
+
+/// Telemetry configuration.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct TelemetryConfig {
+    log_format: Option<LogFormat>,
+    otlp_endpoint: Option<String>,
+    service_name: Option<String>,
+}
+
+impl TelemetryConfig {
+    const DEFAULT_SERVICE_NAME: &'static str = "qgt";
+
+    /// Get a telemetry config instance with values from env variables.
+    pub fn from_env() -> envy::Result<Self> {
+        Self::from_values(std::env::vars())
+    }
+
+    /// Get a telemetry config instance with the `values` of env variables by their names.
+    pub fn from_values(values: impl IntoIterator<Item = (String, String)>) -> envy::Result<Self> {
+        envy::prefixed("TELEMETRY_").from_iter(values)
+    }
+
+    /// Get the format of the log lines.
+    pub fn log_format(&self) -> LogFormat {
+        self.log_format.unwrap_or_default()
+    }
+
+    /// Get the base URL of the OTLP/HTTP collector, if traces are exported.
+    ///
+    /// The traces are sent to its `/v1/traces` path.
+    pub fn otlp_endpoint(&self) -> Option<&str> {
+        self.otlp_endpoint.as_deref()
+    }
+
+    /// Get the service name of the exported traces.
+    pub fn service_name(&self) -> &str {
+        self.service_name
+            .as_deref()
+            .unwrap_or(Self::DEFAULT_SERVICE_NAME)
+    }
+}
@@ -0,0 +1,107 @@
+//! OpenTelemetry tracing of the application.
+//!
+//! The spans of the `tracing` crate are exported with the [OtlpExporter], which is installed as a
+//! layer of the global tracing subscriber. Traces are continued from and propagated to other
+//! services with W3C `traceparent` headers, see [propagation].
+
+pub mod config;
+pub mod mongodb;
+pub mod propagation;
+
+use crate::config::TelemetryConfig;
+use crate::mongodb::MongoDbTracingLayer;
+use opentelemetry::trace::TraceError;
+use opentelemetry::trace::TracerProvider;
+use opentelemetry_otlp::SpanExporter;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::trace::Tracer;
+use opentelemetry_sdk::Resource;
+use tracing::Level;
+use tracing::Subscriber;
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::registry::LookupSpan;
+
+/// The targets whose spans are exported.
+///
+/// The spans of the HTTP clients are left out, since the exporter sends the traces with one.
+const TARGETS: [&str; 4] = ["qgt_server", "qgt_domain", "qgt_auth", "async_graphql"];
+
+/// Exports traces to an OpenTelemetry collector with OTLP/HTTP.
+///
+/// The spans are exported in batches from a background thread.
+pub struct OtlpExporter {
+    provider: SdkTracerProvider,
+    tracer: Tracer,
+}
+
+impl OtlpExporter {
+    /// Set up the exporter, if the [TelemetryConfig] has an OTLP endpoint.
+    ///
+    /// Also installs the W3C trace context propagator, so incoming traces are continued.
+    pub fn new(config: &TelemetryConfig) -> Result<Option<Self>, TraceError> {
+        let Some(endpoint) = config.otlp_endpoint() else {
+            return Ok(None);
+        };
+
+        let exporter = SpanExporter::builder()
+            .with_http()
+            .with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+            .build()?;
+
+        Ok(Some(Self::with_span_exporter(config, exporter)))
+    }
+
+    /// Set up the exporter with another [span exporter](opentelemetry_sdk::trace::SpanExporter)
+    /// than OTLP, e.g. to test the exported spans.
+    ///
+    /// Also installs the W3C trace context propagator, so incoming traces are continued.
+    pub fn with_span_exporter(
+        config: &TelemetryConfig,
+        exporter: impl opentelemetry_sdk::trace::SpanExporter + 'static,
+    ) -> Self {
+        let provider = SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(
+                Resource::builder()
+                    .with_service_name(config.service_name().to_string())
+                    .build(),
+            )
+            .build();
+        let tracer = provider.tracer(env!("CARGO_PKG_NAME"));
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+
+        Self { provider, tracer }
+    }
+
+    /// Get the layer, which exports the spans of the global tracing subscriber.
+    ///
+    /// It must be installed with the [OtlpExporter::filter].
+    pub fn layer<S>(&self) -> OpenTelemetryLayer<S, Tracer>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>,
+    {
+        tracing_opentelemetry::layer().with_tracer(self.tracer.clone())
+    }
+
+    /// Get the layer, which exports the MongoDB commands as spans.
+    ///
+    /// It must be installed with the [MongoDbTracingLayer::filter].
+    pub fn mongodb_layer(&self) -> MongoDbTracingLayer {
+        MongoDbTracingLayer::new(self.tracer.clone())
+    }
+
+    /// Get the filter, which only passes the spans of the application.
+    pub fn filter() -> Targets {
+        Targets::new().with_targets(TARGETS.map(|target| (target, Level::INFO)))
+    }
+
+    /// Export the remaining spans and stop the exporter.
+    pub fn shutdown(&self) {
+        if let Err(e) = self.provider.shutdown() {
+            tracing::warn!("The OTLP exporter could not be shut down: {e}");
+        }
+    }
+}
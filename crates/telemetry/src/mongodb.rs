@@ -0,0 +1,239 @@
+//! The tracing layer, which exports MongoDB commands as spans, and the [CommandEvent] of the
+//! MongoDB driver it reads them from.
+
+use opentelemetry::trace::Span as _;
+use opentelemetry::trace::SpanKind;
+use opentelemetry::trace::Status;
+use opentelemetry::trace::Tracer as _;
+use opentelemetry::KeyValue;
+use opentelemetry_sdk::trace::Tracer;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::time::Duration;
+use std::time::SystemTime;
+use tracing::field::Field;
+use tracing::field::Visit;
+use tracing::Event;
+use tracing::Level;
+use tracing::Subscriber;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::Layer;
+
+/// The target of the command events of the MongoDB driver.
+pub const COMMAND_TARGET: &str = "mongodb::command";
+
+/// Get the filter, which only passes the command events of the MongoDB driver.
+///
+/// The driver only emits the events, if their target is enabled at level `debug`. So the layers
+/// reading them must be installed with this filter, which enables them independent of the log
+/// level.
+pub fn command_filter() -> Targets {
+    Targets::new().with_target(COMMAND_TARGET, Level::DEBUG)
+}
+
+/// Exports the commands of the MongoDB client as spans.
+///
+/// The MongoDB driver emits events for started and finished commands instead of spans. A span is
+/// exported for every finished command, as child of the span the command was started in. So the
+/// layer must be installed with the [MongoDbTracingLayer::filter], which enables the events
+/// independent of the log level.
+pub struct MongoDbTracingLayer {
+    tracer: Tracer,
+    /// The started commands by driver connection and request id.
+    started: Mutex<HashMap<(u64, i64), StartedCommand>>,
+}
+
+impl MongoDbTracingLayer {
+    pub(crate) fn new(tracer: Tracer) -> Self {
+        Self {
+            tracer,
+            started: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Get the started commands.
+    fn started(&self) -> MutexGuard<'_, HashMap<(u64, i64), StartedCommand>> {
+        self.started
+            .lock()
+            .expect("the started commands should not be poisoned")
+    }
+
+    /// Get the filter, which only passes the command events of the MongoDB driver, see
+    /// [command_filter].
+    pub fn filter() -> Targets {
+        command_filter()
+    }
+}
+
+impl<S: Subscriber> Layer<S> for MongoDbTracingLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let Some(command) = CommandEvent::read(event) else {
+            return;
+        };
+        let (Some(connection_id), Some(request_id)) = (command.connection_id, command.request_id)
+        else {
+            return;
+        };
+        match command.stage {
+            CommandStage::Started => {
+                let start = StartedCommand {
+                    parent: tracing::Span::current().context(),
+                    time: SystemTime::now(),
+                    database: command.database.unwrap_or_default(),
+                };
+                self.started().insert((connection_id, request_id), start);
+            }
+            CommandStage::Succeeded | CommandStage::Failed => {
+                let Some(start) = self.started().remove(&(connection_id, request_id)) else {
+                    return;
+                };
+                let name = command.name.unwrap_or_default();
+                let mut attributes = vec![
+                    KeyValue::new("db.system", "mongodb"),
+                    KeyValue::new("db.namespace", start.database),
+                    KeyValue::new("db.operation.name", name.clone()),
+                ];
+                if let Some(host) = command.host {
+                    attributes.push(KeyValue::new("server.address", host));
+                }
+                if let Some(port) = command.port {
+                    attributes.push(KeyValue::new("server.port", port));
+                }
+                let mut span = self
+                    .tracer
+                    .span_builder(name)
+                    .with_kind(SpanKind::Client)
+                    .with_start_time(start.time)
+                    .with_attributes(attributes)
+                    .start_with_context(&self.tracer, &start.parent);
+                if let Some(failure) = command.failure {
+                    span.set_status(Status::error(failure));
+                }
+                span.end();
+            }
+        }
+    }
+}
+
+/// A command, which was started and not finished yet.
+struct StartedCommand {
+    /// The context of the span the command was started in.
+    parent: opentelemetry::Context,
+    time: SystemTime,
+    database: String,
+}
+
+/// The stage of a command of a [CommandEvent].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CommandStage {
+    Started,
+    Succeeded,
+    Failed,
+}
+
+/// The fields of a command event of the MongoDB driver.
+///
+/// The driver only emits them with its `tracing-unstable` feature.
+#[derive(Debug)]
+pub struct CommandEvent {
+    pub stage: CommandStage,
+    /// The name of the command, like `find`.
+    pub name: Option<String>,
+    /// The database of a started command.
+    pub database: Option<String>,
+    /// The id of the connection, which identifies the command with the `request_id`.
+    pub connection_id: Option<u64>,
+    pub request_id: Option<i64>,
+    pub host: Option<String>,
+    pub port: Option<i64>,
+    /// The duration of a finished command, which the driver reports in whole milliseconds.
+    pub duration: Option<Duration>,
+    /// The error of a failed command.
+    pub failure: Option<String>,
+}
+
+impl CommandEvent {
+    /// Read the [CommandEvent], if the `event` is one.
+    pub fn read(event: &Event<'_>) -> Option<Self> {
+        if event.metadata().target() != COMMAND_TARGET {
+            return None;
+        }
+
+        let mut visitor = CommandEventVisitor::default();
+        event.record(&mut visitor);
+        let stage = match visitor.message.as_deref()? {
+            "Command started" => CommandStage::Started,
+            "Command succeeded" => CommandStage::Succeeded,
+            "Command failed" => CommandStage::Failed,
+            _ => return None,
+        };
+        Some(Self {
+            stage,
+            name: visitor.name,
+            database: visitor.database,
+            connection_id: visitor.connection_id,
+            request_id: visitor.request_id,
+            host: visitor.host,
+            port: visitor.port,
+            duration: visitor.duration,
+            failure: visitor.failure,
+        })
+    }
+}
+
+/// The visitor of the fields of a command event.
+#[derive(Default)]
+struct CommandEventVisitor {
+    message: Option<String>,
+    name: Option<String>,
+    database: Option<String>,
+    connection_id: Option<u64>,
+    request_id: Option<i64>,
+    host: Option<String>,
+    port: Option<i64>,
+    duration: Option<Duration>,
+    failure: Option<String>,
+}
+
+impl Visit for CommandEventVisitor {
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        match field.name() {
+            "requestId" => self.request_id = Some(value),
+            "driverConnectionId" => self.connection_id = u64::try_from(value).ok(),
+            "serverPort" => self.port = Some(value),
+            _ => {}
+        }
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == "durationMS" {
+            self.duration = Some(Duration::from_millis(value));
+        } else {
+            self.record_i64(field, i64::try_from(value).unwrap_or(i64::MAX));
+        }
+    }
+
+    fn record_u128(&mut self, field: &Field, value: u128) {
+        self.record_u64(field, u64::try_from(value).unwrap_or(u64::MAX));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "commandName" => self.name = Some(value.to_string()),
+            "databaseName" => self.database = Some(value.to_string()),
+            "serverHost" => self.host = Some(value.to_string()),
+            "failure" => self.failure = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "message" {
+            self.message = Some(format!("{value:?}"));
+        }
+    }
+}
@@ -0,0 +1,26 @@
+//! The propagation of traces with W3C `traceparent` headers.
+//!
+//! Without an [OtlpExporter](crate::OtlpExporter), no propagator is installed and the headers are
+//! neither read nor written.
+
+use http::HeaderMap;
+use opentelemetry_http::HeaderExtractor;
+use opentelemetry_http::HeaderInjector;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Continue the trace of the `traceparent` header in the `headers` with the `span`.
+pub fn continue_trace(span: &Span, headers: &HeaderMap) {
+    let parent = opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.extract(&HeaderExtractor(headers))
+    });
+    span.set_parent(parent);
+}
+
+/// Add the `traceparent` header of the `span` to the `headers` of an outgoing request.
+pub fn inject_trace(span: &Span, headers: &mut HeaderMap) {
+    let context = span.context();
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut HeaderInjector(headers));
+    });
+}
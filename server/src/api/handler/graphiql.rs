@@ -1,8 +1,14 @@
 use crate::api::router::GRAPHIQL_ROUTE;
+use crate::api::router::SUBSCRIPTION_ROUTE;
 use async_graphql::http::GraphiQLSource;
 use axum::response::Html;
 use axum::response::IntoResponse;
 
 pub(crate) async fn graphiql_handler() -> impl IntoResponse {
-    Html(GraphiQLSource::build().endpoint(GRAPHIQL_ROUTE).finish())
+    Html(
+        GraphiQLSource::build()
+            .endpoint(GRAPHIQL_ROUTE)
+            .subscription_endpoint(SUBSCRIPTION_ROUTE)
+            .finish(),
+    )
 }
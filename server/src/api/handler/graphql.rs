@@ -1,14 +1,68 @@
+use crate::shutdown::Shutdown;
+use async_graphql_axum::GraphQLProtocol;
 use async_graphql_axum::GraphQLResponse;
+use async_graphql_axum::GraphQLWebSocket;
+use axum::extract::State;
+use axum::extract::WebSocketUpgrade;
+use axum::http::Extensions;
+use axum::response::IntoResponse;
 use axum::Extension;
+use qgt_auth::AuthToken;
+use qgt_domain::app::App;
+use qgt_domain::owner::Owner;
+use qgt_domain::permission::Permissions;
+use qgt_domain::request_id::RequestId;
 use qgt_domain::schema::Schema;
 
 /// The handler for the GraphQL API.
 ///
 /// The qm server crate provides already a [graphql_handler](qm::server::graphql_handler), but that
 /// requires an authorization container, which does not exist for this example.
+///
+/// The [RequestId] is added to the request, so it is part of the errors of the response.
+///
+/// Requests are anonymous, unless the optional authentication added an [AuthToken]. Then the
+/// request is served with the subject of the token as [Owner], and its roles add to the public
+/// [Permissions] of the schema.
 pub(crate) async fn graphql_handler(
+    State(app): State<App>,
     schema: Extension<Schema>,
+    extensions: Extensions,
     req: async_graphql_axum::GraphQLRequest,
 ) -> GraphQLResponse {
-    schema.execute(req.into_inner()).await.into()
+    let mut req = req.into_inner();
+    if let Some(request_id) = extensions.get::<RequestId>() {
+        req = req.data(request_id.clone());
+    }
+    if let Some(token) = extensions.get::<AuthToken>() {
+        req = req
+            .data(Owner::new(token.subject.clone()))
+            .data(Permissions::public(
+                app.auth_ctx().config(),
+                token.roles.iter().map(String::as_str),
+            ));
+    }
+    schema.execute(req).await.into()
+}
+
+/// The handler for the subscriptions of the GraphQL API over WebSocket.
+///
+/// The connection is tracked by the [Shutdown], so it is closed at the shutdown signal.
+pub(crate) async fn graphql_subscription_handler(
+    Extension(schema): Extension<Schema>,
+    Extension(shutdown): Extension<Shutdown>,
+    protocol: GraphQLProtocol,
+    upgrade: WebSocketUpgrade,
+) -> impl IntoResponse {
+    upgrade
+        .protocols(async_graphql::http::ALL_WEBSOCKET_PROTOCOLS)
+        .on_upgrade(move |socket| {
+            let (mut sink, mut messages) = shutdown.split_websocket(socket);
+            shutdown.clone().track_connection(async move {
+                GraphQLWebSocket::new_with_pair(&mut sink, &mut messages, schema, protocol)
+                    .serve()
+                    .await;
+                shutdown.close_websocket(sink, messages).await;
+            })
+        })
 }
@@ -0,0 +1,71 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use qgt_domain::app::App;
+use serde_json::json;
+use serde_json::Value;
+use std::future::Future;
+use std::time::Duration;
+
+/// The time after which a dependency counts as unavailable.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// The handler for the liveness check, which succeeds as long as the server responds.
+pub(crate) async fn healthz_handler() -> impl IntoResponse {
+    Json(json!({ "status": "ok" }))
+}
+
+/// The handler for the readiness check.
+///
+/// Checks that MongoDB is reachable and that the signing keys to validate tokens are available,
+/// and reports the status of each dependency. Responds with [StatusCode::SERVICE_UNAVAILABLE] if
+/// any of them is not available.
+pub(crate) async fn readyz_handler(State(app): State<App>) -> impl IntoResponse {
+    let auth_ctx = app.auth_ctx();
+    let (mongodb, auth) = tokio::join!(
+        check(async {
+            qgt_domain::db::ping(app.db()).await?;
+            Ok(json!({}))
+        }),
+        check(async {
+            let keys = auth_ctx.check_keys().await?;
+            Ok(json!({ "keys": keys }))
+        }),
+    );
+
+    let ready = [&mongodb, &auth]
+        .iter()
+        .all(|check| check["status"] == "up");
+    (
+        if ready {
+            StatusCode::OK
+        } else {
+            StatusCode::SERVICE_UNAVAILABLE
+        },
+        Json(json!({
+            "status": if ready { "ready" } else { "unavailable" },
+            "checks": {
+                "mongodb": mongodb,
+                "auth": auth,
+            },
+        })),
+    )
+}
+
+/// Run the `check` of a dependency and get its status with the details of the check.
+async fn check(check: impl Future<Output = anyhow::Result<Value>>) -> Value {
+    let (status, details) = match tokio::time::timeout(CHECK_TIMEOUT, check).await {
+        Ok(Ok(details)) => ("up", details),
+        Ok(Err(e)) => {
+            tracing::warn!("Readiness check failed: {e}");
+            ("down", json!({ "error": e.to_string() }))
+        }
+        Err(_) => ("down", json!({ "error": "timed out" })),
+    };
+    let mut result = json!({ "status": status });
+    if let (Some(result), Some(details)) = (result.as_object_mut(), details.as_object()) {
+        result.extend(details.clone());
+    }
+    result
+}
@@ -11,6 +11,7 @@ pub(crate) async fn index_handler() -> impl IntoResponse {
             <body>
                 <h1>Quick Microservice GraphQL TodoMVC Server API (<code>v{version}</code>)</h1>
                 <div>Visit the <a href="/api/graphql" style="color:#61afef;">GraphQL Playground</a></div>
+                <form method="post" action="/secure/logout"><button type="submit">Log out</button></form>
             </body>
         </html>
         "#,
@@ -0,0 +1,99 @@
+use crate::api::middleware::post_logout_redirect_uri;
+use crate::api::middleware::provider_metadata;
+use crate::api::middleware::removed_session_cookie;
+use crate::api::middleware::session_from_cookie;
+use axum::extract::State;
+use axum::http::header::SET_COOKIE;
+use axum::http::HeaderMap;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::response::Redirect;
+use qgt_domain::app::App;
+use qgt_domain::session::Session;
+use reqwest::Client;
+
+/// The handler to log out of the secured endpoint.
+///
+/// Only `POST` requests log out, so other sites can not log users out with a link or an image.
+/// Removes the session of the request and the session cookie. The provider session is ended with
+/// an RP-initiated logout: the browser is redirected to the end-session endpoint of the provider
+/// with the ID token of the session and the provider redirects back to the index page. Providers
+/// without an end-session endpoint get the refresh token revoked instead and the browser is
+/// redirected to the index page right away.
+pub(crate) async fn logout_handler(
+    State(app): State<App>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let mut redirect_uri = String::from("/");
+    if let Some(session) = session_from_cookie(&app, &headers).await? {
+        app.sessions().remove(session.id()).await.map_err(|e| {
+            tracing::error!("Failed to remove the session: {e}");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to remove the session".to_string(),
+            )
+        })?;
+        if let Some(end_session_url) = end_session_url(&app, &headers, &session).await {
+            redirect_uri = end_session_url;
+        } else if let Some(refresh_token) = session.refresh_token() {
+            revoke_refresh_token(&app, refresh_token).await;
+        }
+    }
+
+    Ok((
+        [(SET_COOKIE, removed_session_cookie())],
+        Redirect::to(&redirect_uri),
+    ))
+}
+
+/// Get the URL of the end-session endpoint, which ends the provider session of the [Session].
+///
+/// Returns [None] if the session has no ID token or the provider has no end-session endpoint.
+async fn end_session_url(app: &App, headers: &HeaderMap, session: &Session) -> Option<String> {
+    let id_token = session.id_token()?;
+    let metadata = provider_metadata(app).await.ok()?;
+    let auth_ctx = app.auth_ctx();
+    match metadata.end_session_url(
+        id_token,
+        auth_ctx.config().client_id(),
+        &post_logout_redirect_uri(app, headers),
+    ) {
+        Ok(url) => url.map(String::from),
+        Err(e) => {
+            tracing::warn!("The end-session endpoint of the provider is invalid: {e}");
+            None
+        }
+    }
+}
+
+/// Revoke the `refresh_token` at the revocation endpoint of the provider.
+///
+/// Failures are only logged, since the local session is removed anyway.
+async fn revoke_refresh_token(app: &App, refresh_token: &str) {
+    let Ok(metadata) = provider_metadata(app).await else {
+        return;
+    };
+    let Some(endpoint) = metadata.revocation_endpoint() else {
+        tracing::info!("The provider supports neither end-session nor revocation");
+        return;
+    };
+    let auth_ctx = app.auth_ctx();
+    let request = Client::new().post(endpoint).form(&[
+        ("client_id", auth_ctx.config().client_id()),
+        ("token", refresh_token),
+        ("token_type_hint", "refresh_token"),
+    ]);
+
+    match request.send().await {
+        Ok(response) if response.status().is_success() => {
+            tracing::info!("Revoked the refresh token of the provider session");
+        }
+        Ok(response) => {
+            tracing::warn!(
+                "Revoking the refresh token failed with status {}",
+                response.status()
+            );
+        }
+        Err(e) => tracing::warn!("Revoking the refresh token failed: {e}"),
+    }
+}
@@ -0,0 +1,13 @@
+use axum::http::header::CONTENT_TYPE;
+use axum::response::IntoResponse;
+
+/// The content type of the Prometheus text format.
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// The handler for the Prometheus metrics.
+pub(crate) async fn metrics_handler() -> impl IntoResponse {
+    (
+        [(CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)],
+        qgt_metrics::encode(),
+    )
+}
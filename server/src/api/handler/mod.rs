@@ -2,6 +2,10 @@
 
 pub(crate) mod graphiql;
 pub(crate) mod graphql;
+pub(crate) mod health;
 pub(crate) mod index;
+pub(crate) mod logout;
+pub(crate) mod metrics;
 pub(crate) mod private_graphiql;
 pub(crate) mod private_graphql;
+pub(crate) mod todos;
@@ -1,5 +1,6 @@
 use crate::api::router::GRAPHIQL_ROUTE;
 use crate::api::router::SECURE_PREFIX;
+use crate::api::router::SUBSCRIPTION_ROUTE;
 use async_graphql::http::GraphiQLSource;
 use axum::response::Html;
 use axum::response::IntoResponse;
@@ -8,6 +9,7 @@ pub(crate) async fn private_graphiql_handler() -> impl IntoResponse {
     Html(
         GraphiQLSource::build()
             .endpoint(&format!("{}{}", SECURE_PREFIX, GRAPHIQL_ROUTE))
+            .subscription_endpoint(&format!("{}{}", SECURE_PREFIX, SUBSCRIPTION_ROUTE))
             .finish(),
     )
 }
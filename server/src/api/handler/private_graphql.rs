@@ -1,16 +1,83 @@
+use crate::shutdown::Shutdown;
+use async_graphql::Data;
+use async_graphql_axum::GraphQLProtocol;
 use async_graphql_axum::GraphQLRequest;
 use async_graphql_axum::GraphQLResponse;
+use async_graphql_axum::GraphQLWebSocket;
+use axum::extract::WebSocketUpgrade;
+use axum::http::Extensions;
+use axum::response::IntoResponse;
 use axum::Extension;
+use qgt_auth::AuthToken;
+use qgt_domain::access_token::PersonalAccessTokenAuth;
+use qgt_domain::owner::Owner;
+use qgt_domain::permission::Permissions;
 use qgt_domain::private_schema::PrivateSchema;
+use qgt_domain::request_id::RequestId;
 
 /// The handler for the secured GraphQL API.
 ///
 /// The qm server crate provides already a [graphql_handler](qm::server::graphql_handler), but that
 /// requires an [AuthContainer from the qm-role crate](https://docs.rs/qm-role/latest/qm_role/struct.AuthContainer.html),
 /// which is not used for this example.
+///
+/// The subject of the token is added as [Owner] to the request, so all operations are scoped to
+/// the caller. The roles of the token are added as [Permissions], which guard the mutations. The
+/// [RequestId] is added, so it is part of the errors of the response. The
+/// [PersonalAccessTokenAuth] marker is passed on for requests with a personal access token.
 pub(crate) async fn private_graphql_handler(
     schema: Extension<PrivateSchema>,
+    Extension(token): Extension<AuthToken>,
+    Extension(request_id): Extension<RequestId>,
+    extensions: Extensions,
     req: GraphQLRequest,
 ) -> GraphQLResponse {
-    schema.execute(req.into_inner()).await.into()
+    let permissions = Permissions::from_roles(token.roles.iter().map(String::as_str));
+    let mut req = req
+        .into_inner()
+        .data(Owner::new(token.subject))
+        .data(permissions)
+        .data(request_id);
+    if let Some(personal_access_token) = extensions.get::<PersonalAccessTokenAuth>() {
+        req = req.data(*personal_access_token);
+    }
+    schema.execute(req).await.into()
+}
+
+/// The handler for the subscriptions of the secured GraphQL API over WebSocket.
+///
+/// The subject of the token of the upgrade request is added as [Owner] to the connection, so all
+/// subscriptions are scoped to the caller. The roles of the token are added as [Permissions] and
+/// the [PersonalAccessTokenAuth] marker is passed on, like for the other requests.
+///
+/// The connection is tracked by the [Shutdown], so it is closed at the shutdown signal.
+pub(crate) async fn private_graphql_subscription_handler(
+    Extension(schema): Extension<PrivateSchema>,
+    Extension(token): Extension<AuthToken>,
+    Extension(shutdown): Extension<Shutdown>,
+    extensions: Extensions,
+    protocol: GraphQLProtocol,
+    upgrade: WebSocketUpgrade,
+) -> impl IntoResponse {
+    let permissions = Permissions::from_roles(token.roles.iter().map(String::as_str));
+    let owner = Owner::new(token.subject);
+    let personal_access_token = extensions.get::<PersonalAccessTokenAuth>().copied();
+    upgrade
+        .protocols(async_graphql::http::ALL_WEBSOCKET_PROTOCOLS)
+        .on_upgrade(move |socket| {
+            let mut data = Data::default();
+            data.insert(owner);
+            data.insert(permissions);
+            if let Some(personal_access_token) = personal_access_token {
+                data.insert(personal_access_token);
+            }
+            let (mut sink, mut messages) = shutdown.split_websocket(socket);
+            shutdown.clone().track_connection(async move {
+                GraphQLWebSocket::new_with_pair(&mut sink, &mut messages, schema, protocol)
+                    .with_data(data)
+                    .serve()
+                    .await;
+                shutdown.close_websocket(sink, messages).await;
+            })
+        })
 }
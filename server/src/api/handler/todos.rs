@@ -0,0 +1,240 @@
+use crate::api::middleware::base_url;
+use crate::api::router::TODOS_ROUTE;
+use axum::extract::Path;
+use axum::extract::State;
+use axum::http::header::LOCATION;
+use axum::http::Extensions;
+use axum::http::HeaderMap;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::response::Response;
+use axum::Json;
+use bson::oid::ObjectId;
+use qgt_auth::AuthToken;
+use qgt_domain::app::App;
+use qgt_domain::error::DomainError;
+use qgt_domain::owner::Owner;
+use qgt_domain::owner::OwnerScope;
+use qgt_domain::permission::Permission;
+use qgt_domain::permission::Permissions;
+use qgt_domain::todo;
+use qgt_domain::todo::CreateTodoInput;
+use qgt_domain::todo::Todo;
+use qgt_domain::todo::UpdateTodoInput;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::json;
+
+/// The JSON representation of a [Todo] in the REST API.
+#[derive(Serialize)]
+pub(crate) struct TodoResource {
+    id: String,
+    /// The absolute URL of the todo.
+    url: String,
+    title: String,
+    completed: bool,
+    order: u64,
+}
+
+/// The JSON body to create a [Todo].
+///
+/// Without an `order`, the todo is appended after all other todos.
+#[derive(Deserialize)]
+pub(crate) struct CreateTodoBody {
+    title: String,
+    #[serde(default)]
+    completed: bool,
+    order: Option<u64>,
+}
+
+/// The JSON body to update a [Todo], which only changes the provided fields.
+#[derive(Deserialize)]
+pub(crate) struct UpdateTodoBody {
+    title: Option<String>,
+    completed: Option<bool>,
+    order: Option<u64>,
+}
+
+/// The handler to list all todos.
+pub(crate) async fn list_todos_handler(
+    State(app): State<App>,
+    extensions: Extensions,
+    headers: HeaderMap,
+) -> Result<Json<Vec<TodoResource>>, RestError> {
+    let request = RestRequest::new(&app, &extensions, &headers);
+    let todos = todo::list_todos(&app, &request.scope).await?;
+
+    Ok(Json(
+        todos.iter().map(|todo| request.resource(todo)).collect(),
+    ))
+}
+
+/// The handler to create a todo.
+///
+/// Responds with [StatusCode::CREATED] and the URL of the new todo as `Location` header.
+pub(crate) async fn create_todo_handler(
+    State(app): State<App>,
+    extensions: Extensions,
+    headers: HeaderMap,
+    Json(body): Json<CreateTodoBody>,
+) -> Result<impl IntoResponse, RestError> {
+    let request = RestRequest::new(&app, &extensions, &headers);
+    request.permissions.require(Permission::TodoWrite)?;
+    let mut input = CreateTodoInput::new(body.title, body.completed);
+    if let Some(order) = body.order {
+        input = input.with_order(order)?;
+    }
+    let todo = todo::create_todo(&app, &request.scope, input).await?;
+
+    let resource = request.resource(&todo);
+    Ok((
+        StatusCode::CREATED,
+        [(LOCATION, resource.url.clone())],
+        Json(resource),
+    ))
+}
+
+/// The handler to delete all todos.
+pub(crate) async fn remove_all_todos_handler(
+    State(app): State<App>,
+    extensions: Extensions,
+    headers: HeaderMap,
+) -> Result<StatusCode, RestError> {
+    let request = RestRequest::new(&app, &extensions, &headers);
+    request.permissions.require(Permission::TodoWrite)?;
+    todo::remove_all_todos(&app, &request.scope).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The handler to get a todo.
+pub(crate) async fn todo_handler(
+    State(app): State<App>,
+    Path(id): Path<String>,
+    extensions: Extensions,
+    headers: HeaderMap,
+) -> Result<Json<TodoResource>, RestError> {
+    let request = RestRequest::new(&app, &extensions, &headers);
+    let todo = todo::get_todo(&app, &request.scope, &parse_id(&id)?).await?;
+
+    Ok(Json(request.resource(&todo)))
+}
+
+/// The handler to update a todo.
+pub(crate) async fn update_todo_handler(
+    State(app): State<App>,
+    Path(id): Path<String>,
+    extensions: Extensions,
+    headers: HeaderMap,
+    Json(body): Json<UpdateTodoBody>,
+) -> Result<Json<TodoResource>, RestError> {
+    let request = RestRequest::new(&app, &extensions, &headers);
+    request.permissions.require(Permission::TodoWrite)?;
+    let mut input = UpdateTodoInput::new(parse_id(&id)?, body.title, body.completed);
+    if let Some(order) = body.order {
+        input = input.with_order(order)?;
+    }
+    let todo = todo::update_todo(&app, &request.scope, &input).await?;
+
+    Ok(Json(request.resource(&todo)))
+}
+
+/// The handler to delete a todo.
+pub(crate) async fn remove_todo_handler(
+    State(app): State<App>,
+    Path(id): Path<String>,
+    extensions: Extensions,
+    headers: HeaderMap,
+) -> Result<StatusCode, RestError> {
+    let request = RestRequest::new(&app, &extensions, &headers);
+    request.permissions.require(Permission::TodoWrite)?;
+    let id = parse_id(&id)?;
+    if todo::remove_todos_by_id(&app, &request.scope, &[id]).await? == 0 {
+        return Err(todo::todo_not_found(&id).into());
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The caller and base URL of a REST request.
+///
+/// Requests are anonymous, unless the optional authentication added an [AuthToken]. Then the
+/// subject of the token is the [Owner] and its roles add to the public [Permissions], like for the
+/// GraphQL API.
+struct RestRequest {
+    scope: OwnerScope,
+    permissions: Permissions,
+    /// The scheme and host the client sent the request to.
+    base_url: String,
+}
+
+impl RestRequest {
+    fn new(app: &App, extensions: &Extensions, headers: &HeaderMap) -> Self {
+        let token = extensions.get::<AuthToken>();
+        Self {
+            scope: OwnerScope::new(
+                token
+                    .map(|token| Owner::new(token.subject.clone()))
+                    .as_ref(),
+            ),
+            permissions: Permissions::public(
+                app.auth_ctx().config(),
+                token
+                    .iter()
+                    .flat_map(|token| token.roles.iter().map(String::as_str)),
+            ),
+            base_url: base_url(app, headers),
+        }
+    }
+
+    /// Get the [TodoResource] of the `todo` with its absolute URL.
+    fn resource(&self, todo: &Todo) -> TodoResource {
+        let id = todo.object_id().map(|id| id.to_hex()).unwrap_or_default();
+        TodoResource {
+            url: format!("{}{TODOS_ROUTE}/{id}", self.base_url),
+            id,
+            title: todo.title_str().to_string(),
+            completed: todo.is_completed(),
+            order: todo.sort_order(),
+        }
+    }
+}
+
+/// A [DomainError] as response of the REST API.
+///
+/// The body contains the code and message of the error, like the errors of the GraphQL API.
+pub(crate) struct RestError(DomainError);
+
+impl From<DomainError> for RestError {
+    fn from(error: DomainError) -> Self {
+        Self(error)
+    }
+}
+
+impl IntoResponse for RestError {
+    fn into_response(self) -> Response {
+        let status = match &self.0 {
+            DomainError::NotFound(_) => StatusCode::NOT_FOUND,
+            DomainError::ValidationFailed(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            DomainError::Conflict(_) | DomainError::DuplicateName(_) => StatusCode::CONFLICT,
+            DomainError::Forbidden(_) => StatusCode::FORBIDDEN,
+            DomainError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (
+            status,
+            Json(json!({ "code": self.0.code(), "message": self.0.message() })),
+        )
+            .into_response()
+    }
+}
+
+/// Parse the `id` of a todo URL.
+///
+/// Invalid ids can not belong to a todo, so they are not found.
+fn parse_id(id: &str) -> Result<ObjectId, RestError> {
+    ObjectId::parse_str(id).map_err(|_| {
+        RestError(DomainError::NotFound(format!(
+            "No todo found for id '{id}'"
+        )))
+    })
+}
@@ -1,49 +1,319 @@
 use super::router::GRAPHIQL_ROUTE;
 use super::router::SECURE_PREFIX;
+use axum::extract::MatchedPath;
+use axum::extract::OriginalUri;
 use axum::extract::Query;
 use axum::extract::Request;
 use axum::extract::State;
+use axum::http::header::ACCESS_CONTROL_ALLOW_HEADERS;
+use axum::http::header::ACCESS_CONTROL_ALLOW_METHODS;
+use axum::http::header::ACCESS_CONTROL_ALLOW_ORIGIN;
+use axum::http::header::ACCESS_CONTROL_EXPOSE_HEADERS;
+use axum::http::header::ACCESS_CONTROL_MAX_AGE;
 use axum::http::header::AUTHORIZATION;
+use axum::http::header::COOKIE;
+use axum::http::header::HOST;
+use axum::http::header::SET_COOKIE;
 use axum::http::HeaderMap;
+use axum::http::HeaderName;
 use axum::http::HeaderValue;
+use axum::http::Method;
 use axum::http::StatusCode;
 use axum::middleware::Next;
 use axum::response::IntoResponse;
 use axum::response::Redirect;
+use axum::response::Response;
+use qgt_auth::config::PublicAuthMode;
+use qgt_auth::discovery::ProviderMetadata;
+use qgt_auth::login::Pkce;
+use qgt_auth::login::LOGIN_TIMEOUT;
+use qgt_auth::token::bearer_token;
+use qgt_auth::token::TokenError;
+use qgt_auth::AuthToken;
+use qgt_domain::access_token::PersonalAccessTokenAuth;
+use qgt_domain::access_token::TOKEN_PREFIX;
 use qgt_domain::app::App;
+use qgt_domain::request_id::RequestId;
+use qgt_domain::session::Session;
+use qgt_domain::session::SessionTokens;
+use qgt_metrics::HTTP_REQUESTS;
+use qgt_metrics::HTTP_REQUEST_DURATION;
+use qgt_telemetry::propagation::continue_trace;
+use qgt_telemetry::propagation::inject_trace;
 use reqwest::Client;
+use serde_json::Map;
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+use tracing::Instrument;
+
+/// The name of the cookie with the session id.
+const SESSION_COOKIE: &str = "qgt_session";
+
+/// The name of the cookie with the PKCE code verifier of a log-in.
+const LOGIN_COOKIE: &str = "qgt_login";
+
+/// The token lifetime, if the token response does not contain one.
+const DEFAULT_TOKEN_LIFETIME_SECS: u64 = 300;
+
+/// The header with the id of a request.
+const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// The header with the protocol of the request to a reverse proxy.
+const FORWARDED_PROTO_HEADER: HeaderName = HeaderName::from_static("x-forwarded-proto");
+
+/// Add the [RequestId] of the `X-Request-Id` header to the extensions of requests and the headers
+/// of their responses.
+///
+/// A new id is generated for requests without a valid `X-Request-Id` header.
+pub(crate) async fn set_request_id(mut req: Request, next: Next) -> Response {
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(RequestId::parse)
+        .unwrap_or_else(RequestId::generate);
+    let header = HeaderValue::from_str(request_id.as_str())
+        .expect("request ids should be convertable to HeaderValue");
+    req.extensions_mut().insert(request_id);
+
+    let mut response = next.run(req).await;
+    response.headers_mut().insert(REQUEST_ID_HEADER, header);
+    response
+}
+
+/// Run requests in a span with their [RequestId], which continues the trace of their `traceparent`
+/// header.
+///
+/// The span is the parent of all spans of the request, so every log line of the request contains
+/// its id.
+pub(crate) async fn trace_request(req: Request, next: Next) -> Response {
+    let method = req.method().to_string();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or("unmatched", MatchedPath::as_str)
+        .to_string();
+    let request_id = req
+        .extensions()
+        .get::<RequestId>()
+        .map(ToString::to_string)
+        .unwrap_or_default();
+    let span = tracing::info_span!(
+        "HTTP request",
+        request_id,
+        otel.name = format!("{method} {route}"),
+        otel.kind = "server",
+        otel.status_code = tracing::field::Empty,
+        http.request.method = method,
+        http.route = route,
+        http.response.status_code = tracing::field::Empty,
+    );
+    continue_trace(&span, req.headers());
+    let response = next.run(req).instrument(span.clone()).await;
+
+    span.record("http.response.status_code", response.status().as_u16());
+    if response.status().is_server_error() {
+        span.record("otel.status_code", "error");
+    }
+    response
+}
+
+/// Allow cross-origin requests from any origin and answer their preflight requests.
+///
+/// Cookies are not allowed cross-origin, so browsers can only send bearer tokens in the
+/// `Authorization` header.
+pub(crate) async fn allow_cross_origin(req: Request, next: Next) -> Response {
+    let mut response = if req.method() == Method::OPTIONS {
+        StatusCode::NO_CONTENT.into_response()
+    } else {
+        next.run(req).await
+    };
+    let headers = response.headers_mut();
+    headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static("*"));
+    headers.insert(
+        ACCESS_CONTROL_ALLOW_METHODS,
+        HeaderValue::from_static("GET, POST, PATCH, DELETE, OPTIONS"),
+    );
+    headers.insert(
+        ACCESS_CONTROL_ALLOW_HEADERS,
+        HeaderValue::from_static("Authorization, Content-Type, X-Request-Id"),
+    );
+    headers.insert(
+        ACCESS_CONTROL_EXPOSE_HEADERS,
+        HeaderValue::from_static("Location, X-Request-Id"),
+    );
+    headers.insert(ACCESS_CONTROL_MAX_AGE, HeaderValue::from_static("86400"));
+    response
+}
+
+/// Record the count and duration of requests by method, matched route and status code.
+pub(crate) async fn record_metrics(req: Request, next: Next) -> Response {
+    let method = req.method().to_string();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or("unmatched", MatchedPath::as_str)
+        .to_string();
+    let start = Instant::now();
+    let response = next.run(req).await;
+
+    HTTP_REQUEST_DURATION
+        .with_label_values(&[method.as_str(), route.as_str()])
+        .observe(start.elapsed().as_secs_f64());
+    HTTP_REQUESTS
+        .with_label_values(&[method.as_str(), route.as_str(), response.status().as_str()])
+        .inc();
+    response
+}
+
+/// Only pass requests with a valid bearer token and add the [AuthToken] to their extensions.
+///
+/// Bearer tokens with the [TOKEN_PREFIX] are personal access tokens, whose owner and scopes become
+/// the subject and roles of the [AuthToken]. Their requests also get the [PersonalAccessTokenAuth]
+/// marker. All other bearer tokens are validated as JWTs.
+pub(crate) async fn authenticate(State(app): State<App>, mut req: Request, next: Next) -> Response {
+    match bearer_auth_token(&app, req.headers()).await {
+        Ok(token) => {
+            if bearer_token(req.headers()).is_some_and(|token| token.starts_with(TOKEN_PREFIX)) {
+                req.extensions_mut().insert(PersonalAccessTokenAuth);
+            }
+            req.extensions_mut().insert(token);
+            next.run(req).await
+        }
+        Err(e) => unauthorized(e),
+    }
+}
+
+/// Add the [AuthToken] of requests with a valid bearer token to their extensions, if the public
+/// endpoint uses [PublicAuthMode::Optional].
+///
+/// Requests without a bearer token stay anonymous. Requests with an invalid bearer token are
+/// rejected, so clients notice that they are not signed in.
+pub(crate) async fn authenticate_optionally(
+    State(app): State<App>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    if app.auth_ctx().config().public_mode() == PublicAuthMode::Optional {
+        match bearer_auth_token(&app, req.headers()).await {
+            Ok(token) => {
+                req.extensions_mut().insert(token);
+            }
+            Err(TokenError::Missing) => {}
+            Err(e) => return unauthorized(e),
+        }
+    }
+    next.run(req).await
+}
+
+/// Get the [AuthToken] of the bearer token in the `headers`.
+async fn bearer_auth_token(app: &App, headers: &HeaderMap) -> Result<AuthToken, TokenError> {
+    match bearer_token(headers) {
+        Some(token) if token.starts_with(TOKEN_PREFIX) => personal_access_token(app, token).await,
+        Some(token) => app.auth_ctx().validate(token).await,
+        None => Err(TokenError::Missing),
+    }
+}
+
+/// Get the response to a request, which was not authorized.
+fn unauthorized(e: TokenError) -> Response {
+    tracing::debug!("Request not authorized: {e:?}");
+    (e.status(), e.message().to_string()).into_response()
+}
+
+/// Get the [AuthToken] of the personal access `token`.
+async fn personal_access_token(app: &App, token: &str) -> Result<AuthToken, TokenError> {
+    let personal_access_token = app
+        .access_tokens()
+        .verify(token)
+        .await
+        .map_err(|e| TokenError::Unavailable(e.to_string()))?
+        .ok_or_else(|| TokenError::Invalid(String::from("Unknown personal access token")))?;
+
+    Ok(AuthToken {
+        subject: personal_access_token.owner_subject().to_string(),
+        expires: personal_access_token
+            .expires_timestamp()
+            .and_then(|expires| u64::try_from(expires).ok())
+            .unwrap_or(u64::MAX),
+        roles: personal_access_token
+            .permissions()
+            .iter()
+            .map(|permission| permission.role().to_string())
+            .collect(),
+        claims: Map::new(),
+    })
+}
 
 /// Redirect to log-in if the request was unauthorized.
+///
+/// The log-in is protected with PKCE and a signed `state`, which carries the requested path to
+/// return to after the log-in. The PKCE code verifier is kept in the [LOGIN_COOKIE].
 pub(crate) async fn redirect_if_unauthorized(
     State(app): State<App>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
     req: Request,
     next: Next,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
     let response = next.run(req).await;
     if response.status() == StatusCode::UNAUTHORIZED {
-        let redirect_uri = format!(
-            "http://{}/realms/{}/protocol/openid-connect/auth?scope=openid&response_type=code&client_id={}&redirect_uri=http://{}{}{}",
-            app.auth_ctx().config().address(),
-            app.auth_ctx().config().realm(),
-            app.auth_ctx().config().client_id(),
-            app.server_config().address(),
-            SECURE_PREFIX,
-            GRAPHIQL_ROUTE
-        );
-        return Ok(Redirect::to(redirect_uri.as_str()).into_response());
+        // Do not return to a log-in redirect, its code and state are used up
+        let return_path = match uri.query() {
+            Some(query) if !query.split('&').any(|param| param.starts_with("state=")) => {
+                format!("{}?{query}", uri.path())
+            }
+            _ => uri.path().to_string(),
+        };
+        let pkce = Pkce::new();
+        let state = app
+            .auth_ctx()
+            .state_signer()
+            .sign(&return_path, pkce.verifier())
+            .ok_or((StatusCode::BAD_REQUEST, "Invalid return path".to_string()))?;
+        let redirect_uri = provider_metadata(&app)
+            .await?
+            .authorization_url(
+                app.auth_ctx().config().client_id(),
+                &login_redirect_uri(&app, &headers),
+                &state,
+                &pkce,
+            )
+            .map_err(|e| {
+                tracing::error!("Failed to build the authorization URL: {e}");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Invalid authorization endpoint".to_string(),
+                )
+            })?;
+        return Ok((
+            [(
+                SET_COOKIE,
+                cookie_value(LOGIN_COOKIE, pkce.verifier(), LOGIN_TIMEOUT.as_secs()),
+            )],
+            Redirect::to(redirect_uri.as_str()),
+        )
+            .into_response());
     }
 
     Ok(response)
 }
 
-/// Set the authorization header.
+/// Set the authorization header from the session of the request.
 ///
 /// Does nothing if we already have a [axum::http::header::AUTHORIZATION] header set.
 ///
+/// The session is identified by the [SESSION_COOKIE]. The access token of the session is refreshed
+/// shortly before it expires and the cookie of a refreshed session is set on the response.
+///
+/// Without a session, the `code` query parameter of the log-in redirect is exchanged for a token,
+/// if the `state` query parameter is valid for the [LOGIN_COOKIE]. The token is stored in a new
+/// session and the response redirects to the return path of the `state`.
+///
 /// ## Note
-/// This is a very inefficient and unsecure way to handle authentication.
-/// It is also not very stable, but it is enough for the demonstration of this application.
+/// This is a simplified authentication handling for the GraphiQL page of this example.
 ///
 /// **Do not replicate this in an actual project.**
 pub(crate) async fn set_authorization_header(
@@ -52,85 +322,299 @@ pub(crate) async fn set_authorization_header(
     headers: HeaderMap,
     mut req: Request,
     next: Next,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<Response, (StatusCode, String)> {
     // Skip if the authorization header is already present
     if headers.contains_key(AUTHORIZATION) {
         return Ok(next.run(req).await);
     }
 
-    if let Some(access_token) = app.auth_ctx().token().as_ref() {
-        tracing::info!("Got access token from auth context");
-        tracing::debug!("Access token from auth context: {access_token}");
-        req.headers_mut().append(
-            AUTHORIZATION,
-            HeaderValue::from_str(&format!("Bearer {access_token}"))
-                .expect("string should be convertable to HeaderValue"),
-        );
-    } else if query_params.contains_key("code") {
-        tracing::info!("Requesting access token from keycloak");
-        // Get the JWT
-        let response = Client::new()
-            .post(format!(
-                "http://{}/realms/{}/protocol/openid-connect/token",
-                app.auth_ctx().config().address(),
-                app.auth_ctx().config().realm()
-            ))
-            .form(&[
-                ("grant_type", "authorization_code"),
-                (
-                    "code",
-                    &query_params
-                        .get("code")
-                        .expect("the 'code' query param should exist"),
-                ),
-                ("client_id", app.auth_ctx().config().client_id()),
-                (
-                    "redirect_uri",
-                    format!(
-                        "http://{}{}{}",
-                        app.server_config().address(),
-                        SECURE_PREFIX,
-                        GRAPHIQL_ROUTE
-                    )
-                    .as_str(),
-                ),
-            ])
-            .send()
-            .await
-            .map_err(|_| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "Failed to exchange code for token retrieval".to_string(),
-                )
-            })?;
+    let (session, set_cookie) = match session_from_cookie(&app, &headers).await? {
+        Some(session) if session.needs_refresh() => (refresh_session(&app, &session).await?, true),
+        Some(session) => (Some(session), false),
+        None => match (query_params.get("code"), query_params.get("state")) {
+            (Some(code), Some(state)) => {
+                return complete_login(&app, &headers, code, state).await;
+            }
+            _ => (None, false),
+        },
+    };
+
+    let Some(session) = session else {
+        tracing::info!("Inserting authorization header skipped");
+        return Ok(next.run(req).await);
+    };
+    append_bearer_token(&mut req, session.access_token());
+    let mut response = next.run(req).await;
+    if set_cookie {
+        response
+            .headers_mut()
+            .append(SET_COOKIE, session_cookie(&session));
+    }
+    Ok(response)
+}
+
+/// Complete the log-in by exchanging the `code` for a token stored in a new [Session].
+///
+/// The `state` must be signed for the code verifier in the [LOGIN_COOKIE], which is sent to
+/// the provider with the `code`. Redirects to the return path of the `state`.
+async fn complete_login(
+    app: &App,
+    headers: &HeaderMap,
+    code: &str,
+    state: &str,
+) -> Result<Response, (StatusCode, String)> {
+    let invalid_login = || (StatusCode::BAD_REQUEST, "Invalid log-in state".to_string());
+    let verifier = cookie(headers, LOGIN_COOKIE).ok_or_else(invalid_login)?;
+    let return_path = app
+        .auth_ctx()
+        .state_signer()
+        .verify(state, verifier)
+        .ok_or_else(invalid_login)?;
+
+    let session = exchange_code(app, headers, code, verifier).await?;
+    let mut response = Redirect::to(&return_path).into_response();
+    response
+        .headers_mut()
+        .append(SET_COOKIE, session_cookie(&session));
+    response
+        .headers_mut()
+        .append(SET_COOKIE, cookie_value(LOGIN_COOKIE, "", 0));
+    Ok(response)
+}
+
+/// Get the value of the cookie with the `name` from the request `headers`.
+fn cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find_map(|(cookie_name, value)| (cookie_name == name).then_some(value))
+}
+
+/// Get the [Session] identified by the [SESSION_COOKIE] of the request.
+pub(crate) async fn session_from_cookie(
+    app: &App,
+    headers: &HeaderMap,
+) -> Result<Option<Session>, (StatusCode, String)> {
+    let Some(id) = cookie(headers, SESSION_COOKIE) else {
+        return Ok(None);
+    };
+
+    app.sessions()
+        .get(id)
+        .await
+        .map_err(|e| session_error("Failed to load the session", e))
+}
+
+/// Exchange the `code` of the log-in redirect for a token and store it in a new [Session].
+///
+/// The PKCE code `verifier` proves that the `code` was issued for the log-in of this browser.
+async fn exchange_code(
+    app: &App,
+    headers: &HeaderMap,
+    code: &str,
+    verifier: &str,
+) -> Result<Session, (StatusCode, String)> {
+    tracing::info!("Requesting access token from the provider");
+    let redirect_uri = login_redirect_uri(app, headers);
+    let tokens = request_tokens(
+        app,
+        &[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("code_verifier", verifier),
+            ("client_id", app.auth_ctx().config().client_id()),
+            ("redirect_uri", redirect_uri.as_str()),
+        ],
+    )
+    .await?
+    .map_err(|status| (status, "Failed to get token".to_string()))?;
+
+    app.sessions()
+        .create(tokens)
+        .await
+        .map_err(|e| session_error("Failed to create the session", e))
+}
 
-        if response.status() != StatusCode::OK {
-            return Err((response.status(), "Failed to get token".to_string()));
+/// Refresh the access token of the [Session] with its refresh token.
+///
+/// If the provider rejects the refresh token, the session is removed and [None] is returned, so the
+/// user has to log in again.
+async fn refresh_session(
+    app: &App,
+    session: &Session,
+) -> Result<Option<Session>, (StatusCode, String)> {
+    let Some(refresh_token) = session.refresh_token() else {
+        return Ok(Some(session.clone()));
+    };
+
+    tracing::info!("Refreshing access token from the provider");
+    match request_tokens(
+        app,
+        &[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+            ("client_id", app.auth_ctx().config().client_id()),
+        ],
+    )
+    .await?
+    {
+        Ok(tokens) => app
+            .sessions()
+            .refresh(session.id(), tokens)
+            .await
+            .map_err(|e| session_error("Failed to refresh the session", e)),
+        Err(status) => {
+            tracing::info!("Refreshing the access token was rejected with status {status}");
+            app.sessions()
+                .remove(session.id())
+                .await
+                .map_err(|e| session_error("Failed to remove the session", e))?;
+            Ok(None)
         }
+    }
+}
 
-        let token_response: serde_json::Value = response.json().await.map_err(|_| {
+/// Request tokens from the token endpoint of the provider with the `form`.
+///
+/// Returns the status of the response as error, if the provider did not issue tokens.
+async fn request_tokens(
+    app: &App,
+    form: &[(&str, &str)],
+) -> Result<Result<SessionTokens, StatusCode>, (StatusCode, String)> {
+    let token_endpoint = provider_metadata(app).await?.token_endpoint().to_string();
+    let span = tracing::info_span!(
+        "token request",
+        otel.kind = "client",
+        http.request.method = "POST",
+        url.full = token_endpoint,
+    );
+    let mut headers = HeaderMap::new();
+    inject_trace(&span, &mut headers);
+    let response = Client::new()
+        .post(token_endpoint)
+        .headers(headers)
+        .form(form)
+        .send()
+        .instrument(span)
+        .await
+        .map_err(|_| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                "Failed to parse token response".to_string(),
+                "Failed to request token".to_string(),
             )
         })?;
 
-        // Extract the access token
-        let access_token = token_response["access_token"].as_str().ok_or((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "No access token found".to_string(),
-        ))?;
-        tracing::debug!("Access token from keycloak: {access_token}");
-
-        app.auth_ctx().set_token(Some(access_token.to_string()));
-        req.headers_mut().append(
-            AUTHORIZATION,
-            HeaderValue::from_str(&format!("Bearer {access_token}"))
-                .expect("string should be convertable to HeaderValue"),
-        );
-    } else {
-        tracing::info!("Inserting authorization header skipped");
+    if response.status() != StatusCode::OK {
+        return Ok(Err(response.status()));
     }
 
-    Ok(next.run(req).await)
+    let token_response: serde_json::Value = response.json().await.map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to parse token response".to_string(),
+        )
+    })?;
+
+    // Extract the access token
+    let access_token = token_response["access_token"].as_str().ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "No access token found".to_string(),
+    ))?;
+
+    Ok(Ok(SessionTokens {
+        access_token: access_token.to_string(),
+        expires_in: Duration::from_secs(
+            token_response["expires_in"]
+                .as_u64()
+                .unwrap_or(DEFAULT_TOKEN_LIFETIME_SECS),
+        ),
+        refresh_token: token_response["refresh_token"]
+            .as_str()
+            .map(ToString::to_string),
+        // Keycloak uses `0` for refresh tokens without expiry
+        refresh_expires_in: token_response["refresh_expires_in"]
+            .as_u64()
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs),
+        id_token: token_response["id_token"].as_str().map(ToString::to_string),
+    }))
+}
+
+/// Get the scheme and host the client sent the request with the `headers` to, like
+/// `https://example.com`.
+///
+/// Behind a reverse proxy, the scheme is the one of the `X-Forwarded-Proto` header, otherwise
+/// `http`. Without a `Host` header, the host is the configured server address.
+pub(crate) fn base_url(app: &App, headers: &HeaderMap) -> String {
+    let scheme = headers
+        .get(FORWARDED_PROTO_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("http");
+    let host = headers
+        .get(HOST)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_else(|| app.server_config().address());
+    format!("{scheme}://{host}")
+}
+
+/// Get the URI the provider redirects to after the log-in of the request with the `headers`.
+fn login_redirect_uri(app: &App, headers: &HeaderMap) -> String {
+    format!("{}{SECURE_PREFIX}{GRAPHIQL_ROUTE}", base_url(app, headers))
+}
+
+/// Get the URI the provider redirects to after the logout of the request with the `headers`, which
+/// is the index page.
+pub(crate) fn post_logout_redirect_uri(app: &App, headers: &HeaderMap) -> String {
+    format!("{}/", base_url(app, headers))
+}
+
+/// Get the discovered [ProviderMetadata] of the authentication provider.
+pub(crate) async fn provider_metadata(
+    app: &App,
+) -> Result<Arc<ProviderMetadata>, (StatusCode, String)> {
+    app.auth_ctx().discovery().metadata().await.map_err(|e| {
+        tracing::error!("Failed to discover the authentication provider: {e}");
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Authentication provider unavailable".to_string(),
+        )
+    })
+}
+
+/// Log the session `error` and map it to an internal server error with the `message`.
+fn session_error(message: &str, error: anyhow::Error) -> (StatusCode, String) {
+    tracing::error!("{message}: {error}");
+    (StatusCode::INTERNAL_SERVER_ERROR, message.to_string())
+}
+
+/// Append the `access_token` as bearer token to the [AUTHORIZATION] header of the request.
+fn append_bearer_token(req: &mut Request, access_token: &str) {
+    req.headers_mut().append(
+        AUTHORIZATION,
+        HeaderValue::from_str(&format!("Bearer {access_token}"))
+            .expect("string should be convertable to HeaderValue"),
+    );
+}
+
+/// Get the [SESSION_COOKIE] value for the [Session].
+fn session_cookie(session: &Session) -> HeaderValue {
+    cookie_value(SESSION_COOKIE, session.id(), session.expires_in().as_secs())
+}
+
+/// Get the [SESSION_COOKIE] value, which removes the cookie from the browser.
+pub(crate) fn removed_session_cookie() -> HeaderValue {
+    cookie_value(SESSION_COOKIE, "", 0)
+}
+
+/// Get the `Set-Cookie` value for the cookie `name` with the `value` and the `max_age` in seconds.
+///
+/// The cookie is only sent for the secured endpoint and can not be read by scripts.
+fn cookie_value(name: &str, value: &str, max_age: u64) -> HeaderValue {
+    HeaderValue::from_str(&format!(
+        "{name}={value}; Path={SECURE_PREFIX}; Max-Age={max_age}; HttpOnly; Secure; SameSite=Lax"
+    ))
+    .expect("string should be convertable to HeaderValue")
 }
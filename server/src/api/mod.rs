@@ -1,3 +1,3 @@
 mod handler;
 pub(crate) mod middleware;
-pub(crate) mod router;
+pub mod router;
@@ -1,21 +1,48 @@
 use super::handler::graphiql::graphiql_handler;
 use super::handler::graphql::graphql_handler;
+use super::handler::graphql::graphql_subscription_handler;
+use super::handler::health::healthz_handler;
+use super::handler::health::readyz_handler;
 use super::handler::index::index_handler;
+use super::handler::logout::logout_handler;
+use super::handler::metrics::metrics_handler;
 use super::handler::private_graphiql::private_graphiql_handler;
 use super::handler::private_graphql::private_graphql_handler;
+use super::handler::private_graphql::private_graphql_subscription_handler;
+use super::handler::todos::create_todo_handler;
+use super::handler::todos::list_todos_handler;
+use super::handler::todos::remove_all_todos_handler;
+use super::handler::todos::remove_todo_handler;
+use super::handler::todos::todo_handler;
+use super::handler::todos::update_todo_handler;
+use super::middleware::allow_cross_origin;
+use super::middleware::authenticate;
+use super::middleware::authenticate_optionally;
+use super::middleware::record_metrics;
 use super::middleware::redirect_if_unauthorized;
 use super::middleware::set_authorization_header;
+use super::middleware::set_request_id;
+use super::middleware::trace_request;
+use crate::shutdown::Shutdown;
 use axum::Extension;
 use axum::Router;
-use qgt_auth::keycloak_auth_layer;
 use qgt_domain::app::App;
 use tower::ServiceBuilder;
 
 pub(crate) const GRAPHIQL_ROUTE: &str = "/api/graphql";
+pub(crate) const HEALTH_ROUTE: &str = "/healthz";
+pub(crate) const LOGOUT_ROUTE: &str = "/logout";
+pub(crate) const METRICS_ROUTE: &str = "/metrics";
+pub(crate) const READY_ROUTE: &str = "/readyz";
 pub(crate) const SECURE_PREFIX: &str = "/secure";
+pub(crate) const SUBSCRIPTION_ROUTE: &str = "/api/graphql/ws";
+pub(crate) const TODO_ROUTE: &str = "/todos/{id}";
+pub(crate) const TODOS_ROUTE: &str = "/todos";
 
 /// Get the router defining the API endpoints.
-pub(crate) async fn get(app: App) -> Router {
+///
+/// The WebSocket connections of the subscriptions are tracked by the [Shutdown].
+pub async fn get(app: App, shutdown: Shutdown) -> Router {
     let schema = qgt_domain::schema::SchemaBuilder::default().build(app.clone());
     let private_schema = qgt_domain::private_schema::SchemaBuilder::default().build(app.clone());
     // Write the schema to a file if we run at debug level
@@ -30,22 +57,68 @@ pub(crate) async fn get(app: App) -> Router {
         std::io::Write::write_all(&mut private_file, private_schema.sdl().as_bytes())
             .expect("writing the schema file should work");
     }
+    // The GraphiQL pages are served for GET requests of the GraphQL routes
+    let (public_route, private_route) = if app.features().graphiql() {
+        (
+            axum::routing::get(graphiql_handler).post(graphql_handler),
+            axum::routing::get(private_graphiql_handler).post(private_graphql_handler),
+        )
+    } else {
+        (
+            axum::routing::post(graphql_handler),
+            axum::routing::post(private_graphql_handler),
+        )
+    };
     Router::new()
         .route("/", axum::routing::get(index_handler))
+        .route(HEALTH_ROUTE, axum::routing::get(healthz_handler))
+        .route(READY_ROUTE, axum::routing::get(readyz_handler))
+        .route(METRICS_ROUTE, axum::routing::get(metrics_handler))
         .route(
             GRAPHIQL_ROUTE,
-            axum::routing::get(graphiql_handler).post(graphql_handler),
+            public_route.layer(axum::middleware::from_fn_with_state(
+                app.clone(),
+                authenticate_optionally,
+            )),
+        )
+        .route(
+            SUBSCRIPTION_ROUTE,
+            axum::routing::get(graphql_subscription_handler),
+        )
+        // The REST API of the todo-backend spec, which is called cross-origin by the frontends
+        .merge(
+            Router::new()
+                .route(
+                    TODOS_ROUTE,
+                    axum::routing::get(list_todos_handler)
+                        .post(create_todo_handler)
+                        .delete(remove_all_todos_handler),
+                )
+                .route(
+                    TODO_ROUTE,
+                    axum::routing::get(todo_handler)
+                        .patch(update_todo_handler)
+                        .delete(remove_todo_handler),
+                )
+                .layer(
+                    ServiceBuilder::new()
+                        .layer(axum::middleware::from_fn(allow_cross_origin))
+                        .layer(axum::middleware::from_fn_with_state(
+                            app.clone(),
+                            authenticate_optionally,
+                        )),
+                ),
         )
         .nest(
             SECURE_PREFIX,
             Router::new()
+                .route(GRAPHIQL_ROUTE, private_route)
                 .route(
-                    GRAPHIQL_ROUTE,
-                    axum::routing::get(private_graphiql_handler).post(private_graphql_handler),
+                    SUBSCRIPTION_ROUTE,
+                    axum::routing::get(private_graphql_subscription_handler),
                 )
                 .layer(
                     ServiceBuilder::new()
-                        // TODO: investigate if redirect loop can be avoided (maybe redirect to /secure, not the full route back form Keycloak)
                         .layer(axum::middleware::from_fn_with_state(
                             app.clone(),
                             set_authorization_header,
@@ -54,10 +127,20 @@ pub(crate) async fn get(app: App) -> Router {
                             app.clone(),
                             redirect_if_unauthorized,
                         ))
-                        .layer(keycloak_auth_layer(app.auth_ctx().instance())),
-                ),
+                        .layer(axum::middleware::from_fn_with_state(
+                            app.clone(),
+                            authenticate,
+                        )),
+                )
+                // The logout must work without a valid token, so it is added after the auth layers
+                .route(LOGOUT_ROUTE, axum::routing::post(logout_handler)),
         )
+        // Only matched routes are recorded and traced, so the routes are known labels
+        .route_layer(axum::middleware::from_fn(record_metrics))
+        .route_layer(axum::middleware::from_fn(trace_request))
         .with_state(app)
         .layer(Extension(schema))
         .layer(Extension(private_schema))
+        .layer(Extension(shutdown))
+        .layer(axum::middleware::from_fn(set_request_id))
 }
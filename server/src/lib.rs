@@ -0,0 +1,6 @@
+//! # Quick Microservice GraphQL TodoMVC Server
+//!
+//! The API of the server, which is run by the binary and called by the tests.
+
+pub mod api;
+pub mod shutdown;
@@ -2,43 +2,102 @@
 //!
 //! Runs a server with the GraphQL API.
 
-mod api;
+use anyhow::Context;
+use clap::Parser;
+use qgt_domain::config::Config;
+use qgt_metrics::mongodb::MongoDbMetricsLayer;
+use qgt_server::api;
+use qgt_server::shutdown::Shutdown;
+use qgt_telemetry::config::LogFormat;
+use qgt_telemetry::mongodb::MongoDbTracingLayer;
+use qgt_telemetry::OtlpExporter;
+use std::path::PathBuf;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::Layer;
+
+/// The command line arguments.
+#[derive(Parser)]
+#[command(version, about)]
+struct Args {
+    /// The TOML configuration file, whose values are overridden by environment variables.
+    #[arg(long, env = "QGT_CONFIG")]
+    config: Option<PathBuf>,
+    /// Print the configuration with secrets redacted and exit.
+    #[arg(long)]
+    print_config: bool,
+}
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // This loads the environment variables from `.env` if it exists
-    if let Err(e) = dotenv::dotenv() {
-        tracing::info!("The '.env' file could not be loaded.\n{}", &e);
+    let dotenv_result = dotenv::dotenv();
+    let args = Args::parse();
+
+    // Load and validate the configuration before anything is set up
+    let config = match Config::load(args.config.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+    if args.print_config {
+        print!("{}", config.redacted());
+        return Ok(());
     }
 
-    // Set a global tracing subscriber
+    // Set a global tracing subscriber, which also records the MongoDB command metrics and exports
+    // the traces, if an OTLP endpoint is configured
+    let exporter =
+        OtlpExporter::new(config.telemetry()).context("the OTLP exporter could not be set up")?;
     tracing::subscriber::set_global_default(
-        tracing_subscriber::FmtSubscriber::builder()
-            .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-            .finish(),
+        tracing_subscriber::registry()
+            .with(
+                match config.telemetry().log_format() {
+                    LogFormat::Text => tracing_subscriber::fmt::layer().boxed(),
+                    LogFormat::Json => tracing_subscriber::fmt::layer().json().boxed(),
+                }
+                .with_filter(tracing_subscriber::EnvFilter::from_default_env()),
+            )
+            .with(MongoDbMetricsLayer.with_filter(MongoDbMetricsLayer::filter()))
+            .with(
+                exporter
+                    .as_ref()
+                    .map(|exporter| exporter.layer().with_filter(OtlpExporter::filter())),
+            )
+            .with(exporter.as_ref().map(|exporter| {
+                exporter
+                    .mongodb_layer()
+                    .with_filter(MongoDbTracingLayer::filter())
+            })),
     )
     .expect("the global default tracing subscriber should be settable");
+    if let Err(e) = dotenv_result {
+        tracing::info!("The '.env' file could not be loaded.\n{}", &e);
+    }
 
-    // Load the app
-    let app = qgt_domain::app::App::new().await?;
+    // The remaining traces are exported however the server stops
+    let result = run(config).await;
+    if let Some(exporter) = exporter {
+        exporter.shutdown();
+    }
+    result
+}
+
+/// Load the app with the `config` and serve it until the shutdown.
+async fn run(config: Config) -> anyhow::Result<()> {
+    let shutdown_timeout = config.shutdown().timeout();
+    let app = qgt_domain::app::App::new(config).await?;
 
     // Start the server
-    tracing::info!(
-        "Starting server at address http://{}",
-        &app.server_config().address()
-    );
-    axum::serve(
-        tokio::net::TcpListener::bind::<std::net::SocketAddr>(
-            app.server_config()
-                .address()
-                .parse()
-                .expect("the server config address should be a valid socket address"),
-        )
+    let address = app.server_config().address().to_string();
+    tracing::info!("Starting server at address http://{address}");
+    let listener = tokio::net::TcpListener::bind(&address)
         .await
-        .expect("socket should be bindable"),
-        api::router::get(app).await.into_make_service(),
-    )
-    .await?;
-
+        .with_context(|| format!("the server address {address} could not be bound"))?;
+    let shutdown = Shutdown::new(shutdown_timeout);
+    let router = api::router::get(app, shutdown.clone()).await;
+    qgt_server::shutdown::serve(listener, router, &shutdown).await?;
+    tracing::info!("Server stopped");
     Ok(())
 }
@@ -0,0 +1,188 @@
+//! The graceful shutdown of the server.
+
+use async_graphql::futures_util::stream::SplitSink;
+use async_graphql::futures_util::stream::SplitStream;
+use async_graphql::futures_util::stream::TakeUntil;
+use async_graphql::futures_util::SinkExt;
+use async_graphql::futures_util::StreamExt;
+use axum::extract::ws::close_code;
+use axum::extract::ws::CloseFrame;
+use axum::extract::ws::Message;
+use axum::extract::ws::WebSocket;
+use axum::Router;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+use tokio_util::sync::WaitForCancellationFutureOwned;
+use tokio_util::task::TaskTracker;
+
+/// The messages received on a WebSocket, which end at the shutdown signal.
+pub type WebSocketMessages =
+    TakeUntil<SplitStream<WebSocket>, Pin<Box<WaitForCancellationFutureOwned>>>;
+
+/// Coordinates the graceful shutdown after a `SIGTERM` or `SIGINT`.
+///
+/// After the signal, the server stops accepting connections and in-flight requests get the shutdown
+/// timeout to finish. The subscriptions of open WebSocket connections are ended right away, since
+/// they never finish on their own, and a close frame asks the clients to reconnect to another
+/// instance. Connections whose clients do not complete the closing handshake are dropped at the
+/// deadline. WebSocket connections are upgraded out of the HTTP server, so they are tracked here.
+#[derive(Clone)]
+pub struct Shutdown {
+    signal: CancellationToken,
+    deadline: CancellationToken,
+    connections: TaskTracker,
+}
+
+impl Shutdown {
+    /// Construct a new [Shutdown], which waits for a `SIGTERM` or `SIGINT` in the background.
+    pub fn new(timeout: Duration) -> Self {
+        Self::with_signal(timeout, signal())
+    }
+
+    /// Construct a new [Shutdown], which waits for the `signal` future in the background.
+    pub fn with_signal(
+        timeout: Duration,
+        signal: impl Future<Output = ()> + Send + 'static,
+    ) -> Self {
+        let shutdown = Self {
+            signal: CancellationToken::new(),
+            deadline: CancellationToken::new(),
+            connections: TaskTracker::new(),
+        };
+        let background = shutdown.clone();
+        tokio::spawn(async move {
+            signal.await;
+            tracing::info!(
+                "Shutting down, closing {} open WebSocket connection(s) and waiting up to {}s for them and the in-flight requests",
+                background.connections.len(),
+                timeout.as_secs()
+            );
+            background.signal.cancel();
+            tokio::time::sleep(timeout).await;
+            background.deadline.cancel();
+        });
+        shutdown
+    }
+
+    /// Wait for the shutdown signal.
+    pub async fn signaled(&self) {
+        self.signal.cancelled().await;
+    }
+
+    /// Wait until the shutdown timeout elapsed after the signal.
+    pub async fn deadline(&self) {
+        self.deadline.cancelled().await;
+    }
+
+    /// Track the `connection` of a WebSocket, which is dropped at the shutdown deadline.
+    ///
+    /// Dropping the connection ends its subscription streams and closes the socket.
+    pub fn track_connection(
+        &self,
+        connection: impl Future<Output = ()>,
+    ) -> impl Future<Output = ()> {
+        let deadline = self.deadline.clone();
+        self.connections.track_future(async move {
+            tokio::select! {
+                () = connection => {}
+                () = deadline.cancelled() => {}
+            }
+        })
+    }
+
+    /// Split the `socket` into its sink and its received messages, which end at the shutdown
+    /// signal.
+    ///
+    /// Serving subscriptions from the messages ends them at the signal, after which the socket is
+    /// closed with [Shutdown::close_websocket].
+    pub fn split_websocket(
+        &self,
+        socket: WebSocket,
+    ) -> (SplitSink<WebSocket, Message>, WebSocketMessages) {
+        let (sink, stream) = socket.split();
+        (
+            sink,
+            stream.take_until(Box::pin(self.signal.clone().cancelled_owned())),
+        )
+    }
+
+    /// Close the WebSocket of [Shutdown::split_websocket] after its subscriptions ended.
+    ///
+    /// At the shutdown, the client gets a close frame and the closing handshake is completed once
+    /// it answers with its close frame. Otherwise the client or the connection ended the
+    /// subscriptions, and the socket is just dropped.
+    pub async fn close_websocket(
+        &self,
+        mut sink: SplitSink<WebSocket, Message>,
+        messages: WebSocketMessages,
+    ) {
+        if !self.signal.is_cancelled() {
+            return;
+        }
+        let close = Message::Close(Some(CloseFrame {
+            code: close_code::AWAY,
+            reason: "The server is shutting down".into(),
+        }));
+        if sink.send(close).await.is_err() {
+            return;
+        }
+        let mut stream = messages.into_inner();
+        while let Some(Ok(message)) = stream.next().await {
+            if let Message::Close(_) = message {
+                break;
+            }
+        }
+    }
+
+    /// Wait until all tracked WebSocket connections are closed or dropped at the deadline.
+    pub async fn connections_closed(&self) {
+        self.connections.close();
+        self.connections.wait().await;
+    }
+}
+
+/// Serve the `router` on the `listener` until the `shutdown` is complete.
+///
+/// In-flight requests and WebSocket connections are cut off at the deadline.
+pub async fn serve(listener: TcpListener, router: Router, shutdown: &Shutdown) -> io::Result<()> {
+    let signal = shutdown.clone();
+    let result = tokio::select! {
+        result = axum::serve(listener, router.into_make_service())
+            .with_graceful_shutdown(async move { signal.signaled().await }) => result,
+        () = shutdown.deadline() => {
+            tracing::warn!("Requests still in flight at the shutdown deadline were cut off");
+            Ok(())
+        }
+    };
+    if result.is_ok() {
+        shutdown.connections_closed().await;
+    }
+    result
+}
+
+/// Wait for a `SIGTERM` or `SIGINT`.
+async fn signal() {
+    let interrupt = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("the SIGINT handler should be installable");
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("the SIGTERM handler should be installable")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = interrupt => {}
+        () = terminate => {}
+    }
+}
@@ -1,20 +1,72 @@
+use async_graphql::futures_util::stream::BoxStream;
+use async_graphql::futures_util::StreamExt;
+use async_graphql::Executor;
 use async_graphql::ServerError;
 use async_graphql::{Request, Response, Variables};
 use cucumber::Parameter;
 use cucumber::World;
 use derive_more::derive::Deref;
 use derive_more::derive::FromStr;
+use qgt_auth::token::TokenError;
+use qgt_domain::owner::Owner;
+use qgt_domain::permission::Permissions;
+use qgt_domain::private_schema::PrivateSchema;
+use qgt_domain::private_schema::SchemaBuilder as PrivateSchemaBuilder;
+use qgt_domain::request_id::RequestId;
 use qgt_domain::schema::{Schema, SchemaBuilder};
+use qgt_server::shutdown::Shutdown;
+use std::time::Duration;
 use std::{collections::HashMap, fmt::Debug};
+use tower::ServiceExt;
+
+/// The time a started subscription is polled to register it before the next step.
+const SUBSCRIPTION_START_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// A response of the HTTP API.
+#[derive(Debug)]
+pub struct HttpResponse {
+    pub status: axum::http::StatusCode,
+    pub headers: axum::http::HeaderMap,
+    /// The JSON body, which is null for empty and other bodies.
+    pub body: serde_json::Value,
+}
+
+/// A server running on a local port, which receives its shutdown signal from the test.
+pub struct RunningServer {
+    pub address: std::net::SocketAddr,
+    /// Sends the shutdown signal.
+    pub signal: Option<tokio::sync::oneshot::Sender<()>>,
+    /// The task of the server, which completes after the shutdown.
+    pub served: tokio::task::JoinHandle<std::io::Result<()>>,
+    /// The open WebSocket connection to the server.
+    pub connection: Option<tokio::net::TcpStream>,
+}
 
 #[derive(World)]
 #[world(init = Self::new)]
 pub struct AppWorld {
     pub app: qgt_domain::app::App,
     pub schema: Schema,
+    pub private_schema: PrivateSchema,
     pub state: HashMap<&'static str, serde_json::Value>, // TODO: change to only be serde_json::Value
     pub last_query_operation: String,
     pub last_response: Response,
+    /// The signed-in user, which is added as [Owner] to all requests.
+    pub owner: Option<Owner>,
+    /// The permissions of the signed-in user, which are added to all requests.
+    ///
+    /// Without them, requests get all permissions of the schema.
+    pub permissions: Option<Permissions>,
+    /// The request id, which is added to all requests.
+    pub request_id: Option<RequestId>,
+    /// The error of the last rejected sign-in with a token.
+    pub token_error: Option<TokenError>,
+    /// The last response of the HTTP API.
+    pub last_http_response: Option<HttpResponse>,
+    /// The server running on a local port.
+    pub server: Option<RunningServer>,
+    /// The operation and stream of the started subscription.
+    subscription: Option<(String, BoxStream<'static, Response>)>,
     last_response_data: serde_json::Value,
     last_response_json: String,
 }
@@ -51,17 +103,27 @@ impl Debug for AppWorld {
 
 impl AppWorld {
     pub async fn new() -> Self {
-        let app = qgt_domain::app::App::new()
+        let config = qgt_domain::config::Config::load(None).expect("the config should be valid");
+        let app = qgt_domain::app::App::new(config)
             .await
             .expect("the app should be constructed");
         let schema = SchemaBuilder::default().build(app.clone());
+        let private_schema = PrivateSchemaBuilder::default().build(app.clone());
 
         Self {
             app,
             schema,
+            private_schema,
             state: HashMap::new(),
             last_query_operation: String::default(),
             last_response: async_graphql::Response::default(),
+            owner: None,
+            permissions: None,
+            request_id: None,
+            token_error: None,
+            last_http_response: None,
+            server: None,
+            subscription: None,
             last_response_data: serde_json::Value::Null,
             last_response_json: String::default(),
         }
@@ -74,7 +136,87 @@ impl AppWorld {
         query: &'static str,
     ) -> GraphQLQueryBuilder<'_, 'static> {
         self.last_query_operation = query_operation;
-        GraphQLQueryBuilder::new(&self.schema, query)
+        GraphQLQueryBuilder::new(
+            &self.schema,
+            query,
+            self.owner.clone(),
+            self.permissions.clone(),
+            self.request_id.clone(),
+        )
+    }
+
+    /// Get a [GraphQLQueryBuilder] for the private schema of the secured endpoint.
+    pub fn private_graphql(
+        &mut self,
+        query_operation: String,
+        query: &'static str,
+    ) -> GraphQLQueryBuilder<'_, 'static, PrivateSchema> {
+        self.last_query_operation = query_operation;
+        GraphQLQueryBuilder::new(
+            &self.private_schema,
+            query,
+            self.owner.clone(),
+            self.permissions.clone(),
+            self.request_id.clone(),
+        )
+    }
+
+    /// Send the HTTP `request` to the router of the server and store the response.
+    ///
+    /// The router is never shut down.
+    pub async fn http(&mut self, request: axum::extract::Request) {
+        let shutdown = Shutdown::with_signal(Duration::ZERO, std::future::pending());
+        let router = qgt_server::api::router::get(self.app.clone(), shutdown).await;
+        let response = router
+            .oneshot(request)
+            .await
+            .expect("the router should respond");
+        let (parts, body) = response.into_parts();
+        let body = axum::body::to_bytes(body, usize::MAX)
+            .await
+            .expect("the response body should be readable");
+
+        self.last_http_response = Some(HttpResponse {
+            status: parts.status,
+            headers: parts.headers,
+            body: serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null),
+        });
+    }
+
+    /// Send the GraphQL `query` with the `variables` to the HTTP endpoint at the `uri` with the
+    /// bearer `token` and store the response like the responses of the schemas.
+    pub async fn http_graphql(
+        &mut self,
+        uri: &str,
+        token: &str,
+        query_operation: String,
+        query: &str,
+        variables: serde_json::Value,
+    ) {
+        let request = axum::extract::Request::post(uri)
+            .header(axum::http::header::AUTHORIZATION, format!("Bearer {token}"))
+            .header(axum::http::header::CONTENT_TYPE, "application/json")
+            .body(axum::body::Body::from(
+                serde_json::json!({ "query": query, "variables": variables }).to_string(),
+            ))
+            .expect("the GraphQL request should be valid");
+        self.http(request).await;
+
+        let body = &self.last_http_response().body;
+        let mut response = Response::new(
+            async_graphql::Value::from_json(body["data"].clone())
+                .expect("the response data should be valid"),
+        );
+        response.errors = serde_json::from_value(body["errors"].clone()).unwrap_or_default();
+        self.last_query_operation = query_operation;
+        self.save_last_response(response);
+    }
+
+    /// Get the last response of the HTTP API.
+    pub fn last_http_response(&self) -> &HttpResponse {
+        self.last_http_response
+            .as_ref()
+            .expect("an HTTP request should be sent")
     }
 
     /// Store the response.
@@ -92,6 +234,43 @@ impl AppWorld {
         self.last_response = response;
     }
 
+    /// Start a subscription.
+    ///
+    /// The stream is polled once, so the subscription is registered before the next step.
+    pub async fn start_subscription(
+        &mut self,
+        subscription_operation: String,
+        mut stream: BoxStream<'static, Response>,
+    ) {
+        let response = tokio::time::timeout(SUBSCRIPTION_START_TIMEOUT, stream.next()).await;
+        assert!(
+            response.is_err(),
+            "the subscription should not respond before a change: {response:?}"
+        );
+        self.subscription = Some((subscription_operation, stream));
+    }
+
+    /// Wait for the next response of the started subscription and store it.
+    ///
+    /// The subscription operation becomes the last query operation.
+    ///
+    /// Returns `false` if no response was received within the `timeout`.
+    pub async fn receive_subscription_response(&mut self, timeout: Duration) -> bool {
+        let (subscription_operation, stream) = self
+            .subscription
+            .as_mut()
+            .expect("a subscription should be started");
+        let subscription_operation = subscription_operation.clone();
+        match tokio::time::timeout(timeout, stream.next()).await {
+            Ok(Some(response)) => {
+                self.last_query_operation = subscription_operation;
+                self.save_last_response(response);
+                true
+            }
+            _ => false,
+        }
+    }
+
     /// Get the last response data.
     ///
     /// This function will strip the root with the operation from the data and return only the
@@ -105,6 +284,34 @@ impl AppWorld {
             .clone()
     }
 
+    /// Get the count of queries the database server executed so far.
+    ///
+    /// Counts the `find` and `aggregate` commands (`count_documents` is an aggregation) of the
+    /// whole server, so it must not be used while other clients access the server.
+    pub async fn database_query_count(&self) -> u64 {
+        let status = self
+            .app
+            .db()
+            .get_admin()
+            .run_command(bson::doc! { "serverStatus": 1 })
+            .await
+            .expect("the server status should be retrievable");
+        let commands = status
+            .get_document("metrics")
+            .and_then(|metrics| metrics.get_document("commands"))
+            .expect("the server status should contain command metrics");
+
+        ["find", "aggregate"]
+            .iter()
+            .map(|command| {
+                commands
+                    .get_document(command)
+                    .and_then(|c| c.get_i64("total"))
+                    .unwrap_or(0) as u64
+            })
+            .sum()
+    }
+
     /// Get the last response errors.
     pub fn get_last_response_errors(&self) -> &Vec<ServerError> {
         &self.last_response.errors
@@ -117,28 +324,40 @@ impl AppWorld {
 }
 
 /// Builder for GraphQL requests.
-pub struct GraphQLQueryBuilder<'s, 'o> {
-    schema: &'s Schema,
+pub struct GraphQLQueryBuilder<'s, 'o, E = Schema> {
+    schema: &'s E,
     query: &'o str,
     variables: Option<serde_json::Value>,
+    owner: Option<Owner>,
+    permissions: Option<Permissions>,
+    request_id: Option<RequestId>,
 }
 
-impl<'s, 'o> GraphQLQueryBuilder<'s, 'o> {
-    pub fn new(schema: &'s Schema, query: &'o str) -> Self {
+impl<'s, 'o, E: Executor> GraphQLQueryBuilder<'s, 'o, E> {
+    pub fn new(
+        schema: &'s E,
+        query: &'o str,
+        owner: Option<Owner>,
+        permissions: Option<Permissions>,
+        request_id: Option<RequestId>,
+    ) -> Self {
         Self {
             schema,
             query,
             variables: None,
+            owner,
+            permissions,
+            request_id,
         }
     }
 
-    // /// Set variables
-    // ///
-    // /// Will overwrite any value, if present.
-    // pub fn variables(mut self, variables: serde_json::Value) -> Self {
-    //     self.variables = Some(variables);
-    //     self
-    // }
+    /// Set variables
+    ///
+    /// Will overwrite any value, if present.
+    pub fn variables(mut self, variables: serde_json::Value) -> Self {
+        self.variables = Some(variables);
+        self
+    }
 
     /// Add variable for the request.
     pub fn add_variable(mut self, key: impl Into<String>, value: serde_json::Value) -> Self {
@@ -150,17 +369,41 @@ impl<'s, 'o> GraphQLQueryBuilder<'s, 'o> {
         self
     }
 
-    /// Execute the request against the provided schema.
-    pub async fn execute(self) -> Response {
+    /// Build the request with the variables, the owner, the permissions and the request id.
+    fn request(self) -> Request {
         let mut request = Request::new(self.query);
         if let Some(variables) = self.variables {
             request = request.variables(Variables::from_json(variables));
         }
+        if let Some(owner) = self.owner {
+            request = request.data(owner);
+        }
+        if let Some(permissions) = self.permissions {
+            request = request.data(permissions);
+        }
+        if let Some(request_id) = self.request_id {
+            request = request.data(request_id);
+        }
+        request
+    }
+
+    /// Execute the request against the provided schema.
+    pub async fn execute(self) -> Response {
+        let schema = self.schema;
+        let request = self.request();
         tracing::debug!("GraphQL execute request:\n{request:?}");
-        let response = self.schema.execute(request).await;
+        let response = schema.execute(request).await;
         tracing::debug!("GraphQL execute response:\n{response:?}");
         response
     }
+
+    /// Execute the subscription request against the provided schema.
+    pub fn subscribe(self) -> BoxStream<'static, Response> {
+        let schema = self.schema;
+        let request = self.request();
+        tracing::debug!("GraphQL subscription request:\n{request:?}");
+        schema.execute_stream(request, None)
+    }
 }
 
 /// A custom parameter to support `bool`
@@ -0,0 +1,80 @@
+@access_token
+Feature: Personal access tokens
+  As a user
+  I want to create personal access tokens for my scripts and CI
+
+  Scenario: If a personal access token is created, it is accepted with its scopes
+    Given the user "alice" is signed in with the roles "todo:write, tag:write"
+    When createPersonalAccessToken is sent with body
+      """
+      {"name": "ci", "scopes": ["TODO_WRITE"]}
+      """
+    Then the response has no errors
+    And the response data JSON node "$.personalAccessToken.owner" should have the value "alice"
+    And the response data JSON node "$.personalAccessToken.name" should have the value "ci"
+    And the created personal access token starts with "qgt_pat_"
+    And the created personal access token is accepted for the user "alice" with the roles "todo:write"
+
+  Scenario: If a personal access token exceeds the permissions of the user, it is forbidden
+    Given the user "alice" is signed in with the roles "todo:write"
+    When createPersonalAccessToken is sent with body
+      """
+      {"name": "ci", "scopes": ["TODO_WRITE", "TAG_ADMIN"]}
+      """
+    Then the response should have errors
+    And a response error with code "FORBIDDEN" exists
+
+  Scenario: If a personal access token already expired, it is rejected
+    Given the user "alice" is signed in with the roles "todo:write"
+    When createPersonalAccessToken is sent with body
+      """
+      {"name": "ci", "scopes": ["TODO_WRITE"], "expires": "2000-01-01T00:00:00Z"}
+      """
+    Then the response should have errors
+    And a response error with code "VALIDATION_FAILED" exists
+
+  Scenario: If personal access tokens are requested, only the tokens of the user are returned
+    Given the user "alice" is signed in with the roles "todo:write"
+    And createPersonalAccessToken is sent with body
+      """
+      {"name": "ci", "scopes": ["TODO_WRITE"]}
+      """
+    And the user "bob" is signed in with the roles "todo:write"
+    When personalAccessTokens is sent
+    Then the response has no errors
+    And the response data is a list with 0 entries
+
+  Scenario: If a personal access token is revoked, it is rejected
+    Given the user "alice" is signed in with the roles "todo:write"
+    And createPersonalAccessToken is sent with body
+      """
+      {"name": "ci", "scopes": ["TODO_WRITE"]}
+      """
+    When revokePersonalAccessToken is sent for the created token
+    Then the response has no errors
+    And the created personal access token is rejected
+
+  Scenario: If a personal access token of another user is revoked, it is not found
+    Given the user "alice" is signed in with the roles "todo:write"
+    And createPersonalAccessToken is sent with body
+      """
+      {"name": "ci", "scopes": ["TODO_WRITE"]}
+      """
+    And the user "bob" is signed in with the roles "todo:write"
+    When revokePersonalAccessToken is sent for the created token
+    Then the response should have errors
+    And a response error with code "NOT_FOUND" exists
+    And the created personal access token is accepted for the user "alice" with the roles "todo:write"
+
+  Scenario: If a personal access token creates a personal access token, it is forbidden
+    Given the user "alice" is signed in with the roles "todo:write"
+    And createPersonalAccessToken is sent with body
+      """
+      {"name": "ci", "scopes": ["TODO_WRITE"]}
+      """
+    When createPersonalAccessToken is sent with the created token and body
+      """
+      {"name": "forever", "scopes": ["TODO_WRITE"]}
+      """
+    Then the response should have errors
+    And a response error with code "FORBIDDEN" exists
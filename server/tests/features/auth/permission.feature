@@ -0,0 +1,45 @@
+@auth
+Feature: Permissions
+  As an administrator
+  I want to control which users can change todos and tags by their roles
+
+  Scenario: If a user with the role todo:write creates a todo, it is created
+    Given the user "alice" is signed in with the roles "todo:write"
+    When createTodo is sent with body
+      """
+      {"completed": false, "title": "test"}
+      """
+    Then the response has no errors
+    And the response data JSON node "$.title" should have the value "test"
+
+  Scenario: If a user without the role todo:write creates a todo, it is forbidden
+    Given the user "alice" is signed in with the roles "tag:write"
+    When createTodo is sent with body
+      """
+      {"completed": false, "title": "test"}
+      """
+    Then the response should have errors
+    And a response error with code "FORBIDDEN" exists
+
+  Scenario: If a user with the role tag:write creates a tag, it is created
+    Given the user "alice" is signed in with the roles "tag:write"
+    When createTag is sent with body
+      """
+      {"name": "test"}
+      """
+    Then the response has no errors
+    And the response data JSON node "$.name" should have the value "test"
+
+  Scenario: If a user without the role tag:admin removes tags, it is forbidden
+    Given a tag with name "first" exists
+    And the user "alice" is signed in with the roles "todo:write, tag:write"
+    When removeTags is sent with ids for "first"
+    Then the response should have errors
+    And a response error with code "FORBIDDEN" exists
+    And the tag with name "first" is in the database
+
+  Scenario: If a user with the role tag:admin removes tags, it is accepted
+    Given a tag with name "first" exists
+    And the user "alice" is signed in with the roles "tag:admin"
+    When removeTags is sent with ids for "first"
+    Then the response has no errors
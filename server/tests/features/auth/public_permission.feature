@@ -0,0 +1,49 @@
+@auth
+Feature: Permissions of the public endpoint
+  As an administrator
+  I want anonymous requests to only have the permissions of the public roles
+  And signed-in users to never have fewer permissions than anonymous requests
+
+  Scenario: If an anonymous user removes tags without the public role tag:admin, it is forbidden
+    Given a tag with name "first" exists
+    When removeTags is sent with ids for "first"
+    Then the response should have errors
+    And a response error with code "FORBIDDEN" exists
+    And the tag with name "first" is in the database
+
+  Scenario: If an anonymous user posts a todo with REST without public roles, it is forbidden
+    Given the public endpoint authenticates bearer tokens optionally without public roles
+    When a todo is posted with REST with body
+      """
+      {"title": "test"}
+      """
+    Then the HTTP response status is 403
+    And the todos with titles "test" are not in the database
+
+  Scenario: If a signed-in user without the write role posts a todo with REST, the public roles allow it
+    Given the public endpoint authenticates bearer tokens optionally
+    And the REST requests are sent with a token of the user "alice" with the roles "offline_access"
+    When a todo is posted with REST with body
+      """
+      {"title": "test"}
+      """
+    Then the HTTP response status is 201
+    And the todo with title "test" is owned by the user "alice"
+
+  Scenario: If a signed-in user without the write role creates a todo with GraphQL, the public roles allow it
+    Given the public endpoint authenticates bearer tokens optionally
+    When createTodo is sent to the public endpoint with a token of the user "alice" with the roles "offline_access" and body
+      """
+      {"completed": false, "title": "test"}
+      """
+    Then the response has no errors
+    And the response data JSON node "$.owner" should have the value "alice"
+
+  Scenario: If a signed-in user has a role beyond the public roles, it adds to them
+    Given the public endpoint authenticates bearer tokens optionally without public roles
+    When createTodo is sent to the public endpoint with a token of the user "alice" with the roles "todo:write" and body
+      """
+      {"completed": false, "title": "test"}
+      """
+    Then the response has no errors
+    And the response data JSON node "$.owner" should have the value "alice"
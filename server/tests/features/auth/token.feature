@@ -0,0 +1,42 @@
+@auth
+Feature: Token validation
+  As a user
+  I want the secured endpoint to only accept valid tokens
+
+  Scenario: If a user signs in with a valid token, the todos are owned by the subject of the token
+    Given the user "alice" is signed in with a valid token with the roles "todo:write"
+    When createTodo is sent with body
+      """
+      {"completed": false, "title": "test"}
+      """
+    Then the response has no errors
+    And the response data JSON node "$.owner" should have the value "alice"
+
+  Scenario: If a user signs in with a valid token without roles, changes are forbidden
+    Given the user "alice" is signed in with a valid token with the roles "offline_access"
+    When createTodo is sent with body
+      """
+      {"completed": false, "title": "test"}
+      """
+    Then the response should have errors
+    And a response error with code "FORBIDDEN" exists
+
+  Scenario: If a user signs in with an expired token, it is rejected
+    When the user "alice" signs in with an expired token
+    Then the token is rejected
+
+  Scenario: If a user signs in with a token, which just expired, it is accepted within the leeway
+    When the user "alice" signs in with a token expired 10 seconds ago
+    Then the token is accepted
+
+  Scenario: If a user signs in with a token of another issuer, it is rejected
+    When the user "alice" signs in with a token of the issuer "http://localhost:8080/realms/other"
+    Then the token is rejected
+
+  Scenario: If a user signs in with a token for another audience, it is rejected
+    When the user "alice" signs in with a token for the audience "other"
+    Then the token is rejected
+
+  Scenario: If a user signs in with a malformed token, it is rejected
+    When a user signs in with a malformed token
+    Then the token is rejected
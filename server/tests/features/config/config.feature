@@ -0,0 +1,31 @@
+@config
+Feature: Configuration
+  As an operator
+  I want invalid configurations to be reported before the server starts
+
+  Scenario: If a configuration file has invalid values, all of them are reported
+    When the configuration is loaded with the file "invalid.toml"
+    Then the configuration is rejected with errors for "server.port, server.prot, server.shutdown_timeout, mongodb.sharded, auth.public_mode, metrics, telemetry.log_format"
+
+  Scenario: If a configuration file does not exist, it is reported
+    When the configuration is loaded with the file "missing.toml"
+    Then the configuration is rejected with errors for "The configuration file"
+
+  Scenario: If a configuration is printed, secrets are redacted
+    When the configuration is loaded with the file "valid.toml"
+    Then the printed configuration contains "account,qgt"
+    And the printed configuration contains "<redacted>"
+    And the printed configuration does not contain "not-so-secret"
+
+  Scenario: If a configuration file has an OTLP endpoint, it is configured
+    When the configuration is loaded with the file "valid.toml"
+    Then the printed configuration contains "http://localhost:4318"
+
+  Scenario: If a configuration file is loaded, its values do not change the environment
+    When the configuration is loaded with the file "valid.toml"
+    Then the printed configuration contains "http://localhost:4318"
+    And the environment is not changed
+
+  Scenario: If a configuration file sets every field of the configuration, all keys are known
+    When the configuration is loaded with a file setting every field of the configuration structs
+    Then the configuration has no unknown keys
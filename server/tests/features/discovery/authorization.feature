@@ -0,0 +1,14 @@
+@discovery
+Feature: Authorization URL
+  As a user of the secured endpoint
+  I want to be redirected to the log-in of the provider with valid parameters
+
+  Scenario: If the authorization URL is built, its parameters are encoded
+    Given the discovery document "keycloak.json"
+    When the authorization URL is built for the redirect URI "http://localhost:3000/secure/login?a=1&b=2" and the state "return path/with spaces"
+    Then the authorization URL starts with "http://localhost:8080/realms/qgt/protocol/openid-connect/auth?"
+    And the authorization URL has the parameter "redirect_uri" with the value "http://localhost:3000/secure/login?a=1&b=2"
+    And the authorization URL has the parameter "state" with the value "return path/with spaces"
+    And the authorization URL has the parameter "client_id" with the value "qgt"
+    And the authorization URL has the parameter "response_type" with the value "code"
+    And the authorization URL has the parameter "code_challenge_method" with the value "S256"
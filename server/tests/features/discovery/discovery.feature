@@ -0,0 +1,35 @@
+@discovery
+Feature: OpenID Connect discovery
+  As an operator
+  I want the endpoints and signing keys to be discovered from the issuer of any provider
+
+  Scenario: If a Keycloak realm is discovered, all its endpoints are used
+    Given the discovery document "keycloak.json"
+    And a provider serves the discovery document
+    When the provider is discovered
+    Then the discovered authorization_endpoint is "http://localhost:8080/realms/qgt/protocol/openid-connect/auth"
+    And the discovered token_endpoint is "http://localhost:8080/realms/qgt/protocol/openid-connect/token"
+    And the discovered end_session_endpoint is "http://localhost:8080/realms/qgt/protocol/openid-connect/logout"
+    And the discovered revocation_endpoint is "http://localhost:8080/realms/qgt/protocol/openid-connect/revoke"
+    And 1 signing key of the provider is discovered
+
+  Scenario: If a provider without end-session and revocation endpoints is discovered, they are missing
+    Given the discovery document "dex.json"
+    And a provider serves the discovery document
+    When the provider is discovered
+    Then the discovered token_endpoint is "http://localhost:5556/dex/token"
+    And the discovered end_session_endpoint is missing
+    And the discovered revocation_endpoint is missing
+    And 1 signing key of the provider is discovered
+
+  Scenario: If the discovery document has no JWKS URL, it is rejected
+    Given the discovery document "invalid.json"
+    And a provider serves the discovery document of another issuer
+    When the provider is discovered
+    Then the discovery is rejected with an error containing "decoding response body"
+
+  Scenario: If the discovered issuer is not the configured one, it is rejected
+    Given the discovery document "dex.json"
+    And a provider serves the discovery document of another issuer
+    When the provider is discovered
+    Then the discovery is rejected with an error containing "does not match the configured issuer"
@@ -0,0 +1,17 @@
+@discovery
+Feature: RP-initiated logout
+  As a user of the secured endpoint
+  I want the logout to end my session at the provider
+
+  Scenario: If the provider has an end-session endpoint, the logout redirects to it with the ID token
+    Given the discovery document "keycloak.json"
+    When the end-session URL is built for the ID token "id.token"
+    Then the end-session URL starts with "http://localhost:8080/realms/qgt/protocol/openid-connect/logout?"
+    And the end-session URL has the parameter "id_token_hint" with the value "id.token"
+    And the end-session URL has the parameter "client_id" with the value "qgt"
+    And the end-session URL has the post logout redirect URI
+
+  Scenario: If the provider has no end-session endpoint, there is no end-session URL
+    Given the discovery document "dex.json"
+    When the end-session URL is built for the ID token "id.token"
+    Then there is no end-session URL
@@ -0,0 +1,8 @@
+@health
+Feature: Readiness
+  As an operator
+  I want to know if the server can reach its dependencies
+
+  Scenario: If the dependencies are reachable, the server is ready
+    Then the database is reachable
+    And 1 signing key is available
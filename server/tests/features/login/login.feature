@@ -0,0 +1,34 @@
+@login
+Feature: Log-in protection
+  As a user of the secured endpoint
+  I want my log-in to be protected against CSRF and code injection
+
+  Scenario: If a log-in is started, the code challenge is derived from the code verifier
+    Given a log-in is started for the return path "/secure/api/graphql"
+    Then the code challenge is the S256 hash of the code verifier
+
+  Scenario: If the state is verified for the browser of the log-in, the return path is accepted
+    Given a log-in is started for the return path "/secure/api/graphql?query=todos"
+    When the state is verified with the code verifier of the log-in
+    Then the state is accepted with the return path "/secure/api/graphql?query=todos"
+
+  Scenario: If the state is verified with the code verifier of another log-in, it is rejected
+    Given a log-in is started for the return path "/secure/api/graphql"
+    When the state is verified with the code verifier of another log-in
+    Then the state is rejected
+
+  Scenario: If the state was signed with another secret, it is rejected
+    Given a log-in is started for the return path "/secure/api/graphql"
+    When the state is verified by a signer with another secret
+    Then the state is rejected
+
+  Scenario: If the return path of the state was changed, it is rejected
+    Given a log-in is started for the return path "/secure/api/graphql"
+    When the state is verified with the return path changed to "/secure/logout"
+    Then the state is rejected
+
+  Scenario: If the return path is another site, no log-in is started
+    Then no log-in can be started for the return path "https://evil.example/"
+
+  Scenario: If the return path is protocol-relative, no log-in is started
+    Then no log-in can be started for the return path "//evil.example/"
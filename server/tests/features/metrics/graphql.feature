@@ -0,0 +1,37 @@
+@metrics
+Feature: GraphQL metrics
+  As an operator
+  I want to know the rates, durations and errors of the GraphQL operations
+
+  Scenario: If an operation is executed, it is recorded with its resolvers
+    Given the metrics of the public operations are noted
+    When createTodo is sent with body
+      """
+      {"completed": false, "title": "test"}
+      """
+    Then the response has no errors
+    And 1 more public operation was recorded
+    And 0 more public operations with errors were recorded
+    And the resolver of the field "createTodo" of the type "MutationRoot" of the public schema was recorded
+
+  Scenario: If an operation fails, its error is recorded
+    Given the metrics of the public operations are noted
+    When updateTodo is sent with body for a non-existing todo
+      """
+      {"completed": true, "id": "replaced-by-step-function"}
+      """
+    Then the response should have errors
+    And 1 more public operation was recorded
+    And 1 more public operation with errors was recorded
+
+  Scenario: If operations with different names are executed, each name is recorded separately
+    When the operations "First, Second, First" are executed on a schema recording at most 2 operation names
+    Then the operation "First" was recorded 2 times
+    And the operation "Second" was recorded 1 time
+
+  Scenario: If more operation names than the limit are executed, the others are recorded together
+    When the operations "First, Second, Third, Fourth, First" are executed on a schema recording at most 2 operation names
+    Then the operation "First" was recorded 2 times
+    And the operation "Second" was recorded 1 time
+    And the operation "Third" was recorded 0 times
+    And the operation "other" was recorded 2 times
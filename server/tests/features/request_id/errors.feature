@@ -0,0 +1,23 @@
+@request_id
+Feature: Request ids in errors
+  As a client developer
+  I want errors to contain the id of the request
+  so that I can find the log lines of a failed request
+
+  Scenario: If an operation fails, its errors contain the request id
+    Given the request id "req-42"
+    When updateTodo is sent with body for a non-existing todo
+      """
+      {"completed": true, "id": "replaced-by-step-function"}
+      """
+    Then the response should have errors
+    And every response error has the request id "req-42"
+
+  Scenario: If a query is invalid, its errors contain the request id
+    Given the request id "req-43"
+    When updateTodo is sent with body for a non-existing todo
+      """
+      {"completed": "not-a-boolean", "id": "replaced-by-step-function"}
+      """
+    Then the response should have errors
+    And every response error has the request id "req-43"
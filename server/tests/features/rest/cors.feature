@@ -0,0 +1,17 @@
+@rest
+Feature: Cross-origin requests of the REST API
+  As a user of a todo-backend frontend on another origin
+  I want my browser to allow the requests to the REST API
+
+  Scenario: If a CORS preflight is sent, the requests of the frontends are allowed
+    When a CORS preflight for PATCH "/todos/0123456789abcdef01234567" is sent from the origin "https://frontend.example.com"
+    Then the HTTP response status is 204
+    And the HTTP response header "access-control-allow-origin" is "*"
+    And the HTTP response header "access-control-allow-methods" is "GET, POST, PATCH, DELETE, OPTIONS"
+    And the HTTP response header "access-control-allow-headers" is "Authorization, Content-Type, X-Request-Id"
+
+  Scenario: If the todos are requested, the location header is exposed to the frontends
+    When the todos are requested with REST
+    Then the HTTP response status is 200
+    And the HTTP response header "access-control-allow-origin" is "*"
+    And the HTTP response header "access-control-expose-headers" is "Location, X-Request-Id"
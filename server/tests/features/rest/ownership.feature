@@ -0,0 +1,47 @@
+@rest
+Feature: Todo ownership in the REST API
+  As a signed-in user of a todo-backend frontend
+  I want to only see and change my own todos
+
+  Background:
+    Given the public endpoint authenticates bearer tokens optionally
+    And the todos with titles "mine" exist for the user "alice"
+    And the todos with titles "other" exist for the user "bob"
+    And the todos with titles "anonymous" exist
+
+  Scenario: If a signed-in user requests the todos, only the todos of the user are returned
+    Given the REST requests are sent with a token of the user "alice" with the roles "todo:write"
+    When the todos are requested with REST
+    Then the HTTP response status is 200
+    And the HTTP response is a list with 1 entry
+    And the HTTP response JSON node "$[0].title" has the value "mine"
+
+  Scenario: If an anonymous user requests the todos, only the todos without owner are returned
+    When the todos are requested with REST
+    Then the HTTP response status is 200
+    And the HTTP response is a list with 1 entry
+    And the HTTP response JSON node "$[0].title" has the value "anonymous"
+
+  Scenario: If a signed-in user requests the todo of another user, it is not found
+    Given the REST requests are sent with a token of the user "alice" with the roles "todo:write"
+    When the todo "other" is requested with REST
+    Then the HTTP response status is 404
+
+  Scenario: If a signed-in user posts a todo, it is owned by the user
+    Given the REST requests are sent with a token of the user "alice" with the roles "todo:write"
+    When a todo is posted with REST with body
+      """
+      {"title": "test"}
+      """
+    Then the HTTP response status is 201
+    And the todo with title "test" is owned by the user "alice"
+
+  Scenario: If a signed-in user without the write role posts a todo without public roles, it is forbidden
+    Given the public endpoint authenticates bearer tokens optionally without public roles
+    And the REST requests are sent with a token of the user "alice" with the roles "offline_access"
+    When a todo is posted with REST with body
+      """
+      {"title": "test"}
+      """
+    Then the HTTP response status is 403
+    And the todos with titles "test" are not in the database
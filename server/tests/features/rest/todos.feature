@@ -0,0 +1,95 @@
+@rest
+Feature: REST API of the todos
+  As a user of a todo-backend frontend
+  I want to manage the todos over the REST API
+
+  Scenario: If the todos are requested, they are returned in their order
+    Given the todos with titles "first, second" exist
+    When the todos are requested with REST
+    Then the HTTP response status is 200
+    And the HTTP response is a list with 2 entries
+    And the HTTP response JSON node "$[0].title" has the value "first"
+    And the HTTP response JSON node "$[1].title" has the value "second"
+    And the HTTP response JSON node "$[0].url" is the url of the todo "first"
+
+  Scenario: If a todo is posted, it is created with its url as location
+    When a todo is posted with REST with body
+      """
+      {"title": "test"}
+      """
+    Then the HTTP response status is 201
+    And the HTTP response header "location" is the url of the todo "test"
+    And the HTTP response JSON node "$.url" is the url of the todo "test"
+    And the HTTP response JSON node "$.title" has the value "test"
+    And the HTTP response JSON node "$.completed" has the boolean value false
+    And the todo with title "test" is in the database
+
+  Scenario: If a todo is posted with an order, it is created at that order
+    When a todo is posted with REST with body
+      """
+      {"title": "test", "completed": true, "order": 7}
+      """
+    Then the HTTP response status is 201
+    And the HTTP response JSON node "$.order" has the integer value 7
+    And the HTTP response JSON node "$.completed" has the boolean value true
+
+  Scenario: If a todo is posted with a too large order, it is not created
+    When a todo is posted with REST with body
+      """
+      {"title": "test", "order": 18446744073709551615}
+      """
+    Then the HTTP response status is 422
+    And the HTTP response JSON node "$.code" has the value "VALIDATION_FAILED"
+    And the todos with titles "test" are not in the database
+
+  Scenario: If a todo is requested, it is returned
+    Given the todos with titles "first, second" exist
+    When the todo "second" is requested with REST
+    Then the HTTP response status is 200
+    And the HTTP response JSON node "$.title" has the value "second"
+    And the HTTP response JSON node "$.url" is the url of the todo "second"
+
+  Scenario: If a todo is patched, only the sent fields are changed
+    Given the todos with titles "first" exist
+    When the todo "first" is patched with REST with body
+      """
+      {"completed": true}
+      """
+    Then the HTTP response status is 200
+    And the HTTP response JSON node "$.title" has the value "first"
+    And the HTTP response JSON node "$.completed" has the boolean value true
+    And 1 todos with completed true are in the database
+
+  Scenario: If a todo is patched with a too large order, it is not changed
+    Given the todos with titles "first" exist
+    When the todo "first" is patched with REST with body
+      """
+      {"completed": true, "order": 18446744073709551615}
+      """
+    Then the HTTP response status is 422
+    And the HTTP response JSON node "$.code" has the value "VALIDATION_FAILED"
+    And 0 todos with completed true are in the database
+
+  Scenario: If a todo is deleted, it is removed from the database
+    Given the todos with titles "first, second" exist
+    When the todo "first" is deleted with REST
+    Then the HTTP response status is 204
+    And the todos with titles "first" are not in the database
+    And the todo with title "second" is in the database
+
+  Scenario: If all todos are deleted, they are removed from the database
+    Given the todos with titles "first, second" exist
+    When all todos are deleted with REST
+    Then the HTTP response status is 204
+    And the todos with titles "first, second" are not in the database
+
+  Scenario: If a todo with an invalid id is requested, it is not found
+    When the todo with the id "not-an-id" is requested with REST
+    Then the HTTP response status is 404
+    And the HTTP response JSON node "$.code" has the value "NOT_FOUND"
+
+  Scenario: If the todos are requested through a proxy, their urls use the forwarded protocol and host
+    Given the todos with titles "first" exist
+    When the todos are requested with REST from the host "todos.example.com" with the protocol "https"
+    Then the HTTP response status is 200
+    And the HTTP response JSON node "$[0].url" starts with "https://todos.example.com/todos/"
@@ -0,0 +1,18 @@
+@session
+Feature: Logout
+  As a user of the secured endpoint
+  I want to log out, but not by following a link of another site
+
+  Scenario: If the logout is posted, the session and its cookie are removed
+    Given a session is created with the access token "access" expiring in 300 seconds
+    When the logout is posted with the session cookie
+    Then the HTTP response status is 303
+    And the HTTP response header "location" is "/"
+    And the HTTP response header "set-cookie" starts with "qgt_session=;"
+    And the session was removed
+
+  Scenario: If the logout is requested with GET, the session is kept
+    Given a session is created with the access token "access" expiring in 300 seconds
+    When the logout is requested with GET and the session cookie
+    Then the HTTP response status is 405
+    And the session still exists
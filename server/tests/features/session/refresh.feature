@@ -0,0 +1,27 @@
+@session
+Feature: Session refresh
+  As a user of the secured endpoint
+  I want my access token to be refreshed before it expires
+
+  Scenario: If the access token expires within the refresh margin, the session needs a refresh
+    Given a session is created with the access token "access" expiring in 20 seconds and the refresh token "refresh"
+    When the session is looked up
+    Then the session needs a refresh
+
+  Scenario: If the access token expires after the refresh margin, the session does not need a refresh
+    Given a session is created with the access token "access" expiring in 300 seconds and the refresh token "refresh"
+    When the session is looked up
+    Then the session does not need a refresh
+
+  Scenario: If a session without a refresh token expires soon, it does not need a refresh
+    Given a session is created with the access token "access" expiring in 20 seconds
+    When the session is looked up
+    Then the session does not need a refresh
+
+  Scenario: If a session is refreshed without a new refresh token, the refresh token is kept
+    Given a session is created with the access token "access" expiring in 20 seconds and the refresh token "refresh"
+    When the session is refreshed with the access token "refreshed"
+    And the session is looked up
+    Then the session is found with the access token "refreshed"
+    And the session is found with the refresh token "refresh"
+    And the session does not need a refresh
@@ -0,0 +1,19 @@
+@session
+Feature: Sessions
+  As a user of the secured endpoint
+  I want my tokens to be kept in a server-side session
+
+  Scenario: If a session is created, it is found by its id
+    Given a session is created with the access token "access" expiring in 300 seconds
+    When the session is looked up
+    Then the session is found with the access token "access"
+
+  Scenario: If a session with an unknown id is looked up, it is not found
+    Given a session is created with the access token "access" expiring in 300 seconds
+    When a session with an unknown id is looked up
+    Then the session is not found
+
+  Scenario: If a session without a refresh token expired with its access token, it is not found
+    Given a session is created with the access token "access" expiring in 0 seconds
+    When the session is looked up
+    Then the session is not found
@@ -0,0 +1,21 @@
+@shutdown
+Feature: Graceful shutdown
+  As an operator
+  I want the server to stop promptly on a shutdown signal
+
+  Scenario: If the server receives the shutdown signal, open WebSocket subscriptions are closed with a close frame
+    Given the server is running with a shutdown timeout of 30 seconds
+    And a WebSocket subscription is started
+    When the server receives the shutdown signal
+    Then the WebSocket receives a close frame with the code 1001 within 2 seconds
+    When the WebSocket client answers with a close frame
+    Then the WebSocket connection is closed within 2 seconds
+    And the server stops within 2 seconds
+
+  Scenario: If a WebSocket client does not answer the close frame, its connection is dropped at the deadline
+    Given the server is running with a shutdown timeout of 1 seconds
+    And a WebSocket subscription is started
+    When the server receives the shutdown signal
+    Then the WebSocket receives a close frame with the code 1001 within 2 seconds
+    And the WebSocket connection is closed within 3 seconds
+    And the server stops within 3 seconds
@@ -22,4 +22,5 @@ Feature: Create tag
           {"name": "test"}
         """
       Then the response should have errors
-      Then a response error with message containing "dup key: { name: \"test\" }" exists
+      Then a response error with code "DUPLICATE_NAME" exists
+      And a response error with message containing "{ name: \"test\" }" exists
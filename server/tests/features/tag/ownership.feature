@@ -0,0 +1,27 @@
+@tag
+Feature: Tag ownership
+  As a user
+  I want to have my own tags
+
+  Scenario: If a signed-in user creates a tag, it is owned by the user
+    Given a tag with name "work" exists
+    And the user "alice" is signed in
+    When createTag is sent with body
+      """
+      {"name": "work"}
+      """
+    Then the response has no errors
+    And the response data JSON node "$.owner" should have the value "alice"
+
+  Scenario: If a signed-in user creates a tag with an existing name of the user, it is rejected
+    Given the user "alice" is signed in
+    When createTag is sent with body
+      """
+      {"name": "work"}
+      """
+    And createTag is sent with body
+      """
+      {"name": "work"}
+      """
+    Then the response should have errors
+    And a response error with code "DUPLICATE_NAME" exists
@@ -4,7 +4,8 @@ Feature: Remove tags
   I want to be able to delete tags
 
   Scenario Template: If tags get deleted, they are removed from the database
-    Given a tag with name "first" exists
+    Given the public roles are "tag:admin"
+    And a tag with name "first" exists
     Given a tag with name "second" exists
     Given a tag with name "third" exists
     When removeTags is sent with ids for "<names>"
@@ -17,3 +18,27 @@ Feature: Remove tags
       | names         | result_value |
       | second        |            1 |
       | second, third |            2 |
+
+  Rule: Todos must not reference removed tags
+
+    Background:
+      Given the public roles are "tag:admin"
+      And a tag with name "first" exists
+      And a tag with name "second" exists
+      And the todos with titles "todo" exist
+      And the todo with title "todo" has the tag "first"
+      And the todo with title "todo" has the tag "second"
+
+    Scenario: If referenced tags get deleted with mode PULL, they are removed from the todos
+      When removeTags is sent with ids for "second" and mode PULL
+      Then the response has no errors
+      And the response data is integer value 1
+      And the tags with names "second" are not in the database
+      And the todo with title "todo" has 1 tags in the database
+
+    Scenario: If referenced tags get deleted with mode RESTRICT, it is rejected
+      When removeTags is sent with ids for "second" and mode RESTRICT
+      Then the response should have errors
+      And a response error with code "CONFLICT" exists
+      And the tag with name "second" is in the database
+      And the todo with title "todo" has 2 tags in the database
@@ -0,0 +1,23 @@
+@tag
+Feature: Tag subscriptions
+  As a user
+  I want to be notified about changed tags
+
+  Scenario: If a tag is created, it is sent to the tagChanged subscription
+    Given the tagChanged subscription is started
+    When createTag is sent with body
+      """
+      {"name": "test"}
+      """
+    Then the subscription sends a response
+    And the response has no errors
+    And the response data JSON node "$.name" should have the value "test"
+
+  Scenario: If tags are removed, their ids are sent to the tagRemoved subscription
+    Given the public roles are "tag:admin"
+    And a tag with name "test" exists
+    And the tagRemoved subscription is started
+    When removeTags is sent with ids for "test"
+    Then the subscription sends a response
+    And the response has no errors
+    And the response data JSON node "$" should have a value
@@ -23,4 +23,15 @@ Feature: Update tag
           {"name": "duplicate", "id": "replaced-by-step-function"}
         """
       Then the response should have errors
-      And a response error with message containing "dup key: { name: \"duplicate\" }" exists
+      And a response error with code "DUPLICATE_NAME" exists
+      And a response error with message containing "{ name: \"duplicate\" }" exists
+
+  Rule: The tag must exist
+
+    Scenario: If a non-existing tag is updated, it is rejected
+      When updateTag is sent with body for a non-existing tag
+        """
+          {"name": "test", "id": "replaced-by-step-function"}
+        """
+      Then the response should have errors
+      And a response error with code "NOT_FOUND" exists
@@ -0,0 +1,15 @@
+@telemetry
+Feature: Tracing of the requests
+  As an operator of the server
+  I want the requests and their MongoDB commands exported as traces
+  So that I can follow a request through the services
+
+  Scenario: If a request is traced, its MongoDB commands are exported as its child spans
+    Given the todos with titles "first" exist
+    When the todos are requested with REST while the spans are exported
+    Then the span "find" is exported within the span "GET /todos"
+
+  Scenario: If a request has a traceparent, its trace is continued
+    When the todos are requested with REST with the traceparent "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01" while the spans are exported
+    Then all exported spans belong to the trace "4bf92f3577b34da6a3ce929d0e0e4736"
+    And the span "GET /todos" has the parent span id "00f067aa0ba902b7"
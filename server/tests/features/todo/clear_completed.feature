@@ -0,0 +1,14 @@
+@todo
+Feature: Clear completed todos
+  As a user
+  I want to be able to delete all completed todos at once
+
+  Scenario: If completed todos are cleared, they are removed from the database
+    Given the todos with titles "first, second, third" exist
+    And the todo with title "first" is completed
+    And the todo with title "third" is completed
+    When clearCompletedTodos is sent
+    Then the response has no errors
+    And the response data is integer value 2
+    And the todos with titles "first, third" are not in the database
+    And the todo with title "second" is in the database
@@ -0,0 +1,66 @@
+@todo
+Feature: Paginate todos
+  As a user
+  I want to be able to retrieve todos page by page
+
+  Background:
+    Given the todos with titles "first, second, third" exist
+
+  Scenario: If the first page is requested, the first todos are returned
+    When todosConnection is sent with body
+      """
+      {"first": 2}
+      """
+    Then the response has no errors
+    And the response data JSON node "$.totalCount" should have the integer value 3
+    And the response data JSON node "$.pageInfo.hasNextPage" should have the boolean value true
+    And the response data JSON node "$.pageInfo.hasPreviousPage" should have the boolean value false
+    And the response data JSON node "$.edges[0].node.title" should have the value "first"
+    And the response data JSON node "$.edges[1].node.title" should have the value "second"
+
+  Scenario: If the next page is requested, the todos after the end cursor are returned
+    When todosConnection is sent with body
+      """
+      {"first": 2}
+      """
+    And the next page of todosConnection is requested with first 2
+    Then the response has no errors
+    And the response data JSON node "$.totalCount" should have the integer value 3
+    And the response data JSON node "$.pageInfo.hasNextPage" should have the boolean value false
+    And the response data JSON node "$.pageInfo.hasPreviousPage" should have the boolean value true
+    And the response data JSON node "$.edges[0].node.title" should have the value "third"
+
+  Scenario: If the last page is requested, the last todos are returned
+    When todosConnection is sent with body
+      """
+      {"last": 1}
+      """
+    Then the response has no errors
+    And the response data JSON node "$.pageInfo.hasNextPage" should have the boolean value false
+    And the response data JSON node "$.pageInfo.hasPreviousPage" should have the boolean value true
+    And the response data JSON node "$.edges[0].node.title" should have the value "third"
+
+  Rule: The cursor must be valid
+
+    Scenario: If an invalid cursor is sent, it is rejected
+      When todosConnection is sent with body
+        """
+        {"after": "invalid"}
+        """
+      Then the response should have errors
+
+    Scenario: If a cursor with a query operator is sent, it is rejected
+      When todosConnection is sent after a cursor with the values
+        """
+        [{"$exists": true}, {"$exists": true}]
+        """
+      Then the response should have errors
+      And a response error with code "VALIDATION_FAILED" exists
+
+    Scenario: If a cursor with values of the wrong type is sent, it is rejected
+      When todosConnection is sent after a cursor with the values
+        """
+        ["1", "first"]
+        """
+      Then the response should have errors
+      And a response error with code "VALIDATION_FAILED" exists
@@ -6,9 +6,31 @@ Feature: Create todo
   Scenario: If a todo is created, it is stored in the database
     When createTodo is sent with body
       """
-      {"completed": false, "order": 1, "title": "test"}
+      {"completed": false, "title": "test"}
       """
     Then the todo with title "test" is in the database
     And the response has no errors
     And the response data JSON node "$.title" should have the value "test"
     And the response data JSON node "$.created" should have a value
+
+  Rule: The order is assigned by the server
+
+    Scenario: If a todo is created, it is appended after the existing todos
+      Given the todos with titles "first, second" exist
+      When createTodo is sent with body
+        """
+        {"completed": false, "title": "test"}
+        """
+      Then the response has no errors
+      And the response data JSON node "$.order" should have the integer value 65538
+
+  Rule: The tags must exist
+
+    Scenario: If a todo is created with a non-existing tag, it is rejected
+      When createTodo is sent with body
+        """
+        {"completed": false, "tags": ["000000000000000000000000"], "title": "test"}
+        """
+      Then the response should have errors
+      And a response error with code "VALIDATION_FAILED" exists
+      And the todos with titles "test" are not in the database
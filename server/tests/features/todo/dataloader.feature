@@ -0,0 +1,18 @@
+@todo
+Feature: Batch database queries of todos
+  As a user
+  I want that listing todos with tags does not query the database for every todo
+
+  Scenario Template: The database query count does not depend on the number of todos
+    Given a tag with name "work" exists
+    And <count> todos with the tag "work" exist
+    When todos with tags are requested
+    Then the response has no errors
+    And the response data is a list with <count> entries
+    And the response data JSON node "$[0].tags[0].count" should have the integer value <count>
+    And the request sent 3 queries to the database
+
+    Examples:
+      | count |
+      |     1 |
+      |    20 |
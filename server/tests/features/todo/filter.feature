@@ -0,0 +1,36 @@
+@todo
+Feature: Filter and sort todos
+  As a user
+  I want to be able to filter and sort todos
+
+  Background:
+    Given the todos with titles "first, second, third" exist
+    And the todo with title "second" is completed
+
+  Scenario: If todos are filtered by completion, only completed todos are returned
+    When todos is sent with body
+      """
+      {"filter": {"completed": true}}
+      """
+    Then the response has no errors
+    And the response data is a list with 1 entries
+    And the response data JSON node "$[0].title" should have the value "second"
+
+  Scenario: If todos are filtered by title, only todos containing the value are returned
+    When todos is sent with body
+      """
+      {"filter": {"completed": false, "titleContains": "IR"}}
+      """
+    Then the response has no errors
+    And the response data is a list with 2 entries
+    And the response data JSON node "$[0].title" should have the value "first"
+    And the response data JSON node "$[1].title" should have the value "third"
+
+  Scenario: If todos are sorted descending by title, they are returned in reverse order
+    When todos is sent with body
+      """
+      {"sort": {"field": "TITLE", "direction": "DESC"}}
+      """
+    Then the response has no errors
+    And the response data JSON node "$[0].title" should have the value "third"
+    And the response data JSON node "$[2].title" should have the value "first"
@@ -0,0 +1,32 @@
+@todo
+Feature: Move todo
+  As a user
+  I want to be able to change the position of a todo
+
+  Background:
+    Given the todos with titles "first, second, third" exist
+
+  Scenario Template: If a todo is moved, it is sorted at the new position
+    When moveTodo is sent to move "<title>" <direction> "<anchor>"
+    Then the response has no errors
+    And the response data JSON node "$.title" should have the value "<title>"
+    When todos is sent with body
+      """
+      {"sort": {"field": "ORDER"}}
+      """
+    Then the response data JSON node "$[0].title" should have the value "<first>"
+    And the response data JSON node "$[1].title" should have the value "<second>"
+    And the response data JSON node "$[2].title" should have the value "<third>"
+
+    Examples:
+      | title  | direction | anchor | first  | second | third  |
+      | third  | before    | first  | third  | first  | second |
+      | first  | after     | third  | second | third  | first  |
+      | first  | after     | second | second | first  | third  |
+      | third  | before    | second | first  | third  | second |
+
+  Rule: The position must reference another existing todo
+
+    Scenario: If a todo is moved relative to itself, it is rejected
+      When moveTodo is sent to move "first" before "first"
+      Then the response should have errors
@@ -0,0 +1,57 @@
+@todo
+Feature: Todo ownership
+  As a user
+  I want to only see and change my own todos
+
+  Scenario: If a signed-in user creates a todo, it is owned by the user
+    Given the user "alice" is signed in
+    When createTodo is sent with body
+      """
+      {"completed": false, "title": "test"}
+      """
+    Then the response has no errors
+    And the response data JSON node "$.owner" should have the value "alice"
+
+  Scenario: If a signed-in user creates a todo, it is appended after the todos of the user
+    Given the todos with titles "first, second" exist
+    And the user "alice" is signed in
+    When createTodo is sent with body
+      """
+      {"completed": false, "title": "test"}
+      """
+    Then the response has no errors
+    And the response data JSON node "$.order" should have the integer value 65536
+
+  Scenario: If a signed-in user requests todos, only the todos of the user are returned
+    Given the todos with titles "mine" exist for the user "alice"
+    And the todos with titles "other" exist for the user "bob"
+    And the todos with titles "anonymous" exist
+    And the user "alice" is signed in
+    When todos is sent with body
+      """
+      {}
+      """
+    Then the response has no errors
+    And the response data is a list with 1 entries
+    And the response data JSON node "$[0].title" should have the value "mine"
+
+  Scenario: If todos are requested without signed-in user, only todos without owner are returned
+    Given the todos with titles "mine" exist for the user "alice"
+    And the todos with titles "anonymous" exist
+    When todos is sent with body
+      """
+      {}
+      """
+    Then the response has no errors
+    And the response data is a list with 1 entries
+    And the response data JSON node "$[0].title" should have the value "anonymous"
+
+  Scenario: If a signed-in user updates a todo of another owner, it is rejected
+    Given a todo with title "test" exists
+    And the user "bob" is signed in
+    When updateTodo is sent with body
+      """
+      {"completed": true, "id": "replaced-by-step-function"}
+      """
+    Then the response should have errors
+    And a response error with code "NOT_FOUND" exists
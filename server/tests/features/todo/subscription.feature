@@ -0,0 +1,80 @@
+@todo
+Feature: Todo subscriptions
+  As a user
+  I want to be notified about changed todos
+
+  Scenario: If a todo is created, it is sent to the todoChanged subscription
+    Given the todoChanged subscription is started
+    When createTodo is sent with body
+      """
+      {"completed": false, "title": "test"}
+      """
+    Then the subscription sends a response
+    And the response has no errors
+    And the response data JSON node "$.title" should have the value "test"
+
+  # With change streams, the change of the invalid todo is read from the stream before the creation
+  Scenario: If an invalid todo is changed in the database, the following changes are still sent
+    Given the todoChanged subscription is started
+    And a todo with an invalid title is inserted into the database
+    When createTodo is sent with body
+      """
+      {"completed": false, "title": "test"}
+      """
+    Then the subscription sends a response
+    And the response has no errors
+    And the response data JSON node "$.title" should have the value "test"
+
+  # Only the change stream sends changes which were not made through the server
+  @changestream
+  Scenario: If a todo is inserted into the database, the change stream sends it to the todoChanged subscription
+    Given the todoChanged subscription is started
+    And the todos with titles "inserted" exist
+    Then the subscription sends a response
+    And the response has no errors
+    And the response data JSON node "$.title" should have the value "inserted"
+
+  Scenario: If a todo is updated, it is sent to the todoChanged subscription
+    Given a todo with title "test" exists
+    And the todoChanged subscription is started
+    When updateTodo is sent with body
+      """
+      {"title": "updated-test", "id": "replaced-by-step-function"}
+      """
+    Then the subscription sends a response
+    And the response has no errors
+    And the response data JSON node "$.title" should have the value "updated-test"
+
+  Scenario: If todos are removed, their ids are sent to the todoRemoved subscription
+    Given a todo with title "test" exists
+    And the todoRemoved subscription is started
+    When removeTodos is sent with ids for "test"
+    Then the subscription sends a response
+    And the response has no errors
+    And the response data JSON node "$" should have a value
+
+  Rule: The todoChanged subscription can be filtered
+
+    Scenario: If a todo does not match the filter, it is not sent
+      Given the todoChanged subscription is started with filter
+        """
+        {"completed": true}
+        """
+      When createTodo is sent with body
+        """
+        {"completed": false, "title": "test"}
+        """
+      Then the subscription sends no response
+
+    Scenario: If a todo matches the filter, it is sent
+      Given a todo with title "test" exists
+      And the todoChanged subscription is started with filter
+        """
+        {"completed": true}
+        """
+      When updateTodo is sent with body
+        """
+        {"completed": true, "id": "replaced-by-step-function"}
+        """
+      Then the subscription sends a response
+      And the response data JSON node "$.completed" should have the boolean value true
@@ -0,0 +1,24 @@
+@todo
+Feature: Toggle all todos
+  As a user
+  I want to be able to mark all todos as complete or incomplete at once
+
+  Background:
+    Given the todos with titles "first, second, third" exist
+    And the todo with title "second" is completed
+
+  Scenario: If all todos are marked as complete, the changed todos are returned
+    When toggleAllTodos is sent with completed true
+    Then the response has no errors
+    And the response data is a list with 2 entries
+    And the response data JSON node "$[0].title" should have the value "first"
+    And the response data JSON node "$[1].title" should have the value "third"
+    And the response data JSON node "$[0].completed" should have the boolean value true
+    And 3 todos with completed true are in the database
+
+  Scenario: If all todos are marked as incomplete, the changed todos are returned
+    When toggleAllTodos is sent with completed false
+    Then the response has no errors
+    And the response data is a list with 1 entries
+    And the response data JSON node "$[0].title" should have the value "second"
+    And 3 todos with completed false are in the database
@@ -13,3 +13,25 @@ Feature: Update todo
     And the given todo has field completed with boolean value true
     And the response has no errors
     And the response data JSON node "$.modified" should have a value
+
+  Rule: The todo must exist
+
+    Scenario: If a non-existing todo is updated, it is rejected
+      When updateTodo is sent with body for a non-existing todo
+        """
+        {"completed": true, "id": "replaced-by-step-function"}
+        """
+      Then the response should have errors
+      And a response error with code "NOT_FOUND" exists
+
+  Rule: The tags must exist
+
+    Scenario: If a todo is updated with a non-existing tag, it is rejected
+      Given a todo with title "test" exists
+      When updateTodo is sent with body
+        """
+        {"tags": ["000000000000000000000000"], "id": "replaced-by-step-function"}
+        """
+      Then the response should have errors
+      And a response error with code "VALIDATION_FAILED" exists
+      And the todo with title "test" has 0 tags in the database
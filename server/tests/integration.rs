@@ -22,6 +22,13 @@ async fn main() {
     if let Err(e) = dotenv::dotenv() {
         tracing::info!("The '.env' file could not be loaded.\n{}", &e);
     }
+    // This validates tokens with the test signing key, so no authentication provider is required
+    if std::env::var("AUTH_JWKS_FILE").is_err() {
+        std::env::set_var(
+            "AUTH_JWKS_FILE",
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/jwks.json"),
+        );
+    }
 
     // Run all relevant tests
     AppWorld::cucumber()
@@ -36,7 +43,7 @@ async fn main() {
         )
         .before(move |_feature, _rule, _scenario, w| {
             if std::env::var("TEST_SKIP_CLEANUP_BEFORE").as_deref() == Ok("true") {
-                Box::pin(async move { () })
+                Box::pin(async move {})
             } else {
                 Box::pin(async move {
                     w.app
@@ -49,7 +56,7 @@ async fn main() {
         })
         .after(move |_feature, _rule, _scenario, _result, w| {
             if std::env::var("TEST_SKIP_CLEANUP_AFTER").as_deref() == Ok("true") {
-                Box::pin(async move { () })
+                Box::pin(async move {})
             } else {
                 Box::pin(async move {
                     w.expect("world should exist")
@@ -74,14 +81,13 @@ async fn main() {
                     &tags,
                     &env_tags
                         .split(",")
-                        .into_iter()
                         .map(|t| t.trim().to_string())
                         .collect::<Vec<String>>(),
                     true,
                 ),
                 Err(_) => has_tag_condition(
                     &tags,
-                    &vec![String::from("smoketest"), String::from("setup")],
+                    &[String::from("smoketest"), String::from("setup")],
                     false,
                 ),
             }
@@ -38,7 +38,7 @@ async fn main() {
         )
         .before(move |_feature, _rule, _scenario, w| {
             if std::env::var("TEST_SKIP_CLEANUP_BEFORE").as_deref() == Ok("true") {
-                Box::pin(async move { () })
+                Box::pin(async move {})
             } else {
                 Box::pin(async move {
                     w.app
@@ -51,7 +51,7 @@ async fn main() {
         })
         .after(move |_feature, _rule, _scenario, _result, w| {
             if std::env::var("TEST_SKIP_CLEANUP_AFTER").as_deref() == Ok("true") {
-                Box::pin(async move { () })
+                Box::pin(async move {})
             } else {
                 Box::pin(async move {
                     w.expect("world should exist")
@@ -71,7 +71,7 @@ async fn main() {
                 .chain(scenario.tags.iter())
                 .cloned()
                 .collect();
-            has_tag_condition(&tags, &vec![String::from("setup")], true)
+            has_tag_condition(&tags, &[String::from("setup")], true)
         })
         .await;
 }
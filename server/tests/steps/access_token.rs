@@ -0,0 +1,157 @@
+use crate::common::AppWorld;
+use cucumber::gherkin::Step;
+use cucumber::then;
+use cucumber::when;
+use std::str::FromStr;
+
+/// Creates a personal access token with given payload for the signed-in user.
+///
+/// Stores the created token with `personal-access-token` key and its id with
+/// `personal-access-token-id` key in the world state as string.
+#[when(expr = "createPersonalAccessToken is sent with body")]
+async fn create(w: &mut AppWorld, step: &Step) -> anyhow::Result<()> {
+    let docstring = step
+        .docstring()
+        .expect("a docstring payload should be provided");
+    let payload = serde_json::Value::from_str(docstring.trim())
+        .expect("docstring should be valid and parsable JSON");
+    let response = w
+        .private_graphql(
+            String::from("createPersonalAccessToken"),
+            include_str!("../graphql/access_token/create.graphql"),
+        )
+        .add_variable("input", payload)
+        .execute()
+        .await;
+
+    w.save_last_response(response);
+    let data = w.get_last_response_data();
+    if let Some(token) = data.get("token") {
+        w.state.insert("personal-access-token", token.clone());
+    }
+    if let Some(id) = data
+        .get("personalAccessToken")
+        .and_then(|personal_access_token| personal_access_token.get("id"))
+    {
+        w.state.insert("personal-access-token-id", id.clone());
+    }
+    Ok(())
+}
+
+/// Sends createPersonalAccessToken with given payload to the secured endpoint with the created
+/// personal access token as bearer token.
+#[when(expr = "createPersonalAccessToken is sent with the created token and body")]
+async fn create_with_token(w: &mut AppWorld, step: &Step) -> anyhow::Result<()> {
+    let docstring = step
+        .docstring()
+        .expect("a docstring payload should be provided");
+    let payload = serde_json::Value::from_str(docstring.trim())
+        .expect("docstring should be valid and parsable JSON");
+    let token = w
+        .state
+        .get("personal-access-token")
+        .and_then(|token| token.as_str())
+        .expect("a personal access token should be created")
+        .to_string();
+
+    w.http_graphql(
+        "/secure/api/graphql",
+        &token,
+        String::from("createPersonalAccessToken"),
+        include_str!("../graphql/access_token/create.graphql"),
+        serde_json::json!({ "input": payload }),
+    )
+    .await;
+    Ok(())
+}
+
+#[when(expr = "personalAccessTokens is sent")]
+async fn list(w: &mut AppWorld) -> anyhow::Result<()> {
+    let response = w
+        .private_graphql(
+            String::from("personalAccessTokens"),
+            include_str!("../graphql/access_token/list.graphql"),
+        )
+        .execute()
+        .await;
+
+    w.save_last_response(response);
+    Ok(())
+}
+
+#[when(expr = "revokePersonalAccessToken is sent for the created token")]
+async fn revoke(w: &mut AppWorld) -> anyhow::Result<()> {
+    let id = w
+        .state
+        .get("personal-access-token-id")
+        .expect("a personal access token should be created")
+        .clone();
+    let response = w
+        .private_graphql(
+            String::from("revokePersonalAccessToken"),
+            include_str!("../graphql/access_token/revoke.graphql"),
+        )
+        .add_variable("id", id)
+        .execute()
+        .await;
+
+    w.save_last_response(response);
+    Ok(())
+}
+
+#[then(expr = "the created personal access token starts with {string}")]
+async fn token_prefix(w: &mut AppWorld, prefix: String) -> anyhow::Result<()> {
+    let token = w
+        .state
+        .get("personal-access-token")
+        .and_then(|token| token.as_str())
+        .expect("a personal access token should be created");
+    assert!(
+        token.starts_with(&prefix),
+        "the token '{token}' should start with '{prefix}'"
+    );
+    Ok(())
+}
+
+#[then(
+    expr = "the created personal access token is accepted for the user {string} with the roles {string}"
+)]
+async fn token_accepted(w: &mut AppWorld, subject: String, roles: String) -> anyhow::Result<()> {
+    let token = w
+        .state
+        .get("personal-access-token")
+        .and_then(|token| token.as_str())
+        .expect("a personal access token should be created");
+    let personal_access_token = w
+        .app
+        .access_tokens()
+        .verify(token)
+        .await?
+        .expect("the personal access token should be accepted");
+
+    assert_eq!(personal_access_token.owner_subject(), subject);
+    let roles: Vec<&str> = roles.split(",").map(|role| role.trim()).collect();
+    assert_eq!(
+        personal_access_token
+            .permissions()
+            .iter()
+            .map(|permission| permission.role())
+            .collect::<Vec<&str>>(),
+        roles
+    );
+    Ok(())
+}
+
+#[then(expr = "the created personal access token is rejected")]
+async fn token_rejected(w: &mut AppWorld) -> anyhow::Result<()> {
+    let token = w
+        .state
+        .get("personal-access-token")
+        .and_then(|token| token.as_str())
+        .expect("a personal access token should be created");
+    assert!(
+        w.app.access_tokens().verify(token).await?.is_none(),
+        "the personal access token should be rejected"
+    );
+    Ok(())
+}
@@ -0,0 +1,184 @@
+use crate::common::AppWorld;
+use crate::utils::mint_token;
+use cucumber::gherkin::Step;
+use cucumber::given;
+use cucumber::then;
+use cucumber::when;
+use qgt_domain::permission::Permissions;
+use std::str::FromStr;
+
+/// The lifetime of the minted tokens in seconds.
+pub(crate) const TOKEN_LIFETIME: i64 = 300;
+
+/// Signs in with the `token`, like the secured endpoint does for the bearer token of a request.
+///
+/// If the token is valid, all following requests are sent with its subject as owner and the
+/// permissions of its roles.
+async fn sign_in(w: &mut AppWorld, token: &str) {
+    match w.app.auth_ctx().validate(token).await {
+        Ok(token) => {
+            w.permissions = Some(Permissions::from_roles(
+                token.roles.iter().map(String::as_str),
+            ));
+            w.owner = Some(qgt_domain::owner::Owner::new(token.subject));
+            w.token_error = None;
+        }
+        Err(e) => {
+            w.owner = None;
+            w.permissions = None;
+            w.token_error = Some(e);
+        }
+    }
+}
+
+/// Get the issuer and first audience accepted by the app.
+pub(crate) fn issuer_and_audience(w: &AppWorld) -> (String, String) {
+    let auth_ctx = w.app.auth_ctx();
+    let policy = auth_ctx.token_policy();
+    (
+        policy.issuer().to_string(),
+        policy
+            .audiences()
+            .first()
+            .expect("the app should accept an audience")
+            .clone(),
+    )
+}
+
+#[given(expr = "the user {string} is signed in with a valid token with the roles {string}")]
+async fn given_signed_in_with_token(
+    w: &mut AppWorld,
+    subject: String,
+    roles: String,
+) -> anyhow::Result<()> {
+    let (issuer, audience) = issuer_and_audience(w);
+    let roles: Vec<&str> = roles.split(",").map(|role| role.trim()).collect();
+    sign_in(
+        w,
+        &mint_token(&issuer, &audience, &subject, &roles, TOKEN_LIFETIME),
+    )
+    .await;
+    assert!(
+        w.token_error.is_none(),
+        "the token should be valid: {:?}",
+        w.token_error
+    );
+    Ok(())
+}
+
+#[when(expr = "the user {string} signs in with an expired token")]
+async fn sign_in_with_expired_token(w: &mut AppWorld, subject: String) -> anyhow::Result<()> {
+    let (issuer, audience) = issuer_and_audience(w);
+    // Expired for longer than the default leeway of the validation
+    sign_in(w, &mint_token(&issuer, &audience, &subject, &[], -3600)).await;
+    Ok(())
+}
+
+#[when(expr = "the user {string} signs in with a token expired {int} seconds ago")]
+async fn sign_in_with_token_expired_ago(
+    w: &mut AppWorld,
+    subject: String,
+    seconds: i64,
+) -> anyhow::Result<()> {
+    let (issuer, audience) = issuer_and_audience(w);
+    sign_in(w, &mint_token(&issuer, &audience, &subject, &[], -seconds)).await;
+    Ok(())
+}
+
+#[when(expr = "the user {string} signs in with a token of the issuer {string}")]
+async fn sign_in_with_token_of_issuer(
+    w: &mut AppWorld,
+    subject: String,
+    issuer: String,
+) -> anyhow::Result<()> {
+    let (_, audience) = issuer_and_audience(w);
+    sign_in(
+        w,
+        &mint_token(&issuer, &audience, &subject, &[], TOKEN_LIFETIME),
+    )
+    .await;
+    Ok(())
+}
+
+#[when(expr = "the user {string} signs in with a token for the audience {string}")]
+async fn sign_in_with_token_for_audience(
+    w: &mut AppWorld,
+    subject: String,
+    audience: String,
+) -> anyhow::Result<()> {
+    let (issuer, _) = issuer_and_audience(w);
+    sign_in(
+        w,
+        &mint_token(&issuer, &audience, &subject, &[], TOKEN_LIFETIME),
+    )
+    .await;
+    Ok(())
+}
+
+#[when("a user signs in with a malformed token")]
+async fn sign_in_with_malformed_token(w: &mut AppWorld) -> anyhow::Result<()> {
+    sign_in(w, "not-a-token").await;
+    Ok(())
+}
+
+#[then("the token is rejected")]
+async fn token_rejected(w: &mut AppWorld) -> anyhow::Result<()> {
+    assert!(
+        w.token_error.is_some(),
+        "the token should be rejected, but the user {:?} is signed in",
+        w.owner
+    );
+    Ok(())
+}
+
+#[then("the token is accepted")]
+async fn token_accepted(w: &mut AppWorld) -> anyhow::Result<()> {
+    assert!(
+        w.token_error.is_none(),
+        "the token should be accepted: {:?}",
+        w.token_error
+    );
+    Ok(())
+}
+
+/// Sends all following requests anonymously with the permissions of the `roles`, like the public
+/// endpoint does if they are its public roles.
+#[given(expr = "the public roles are {string}")]
+async fn given_public_roles(w: &mut AppWorld, roles: String) -> anyhow::Result<()> {
+    w.owner = None;
+    w.permissions = Some(Permissions::from_roles(
+        roles.split(",").map(|role| role.trim()),
+    ));
+    Ok(())
+}
+
+/// Sends createTodo with given payload to the public endpoint over HTTP with a token of the user
+/// with the roles.
+#[when(
+    expr = "createTodo is sent to the public endpoint with a token of the user {string} with the roles {string} and body"
+)]
+async fn create_todo_with_token(
+    w: &mut AppWorld,
+    subject: String,
+    roles: String,
+    step: &Step,
+) -> anyhow::Result<()> {
+    let docstring = step
+        .docstring()
+        .expect("a docstring payload should be provided");
+    let payload = serde_json::Value::from_str(docstring.trim())
+        .expect("docstring should be valid and parsable JSON");
+    let (issuer, audience) = issuer_and_audience(w);
+    let roles: Vec<&str> = roles.split(",").map(|role| role.trim()).collect();
+    let token = mint_token(&issuer, &audience, &subject, &roles, TOKEN_LIFETIME);
+
+    w.http_graphql(
+        "/api/graphql",
+        &token,
+        String::from("createTodo"),
+        include_str!("../graphql/todo/create.graphql"),
+        serde_json::json!({ "input": payload }),
+    )
+    .await;
+    Ok(())
+}
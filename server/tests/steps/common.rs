@@ -1,7 +1,45 @@
 use crate::common::AppWorld;
+use crate::common::CustomBool;
+use cucumber::given;
 use cucumber::then;
 use jsonpath_rust::JsonPath;
 use std::str::FromStr;
+use std::time::Duration;
+
+/// Signs in the user with the requested subject.
+///
+/// All following requests are sent with the user as owner.
+#[given(expr = "the user {string} is signed in")]
+async fn given_signed_in(w: &mut AppWorld, subject: String) -> anyhow::Result<()> {
+    w.owner = Some(qgt_domain::owner::Owner::new(subject));
+    Ok(())
+}
+
+/// Signs in the user with the requested subject and the permissions of the requested roles.
+///
+/// All following requests are sent with the user as owner and only the permissions of the roles.
+#[given(expr = "the user {string} is signed in with the roles {string}")]
+async fn given_signed_in_with_roles(
+    w: &mut AppWorld,
+    subject: String,
+    roles: String,
+) -> anyhow::Result<()> {
+    w.owner = Some(qgt_domain::owner::Owner::new(subject));
+    w.permissions = Some(qgt_domain::permission::Permissions::from_roles(
+        roles.split(",").map(|role| role.trim()),
+    ));
+    Ok(())
+}
+
+/// Sends all following requests with the request id.
+#[given(expr = "the request id {string}")]
+async fn given_request_id(w: &mut AppWorld, request_id: String) -> anyhow::Result<()> {
+    w.request_id = Some(
+        qgt_domain::request_id::RequestId::parse(&request_id)
+            .expect("the request id should be valid"),
+    );
+    Ok(())
+}
 
 #[then(expr = "the response data JSON node {string} should have the value {string}")]
 async fn json_node_value_eq(
@@ -30,6 +68,50 @@ async fn json_node_value_eq(
     Ok(())
 }
 
+#[then(expr = "the response data JSON node {string} should have the integer value {int}")]
+async fn json_node_integer_value_eq(
+    w: &mut AppWorld,
+    json_path: String,
+    value: i64,
+) -> anyhow::Result<()> {
+    let path = JsonPath::from_str(&json_path)?;
+    let response_data = w.get_last_response_data();
+    let result = path.find(&response_data);
+
+    let arr = result.as_array().expect("the value should be an array");
+    assert_eq!(
+        arr.len(),
+        1,
+        "unexpected length '{}' of found values for path '{json_path}'",
+        arr.len()
+    );
+
+    assert_eq!(arr.first().unwrap().as_i64(), Some(value));
+    Ok(())
+}
+
+#[then(expr = "the response data JSON node {string} should have the boolean value {bool}")]
+async fn json_node_boolean_value_eq(
+    w: &mut AppWorld,
+    json_path: String,
+    value: CustomBool,
+) -> anyhow::Result<()> {
+    let path = JsonPath::from_str(&json_path)?;
+    let response_data = w.get_last_response_data();
+    let result = path.find(&response_data);
+
+    let arr = result.as_array().expect("the value should be an array");
+    assert_eq!(
+        arr.len(),
+        1,
+        "unexpected length '{}' of found values for path '{json_path}'",
+        arr.len()
+    );
+
+    assert_eq!(arr.first().unwrap().as_bool(), Some(*value));
+    Ok(())
+}
+
 #[then(expr = "the response data JSON node {string} should have a value")]
 async fn json_node_value(w: &mut AppWorld, json_path: String) -> anyhow::Result<()> {
     let path = JsonPath::from_str(&json_path)?;
@@ -78,6 +160,40 @@ async fn error_containing(w: &mut AppWorld, value: String) -> anyhow::Result<()>
     Ok(())
 }
 
+#[then(expr = "a response error with code {string} exists")]
+async fn error_with_code(w: &mut AppWorld, code: String) -> anyhow::Result<()> {
+    let errors = w.get_last_response_errors();
+    assert!(
+        errors.iter().any(|e| {
+            e.extensions
+                .as_ref()
+                .and_then(|extensions| extensions.get("code"))
+                == Some(&async_graphql::Value::from(code.as_str()))
+        }),
+        "no error with code '{code}' found in:\n{errors:?}"
+    );
+
+    Ok(())
+}
+
+#[then(expr = "every response error has the request id {string}")]
+async fn errors_with_request_id(w: &mut AppWorld, request_id: String) -> anyhow::Result<()> {
+    let errors = w.get_last_response_errors();
+    assert!(!errors.is_empty());
+    for error in errors {
+        assert_eq!(
+            error
+                .extensions
+                .as_ref()
+                .and_then(|extensions| extensions.get("requestId")),
+            Some(&async_graphql::Value::from(request_id.as_str())),
+            "the error should have the request id: {error:?}"
+        );
+    }
+
+    Ok(())
+}
+
 #[then(expr = "the response data is integer value {int}")]
 async fn response_data_value(w: &mut AppWorld, value: i64) -> anyhow::Result<()> {
     assert_eq!(
@@ -88,3 +204,35 @@ async fn response_data_value(w: &mut AppWorld, value: i64) -> anyhow::Result<()>
     );
     Ok(())
 }
+
+#[then(expr = "the response data is a list with {int} entries")]
+async fn response_data_length(w: &mut AppWorld, length: usize) -> anyhow::Result<()> {
+    assert_eq!(
+        w.get_last_response_data()
+            .as_array()
+            .expect("the response data value should be a list")
+            .len(),
+        length
+    );
+    Ok(())
+}
+
+#[then(expr = "the subscription sends a response")]
+async fn subscription_response(w: &mut AppWorld) -> anyhow::Result<()> {
+    assert!(
+        w.receive_subscription_response(Duration::from_secs(5))
+            .await,
+        "the subscription should send a response"
+    );
+    Ok(())
+}
+
+#[then(expr = "the subscription sends no response")]
+async fn no_subscription_response(w: &mut AppWorld) -> anyhow::Result<()> {
+    assert!(
+        !w.receive_subscription_response(Duration::from_millis(500))
+            .await,
+        "the subscription should not send a response"
+    );
+    Ok(())
+}
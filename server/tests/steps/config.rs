@@ -0,0 +1,206 @@
+use crate::common::AppWorld;
+use cucumber::then;
+use cucumber::when;
+use qgt_auth::config::AuthConfig;
+use qgt_domain::config::Config;
+use qgt_domain::config::DbConfig;
+use qgt_domain::config::FeatureConfig;
+use qgt_domain::config::ServerConfig;
+use qgt_domain::config::ShutdownConfig;
+use qgt_telemetry::config::TelemetryConfig;
+use serde::de::DeserializeOwned;
+use serde::de::Error;
+use serde::de::Visitor;
+use serde::forward_to_deserialize_any;
+use serde::Deserializer;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// A deserializer, which only captures the names of the fields of a struct.
+#[derive(Default)]
+struct FieldNames(Vec<&'static str>);
+
+impl<'de> Deserializer<'de> for &mut FieldNames {
+    type Error = serde::de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(Self::Error::custom("only structs have field names"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.0.extend(fields);
+        Err(Self::Error::custom("the field names are captured"))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+        unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier ignored_any
+    }
+}
+
+/// Get the names of the deserialized fields of the configuration struct `T`.
+fn field_names<T: DeserializeOwned>() -> Vec<&'static str> {
+    let mut names = FieldNames::default();
+    let _ = T::deserialize(&mut names);
+    names.0
+}
+
+/// Loads the configuration with the file of the config fixtures.
+///
+/// Stores the errors with `config-errors` key, the printed configuration with `printed-config` key
+/// and the names of the environment variables changed by loading with `changed-env` key in the
+/// world state.
+#[when(expr = "the configuration is loaded with the file {string}")]
+async fn load(w: &mut AppWorld, file: String) -> anyhow::Result<()> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/config")
+        .join(file);
+    load_path(w, &path)
+}
+
+/// Loads the configuration with a file, which sets every field of the configuration structs.
+///
+/// The keys of the `[auth]` table are the fields without their `auth_` prefix.
+#[when("the configuration is loaded with a file setting every field of the configuration structs")]
+async fn load_all_fields(w: &mut AppWorld) -> anyhow::Result<()> {
+    let tables = [
+        (
+            "server",
+            [
+                field_names::<ServerConfig>(),
+                field_names::<ShutdownConfig>(),
+            ]
+            .concat(),
+        ),
+        ("mongodb", field_names::<DbConfig>()),
+        ("auth", field_names::<AuthConfig>()),
+        ("features", field_names::<FeatureConfig>()),
+        ("telemetry", field_names::<TelemetryConfig>()),
+    ];
+    let mut content = String::new();
+    for (table, fields) in tables {
+        assert!(!fields.is_empty(), "the [{table}] table should have fields");
+        content.push_str(&format!("[{table}]\n"));
+        for field in fields {
+            let key = field.strip_prefix("auth_").unwrap_or(field);
+            content.push_str(&format!("{key} = \"1\"\n"));
+        }
+    }
+    let path = std::env::temp_dir().join(format!("qgt-all-fields-{}.toml", std::process::id()));
+    std::fs::write(&path, content)?;
+    let result = load_path(w, &path);
+    std::fs::remove_file(&path)?;
+    result
+}
+
+/// Loads the configuration with the file at `path`, see [load].
+fn load_path(w: &mut AppWorld, path: &Path) -> anyhow::Result<()> {
+    let env_before: BTreeMap<String, String> = std::env::vars().collect();
+    let result = Config::load(Some(path));
+    let env_after: BTreeMap<String, String> = std::env::vars().collect();
+    let changed_env: Vec<&String> = env_before
+        .keys()
+        .chain(env_after.keys())
+        .filter(|key| env_before.get(*key) != env_after.get(*key))
+        .collect();
+    w.state
+        .insert("changed-env", serde_json::json!(changed_env));
+
+    match result {
+        Ok(config) => {
+            w.state.insert("config-errors", serde_json::json!([]));
+            w.state
+                .insert("printed-config", serde_json::json!(config.redacted()));
+        }
+        Err(e) => {
+            w.state
+                .insert("config-errors", serde_json::json!(e.errors()));
+            w.state.remove("printed-config");
+        }
+    }
+    Ok(())
+}
+
+#[then(expr = "the configuration is rejected with errors for {string}")]
+async fn rejected(w: &mut AppWorld, keys: String) -> anyhow::Result<()> {
+    let errors = w
+        .state
+        .get("config-errors")
+        .and_then(|errors| errors.as_array())
+        .expect("the configuration should be loaded");
+    let keys: Vec<&str> = keys.split(",").map(|key| key.trim()).collect();
+    assert_eq!(errors.len(), keys.len(), "errors: {errors:?}");
+    for key in keys {
+        assert!(
+            errors
+                .iter()
+                .filter_map(|error| error.as_str())
+                .any(|error| error.starts_with(key)),
+            "an error for '{key}' should exist: {errors:?}"
+        );
+    }
+    Ok(())
+}
+
+#[then("the configuration has no unknown keys")]
+async fn no_unknown_keys(w: &mut AppWorld) -> anyhow::Result<()> {
+    let errors = w
+        .state
+        .get("config-errors")
+        .and_then(|errors| errors.as_array())
+        .expect("the configuration should be loaded");
+    let unknown: Vec<&str> = errors
+        .iter()
+        .filter_map(|error| error.as_str())
+        .filter(|error| error.ends_with("unknown key") || error.ends_with("unknown table"))
+        .collect();
+    assert!(unknown.is_empty(), "the keys should be known: {unknown:?}");
+    Ok(())
+}
+
+#[then(expr = "the printed configuration contains {string}")]
+async fn printed_contains(w: &mut AppWorld, text: String) -> anyhow::Result<()> {
+    let printed = w
+        .state
+        .get("printed-config")
+        .and_then(|printed| printed.as_str())
+        .expect("the configuration should be valid");
+    assert!(
+        printed.contains(&text),
+        "'{text}' should be printed:\n{printed}"
+    );
+    Ok(())
+}
+
+#[then(expr = "the printed configuration does not contain {string}")]
+async fn printed_not_contains(w: &mut AppWorld, text: String) -> anyhow::Result<()> {
+    let printed = w
+        .state
+        .get("printed-config")
+        .and_then(|printed| printed.as_str())
+        .expect("the configuration should be valid");
+    assert!(
+        !printed.contains(&text),
+        "'{text}' should not be printed:\n{printed}"
+    );
+    Ok(())
+}
+
+#[then(expr = "the environment is not changed")]
+async fn env_not_changed(w: &mut AppWorld) -> anyhow::Result<()> {
+    let changed_env = w
+        .state
+        .get("changed-env")
+        .and_then(|changed_env| changed_env.as_array())
+        .expect("the configuration should be loaded");
+    assert!(
+        changed_env.is_empty(),
+        "the environment variables {changed_env:?} should not be changed"
+    );
+    Ok(())
+}
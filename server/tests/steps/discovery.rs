@@ -0,0 +1,326 @@
+use crate::common::AppWorld;
+use cucumber::given;
+use cucumber::then;
+use cucumber::when;
+use qgt_auth::discovery::Discovery;
+use qgt_auth::discovery::ProviderMetadata;
+use qgt_auth::login::Pkce;
+use qgt_auth::provider::Provider;
+use reqwest::Url;
+use serde_json::Map;
+use serde_json::Value;
+use std::path::Path;
+use std::sync::Arc;
+
+/// The client id of the authorization and end-session URLs.
+const CLIENT_ID: &str = "qgt";
+
+/// The URI the provider redirects to after the logout.
+const POST_LOGOUT_REDIRECT_URI: &str = "http://localhost:3000/";
+
+/// A [Provider], which is only known by its issuer URL.
+struct TestProvider {
+    issuer: String,
+}
+
+impl Provider for TestProvider {
+    fn name(&self) -> &'static str {
+        "test"
+    }
+
+    fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
+    fn audiences(&self) -> &[String] {
+        &[]
+    }
+
+    fn roles(&self, _claims: &Map<String, Value>) -> Vec<String> {
+        vec![]
+    }
+}
+
+/// Read the `file` of the fixtures.
+fn read_fixture(file: &str) -> anyhow::Result<String> {
+    Ok(std::fs::read_to_string(
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(file),
+    )?)
+}
+
+/// Serve the discovery `document` and the signing keys of the test fixtures on a local port.
+///
+/// If `local_issuer` is set, the issuer and JWKS URL of the document are changed to the local
+/// server. Returns the URL of the local server.
+async fn serve_provider(mut document: Value, local_issuer: bool) -> anyhow::Result<String> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("http://{}", listener.local_addr()?);
+    if local_issuer {
+        document["issuer"] = Value::from(url.as_str());
+        document["jwks_uri"] = Value::from(format!("{url}/keys"));
+    }
+    let keys: Value = serde_json::from_str(&read_fixture("jwks.json")?)?;
+    let router = axum::Router::new()
+        .route(
+            "/.well-known/openid-configuration",
+            axum::routing::get(move || async move { axum::Json(document) }),
+        )
+        .route(
+            "/keys",
+            axum::routing::get(move || async move { axum::Json(keys) }),
+        );
+    tokio::spawn(async move { axum::serve(listener, router).await });
+
+    Ok(url)
+}
+
+/// Get the [ProviderMetadata] of the discovery document in the world state.
+fn metadata(w: &AppWorld) -> anyhow::Result<ProviderMetadata> {
+    let document = w
+        .state
+        .get("discovery-document")
+        .expect("a discovery document should be given");
+    Ok(serde_json::from_value(document.clone())?)
+}
+
+/// Reads the discovery document of the discovery fixtures.
+///
+/// Stores the document with `discovery-document` key in the world state.
+#[given(expr = "the discovery document {string}")]
+async fn given_document(w: &mut AppWorld, file: String) -> anyhow::Result<()> {
+    let document: Value = serde_json::from_str(&read_fixture(&format!("discovery/{file}"))?)?;
+
+    w.state.insert("discovery-document", document);
+    Ok(())
+}
+
+/// Serves the discovery document in the world state at the issuer URL of a local provider.
+///
+/// Stores the URL of the provider with `issuer` key in the world state.
+#[given(expr = "a provider serves the discovery document")]
+async fn given_provider(w: &mut AppWorld) -> anyhow::Result<()> {
+    let document = w
+        .state
+        .get("discovery-document")
+        .expect("a discovery document should be given")
+        .clone();
+    let url = serve_provider(document, true).await?;
+
+    w.state.insert("issuer", Value::from(url));
+    Ok(())
+}
+
+/// Serves the discovery document in the world state unchanged, so its issuer is not the URL of
+/// the local provider.
+///
+/// Stores the URL of the provider with `issuer` key in the world state.
+#[given(expr = "a provider serves the discovery document of another issuer")]
+async fn given_provider_of_other_issuer(w: &mut AppWorld) -> anyhow::Result<()> {
+    let document = w
+        .state
+        .get("discovery-document")
+        .expect("a discovery document should be given")
+        .clone();
+    let url = serve_provider(document, false).await?;
+
+    w.state.insert("issuer", Value::from(url));
+    Ok(())
+}
+
+/// Discovers the local provider and checks its signing keys.
+///
+/// Stores the discovered endpoints and the count of signing keys with `discovered` key in the
+/// world state, or the error with `discovery-error` key.
+#[when(expr = "the provider is discovered")]
+async fn discovered(w: &mut AppWorld) -> anyhow::Result<()> {
+    let issuer = w
+        .state
+        .get("issuer")
+        .and_then(|issuer| issuer.as_str())
+        .expect("a provider should serve the discovery document")
+        .to_string();
+    let discovery = Discovery::new(Arc::new(TestProvider { issuer }));
+    let result = match discovery.metadata().await {
+        Ok(metadata) => discovery.check_keys().await.map(|keys| {
+            serde_json::json!({
+                "authorization_endpoint": metadata.authorization_endpoint(),
+                "token_endpoint": metadata.token_endpoint(),
+                "end_session_endpoint": metadata.end_session_endpoint(),
+                "revocation_endpoint": metadata.revocation_endpoint(),
+                "keys": keys,
+            })
+        }),
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(discovered) => {
+            w.state.insert("discovered", discovered);
+            w.state.remove("discovery-error");
+        }
+        Err(e) => {
+            w.state.remove("discovered");
+            w.state
+                .insert("discovery-error", Value::from(e.to_string()));
+        }
+    }
+    Ok(())
+}
+
+/// Get the discovered value of the `field`.
+fn discovered_value<'a>(w: &'a AppWorld, field: &str) -> &'a Value {
+    let discovered = w.state.get("discovered").unwrap_or_else(|| {
+        panic!(
+            "the provider should be discovered: {:?}",
+            w.state.get("discovery-error")
+        )
+    });
+    &discovered[field]
+}
+
+#[then(expr = "the discovered {word} is {string}")]
+async fn discovered_endpoint(w: &mut AppWorld, field: String, value: String) -> anyhow::Result<()> {
+    assert_eq!(discovered_value(w, &field).as_str(), Some(value.as_str()));
+    Ok(())
+}
+
+#[then(expr = "the discovered {word} is missing")]
+async fn discovered_endpoint_missing(w: &mut AppWorld, field: String) -> anyhow::Result<()> {
+    let value = discovered_value(w, &field);
+    assert!(value.is_null(), "the {field} should be missing: {value}");
+    Ok(())
+}
+
+#[then(expr = "{int} signing key(s) of the provider is/are discovered")]
+async fn discovered_keys(w: &mut AppWorld, count: u64) -> anyhow::Result<()> {
+    assert_eq!(discovered_value(w, "keys").as_u64(), Some(count));
+    Ok(())
+}
+
+#[then(expr = "the discovery is rejected with an error containing {string}")]
+async fn discovery_rejected(w: &mut AppWorld, text: String) -> anyhow::Result<()> {
+    let error = w
+        .state
+        .get("discovery-error")
+        .and_then(|error| error.as_str())
+        .expect("the discovery should be rejected");
+    assert!(
+        error.contains(&text),
+        "the error should contain '{text}': {error}"
+    );
+    Ok(())
+}
+
+/// Builds the end-session URL of the discovery document for the ID token.
+///
+/// Stores the URL with `end-session-url` key in the world state, which is null if there is none.
+#[when(expr = "the end-session URL is built for the ID token {string}")]
+async fn end_session_url(w: &mut AppWorld, id_token: String) -> anyhow::Result<()> {
+    let url = metadata(w)?.end_session_url(&id_token, CLIENT_ID, POST_LOGOUT_REDIRECT_URI)?;
+
+    w.state
+        .insert("end-session-url", serde_json::json!(url.map(String::from)));
+    Ok(())
+}
+
+/// Builds the authorization URL of the discovery document for the redirect URI and state.
+///
+/// Stores the URL with `authorization-url` key in the world state.
+#[when(
+    expr = "the authorization URL is built for the redirect URI {string} and the state {string}"
+)]
+async fn authorization_url(
+    w: &mut AppWorld,
+    redirect_uri: String,
+    state: String,
+) -> anyhow::Result<()> {
+    let url = metadata(w)?.authorization_url(CLIENT_ID, &redirect_uri, &state, &Pkce::new())?;
+
+    w.state
+        .insert("authorization-url", Value::from(String::from(url)));
+    Ok(())
+}
+
+#[then(expr = "the authorization URL starts with {string}")]
+async fn authorization_url_prefix(w: &mut AppWorld, prefix: String) -> anyhow::Result<()> {
+    let url = built_url(w, "authorization-url").expect("an authorization URL should be built");
+    assert!(
+        url.as_str().starts_with(&prefix),
+        "'{url}' should start with '{prefix}'"
+    );
+    Ok(())
+}
+
+#[then(expr = "the authorization URL has the parameter {string} with the value {string}")]
+async fn authorization_url_parameter(
+    w: &mut AppWorld,
+    name: String,
+    value: String,
+) -> anyhow::Result<()> {
+    let url = built_url(w, "authorization-url").expect("an authorization URL should be built");
+    assert_parameter(&url, &name, &value);
+    Ok(())
+}
+
+/// Get the URL with the `key` in the world state.
+fn built_url(w: &AppWorld, key: &str) -> Option<Url> {
+    w.state
+        .get(key)
+        .unwrap_or_else(|| panic!("the URL '{key}' should be built"))
+        .as_str()
+        .map(|url| Url::parse(url).expect("the built URL should be valid"))
+}
+
+/// Assert that the `url` has the query parameter `name` with the decoded `value`.
+fn assert_parameter(url: &Url, name: &str, value: &str) {
+    assert!(
+        url.query_pairs()
+            .any(|(param, param_value)| param == name && param_value == value),
+        "'{url}' should have the parameter '{name}' with the value '{value}'"
+    );
+}
+
+/// Get the end-session URL in the world state.
+fn built_end_session_url(w: &AppWorld) -> Option<Url> {
+    built_url(w, "end-session-url")
+}
+
+#[then(expr = "the end-session URL starts with {string}")]
+async fn end_session_url_prefix(w: &mut AppWorld, prefix: String) -> anyhow::Result<()> {
+    let url = built_end_session_url(w).expect("an end-session URL should be built");
+    assert!(
+        url.as_str().starts_with(&prefix),
+        "'{url}' should start with '{prefix}'"
+    );
+    Ok(())
+}
+
+#[then(expr = "the end-session URL has the parameter {string} with the value {string}")]
+async fn end_session_url_parameter(
+    w: &mut AppWorld,
+    name: String,
+    value: String,
+) -> anyhow::Result<()> {
+    let url = built_end_session_url(w).expect("an end-session URL should be built");
+    assert_parameter(&url, &name, &value);
+    Ok(())
+}
+
+#[then(expr = "the end-session URL has the post logout redirect URI")]
+async fn end_session_url_redirect(w: &mut AppWorld) -> anyhow::Result<()> {
+    end_session_url_parameter(
+        w,
+        String::from("post_logout_redirect_uri"),
+        String::from(POST_LOGOUT_REDIRECT_URI),
+    )
+    .await
+}
+
+#[then(expr = "there is no end-session URL")]
+async fn no_end_session_url(w: &mut AppWorld) -> anyhow::Result<()> {
+    let url = built_end_session_url(w);
+    assert!(url.is_none(), "there should be no end-session URL: {url:?}");
+    Ok(())
+}
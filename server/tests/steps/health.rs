@@ -0,0 +1,13 @@
+use crate::common::AppWorld;
+use cucumber::then;
+
+#[then("the database is reachable")]
+async fn database_reachable(w: &mut AppWorld) -> anyhow::Result<()> {
+    qgt_domain::db::ping(w.app.db()).await
+}
+
+#[then(expr = "{int} signing key(s) is/are available")]
+async fn signing_keys_available(w: &mut AppWorld, count: usize) -> anyhow::Result<()> {
+    assert_eq!(w.app.auth_ctx().check_keys().await?, count);
+    Ok(())
+}
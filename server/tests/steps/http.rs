@@ -0,0 +1,38 @@
+use crate::common::AppWorld;
+use cucumber::then;
+
+#[then(expr = "the HTTP response status is {int}")]
+async fn response_status(w: &mut AppWorld, status: u16) -> anyhow::Result<()> {
+    let response = w.last_http_response();
+    assert_eq!(response.status.as_u16(), status, "response: {response:?}");
+    Ok(())
+}
+
+#[then(expr = "the HTTP response header {string} is {string}")]
+async fn header(w: &mut AppWorld, name: String, value: String) -> anyhow::Result<()> {
+    let response = w.last_http_response();
+    assert!(
+        response
+            .headers
+            .get_all(name.as_str())
+            .iter()
+            .any(|header| *header == value.as_str()),
+        "the header '{name}' should be '{value}': {response:?}"
+    );
+    Ok(())
+}
+
+#[then(expr = "the HTTP response header {string} starts with {string}")]
+async fn header_prefix(w: &mut AppWorld, name: String, prefix: String) -> anyhow::Result<()> {
+    let response = w.last_http_response();
+    assert!(
+        response
+            .headers
+            .get_all(name.as_str())
+            .iter()
+            .filter_map(|header| header.to_str().ok())
+            .any(|header| header.starts_with(&prefix)),
+        "the header '{name}' should start with '{prefix}': {response:?}"
+    );
+    Ok(())
+}
@@ -0,0 +1,158 @@
+use crate::common::AppWorld;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use cucumber::given;
+use cucumber::then;
+use cucumber::when;
+use qgt_auth::login::Pkce;
+use qgt_auth::login::StateSigner;
+use sha2::Digest;
+use sha2::Sha256;
+
+/// Get the value of the `key` of the started log-in in the world state.
+fn login_value(w: &AppWorld, key: &str) -> String {
+    w.state
+        .get(key)
+        .and_then(|value| value.as_str())
+        .expect("a log-in should be started")
+        .to_string()
+}
+
+/// Verifies the `state` with the code `verifier` by the [StateSigner].
+///
+/// Stores the return path of the accepted state with `return-path` key in the world state, which is
+/// null if the state was rejected.
+fn verify(w: &mut AppWorld, signer: &StateSigner, state: &str, verifier: &str) {
+    let return_path = signer.verify(state, verifier);
+    w.state
+        .insert("return-path", serde_json::json!(return_path));
+}
+
+/// Starts a log-in like the secured endpoint does for an unauthorized request.
+///
+/// Stores the code verifier with `verifier` key, the code challenge with `challenge` key and the
+/// signed state with `login-state` key in the world state.
+#[given(expr = "a log-in is started for the return path {string}")]
+async fn given_started(w: &mut AppWorld, return_path: String) -> anyhow::Result<()> {
+    let pkce = Pkce::new();
+    let state = w
+        .app
+        .auth_ctx()
+        .state_signer()
+        .sign(&return_path, pkce.verifier())
+        .expect("the return path should be signed");
+
+    w.state
+        .insert("verifier", serde_json::json!(pkce.verifier()));
+    w.state
+        .insert("challenge", serde_json::json!(pkce.challenge()));
+    w.state.insert("login-state", serde_json::json!(state));
+    Ok(())
+}
+
+#[when(expr = "the state is verified with the code verifier of the log-in")]
+async fn verified(w: &mut AppWorld) -> anyhow::Result<()> {
+    let state = login_value(w, "login-state");
+    let verifier = login_value(w, "verifier");
+    let auth_ctx = w.app.auth_ctx();
+    verify(w, auth_ctx.state_signer(), &state, &verifier);
+    Ok(())
+}
+
+/// Verifies the state with the code verifier of another log-in, like an attacker injecting their
+/// own `state` and `code` into the log-in of a victim.
+#[when(expr = "the state is verified with the code verifier of another log-in")]
+async fn verified_with_other_verifier(w: &mut AppWorld) -> anyhow::Result<()> {
+    let state = login_value(w, "login-state");
+    let auth_ctx = w.app.auth_ctx();
+    verify(w, auth_ctx.state_signer(), &state, Pkce::new().verifier());
+    Ok(())
+}
+
+#[when(expr = "the state is verified by a signer with another secret")]
+async fn verified_with_other_secret(w: &mut AppWorld) -> anyhow::Result<()> {
+    let state = login_value(w, "login-state");
+    let verifier = login_value(w, "verifier");
+    verify(
+        w,
+        &StateSigner::new(Some("another-secret")),
+        &state,
+        &verifier,
+    );
+    Ok(())
+}
+
+/// Replaces the return path in the payload of the state, but keeps its signature.
+#[when(expr = "the state is verified with the return path changed to {string}")]
+async fn verified_with_changed_return_path(
+    w: &mut AppWorld,
+    return_path: String,
+) -> anyhow::Result<()> {
+    let state = login_value(w, "login-state");
+    let verifier = login_value(w, "verifier");
+    let (payload, signature) = state
+        .split_once('.')
+        .expect("the state should have a signature");
+    let payload = String::from_utf8(URL_SAFE_NO_PAD.decode(payload)?)?;
+    let (expires, _) = payload
+        .split_once('|')
+        .expect("the payload should have an expiry");
+    let state = format!(
+        "{}.{signature}",
+        URL_SAFE_NO_PAD.encode(format!("{expires}|{return_path}"))
+    );
+
+    let auth_ctx = w.app.auth_ctx();
+    verify(w, auth_ctx.state_signer(), &state, &verifier);
+    Ok(())
+}
+
+#[then(expr = "the state is accepted with the return path {string}")]
+async fn accepted(w: &mut AppWorld, return_path: String) -> anyhow::Result<()> {
+    assert_eq!(
+        w.state
+            .get("return-path")
+            .expect("the state should be verified")
+            .as_str(),
+        Some(return_path.as_str())
+    );
+    Ok(())
+}
+
+#[then(expr = "the state is rejected")]
+async fn rejected(w: &mut AppWorld) -> anyhow::Result<()> {
+    let return_path = w
+        .state
+        .get("return-path")
+        .expect("the state should be verified");
+    assert!(
+        return_path.is_null(),
+        "the state should be rejected: {return_path}"
+    );
+    Ok(())
+}
+
+#[then(expr = "the code challenge is the S256 hash of the code verifier")]
+async fn challenge(w: &mut AppWorld) -> anyhow::Result<()> {
+    let verifier = login_value(w, "verifier");
+    assert_eq!(Pkce::METHOD, "S256");
+    assert_eq!(
+        login_value(w, "challenge"),
+        URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+    );
+    Ok(())
+}
+
+#[then(expr = "no log-in can be started for the return path {string}")]
+async fn not_started(w: &mut AppWorld, return_path: String) -> anyhow::Result<()> {
+    let state = w
+        .app
+        .auth_ctx()
+        .state_signer()
+        .sign(&return_path, Pkce::new().verifier());
+    assert!(
+        state.is_none(),
+        "the return path should be rejected: {state:?}"
+    );
+    Ok(())
+}
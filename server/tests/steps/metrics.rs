@@ -0,0 +1,164 @@
+use crate::common::AppWorld;
+use async_graphql::EmptyMutation;
+use async_graphql::EmptySubscription;
+use async_graphql::Object;
+use async_graphql::Schema;
+use cucumber::given;
+use cucumber::then;
+use cucumber::when;
+use prometheus::core::Collector;
+use prometheus::proto::MetricType;
+use qgt_metrics::graphql::GraphQLMetrics;
+use qgt_metrics::GRAPHQL_OPERATIONS;
+use qgt_metrics::GRAPHQL_OPERATION_DURATION;
+use qgt_metrics::GRAPHQL_OPERATION_ERRORS;
+use qgt_metrics::GRAPHQL_RESOLVER_DURATION;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+/// The number of schemas built to test the operation metrics, which gives each a unique name.
+static TEST_SCHEMAS: AtomicUsize = AtomicUsize::new(0);
+
+/// The query root of the schema to test the operation metrics.
+struct TestQuery;
+
+#[Object]
+impl TestQuery {
+    async fn value(&self) -> i32 {
+        1
+    }
+}
+
+/// Get the total of all series of the `metric` with the `schema` label.
+///
+/// Counters are summed by value and histograms by sample count.
+fn schema_total(metric: &impl Collector, schema: &str) -> u64 {
+    let mut total = 0;
+    for family in metric.collect() {
+        for metric in family.get_metric() {
+            if !metric
+                .get_label()
+                .iter()
+                .any(|label| label.name() == "schema" && label.value() == schema)
+            {
+                continue;
+            }
+            total += match family.get_field_type() {
+                MetricType::HISTOGRAM => metric.get_histogram().get_sample_count(),
+                _ => metric.get_counter().get_value() as u64,
+            };
+        }
+    }
+    total
+}
+
+/// Notes the counts of the operations of the schema, since the metrics are shared by all
+/// scenarios.
+///
+/// Stores the counts with the `operation-count` and `operation-error-count` keys in the world
+/// state.
+#[given(expr = "the metrics of the {word} operations are noted")]
+async fn note(w: &mut AppWorld, schema: String) -> anyhow::Result<()> {
+    w.state.insert(
+        "operation-count",
+        serde_json::Value::from(schema_total(&*GRAPHQL_OPERATIONS, &schema)),
+    );
+    w.state.insert(
+        "operation-error-count",
+        serde_json::Value::from(schema_total(&*GRAPHQL_OPERATION_ERRORS, &schema)),
+    );
+    Ok(())
+}
+
+#[then(expr = "{int} more {word} operation(s) was/were recorded")]
+async fn operations_recorded(w: &mut AppWorld, count: u64, schema: String) -> anyhow::Result<()> {
+    let noted = w
+        .state
+        .get("operation-count")
+        .and_then(|count| count.as_u64())
+        .expect("the operation metrics should be noted");
+    assert_eq!(schema_total(&*GRAPHQL_OPERATIONS, &schema), noted + count);
+    assert!(schema_total(&*GRAPHQL_OPERATION_DURATION, &schema) >= noted + count);
+    Ok(())
+}
+
+#[then(expr = "{int} more {word} operation(s) with errors was/were recorded")]
+async fn operation_errors_recorded(
+    w: &mut AppWorld,
+    count: u64,
+    schema: String,
+) -> anyhow::Result<()> {
+    let noted = w
+        .state
+        .get("operation-error-count")
+        .and_then(|count| count.as_u64())
+        .expect("the operation metrics should be noted");
+    assert_eq!(
+        schema_total(&*GRAPHQL_OPERATION_ERRORS, &schema),
+        noted + count
+    );
+    Ok(())
+}
+
+#[then(
+    expr = "the resolver of the field {string} of the type {string} of the {word} schema was recorded"
+)]
+async fn resolver_recorded(
+    _w: &mut AppWorld,
+    field: String,
+    parent_type: String,
+    schema: String,
+) -> anyhow::Result<()> {
+    assert!(
+        GRAPHQL_RESOLVER_DURATION
+            .with_label_values(&[schema.as_str(), parent_type.as_str(), field.as_str()])
+            .get_sample_count()
+            > 0,
+        "the resolver '{parent_type}.{field}' should be recorded"
+    );
+    Ok(())
+}
+
+/// Executes the operations with the `names` on a new schema with a unique name, which records at
+/// most `limit` operation names.
+///
+/// Stores the name of the schema with `metrics-schema` key in the world state.
+#[when(
+    expr = "the operations {string} are executed on a schema recording at most {int} operation names"
+)]
+async fn execute_named(w: &mut AppWorld, names: String, limit: usize) -> anyhow::Result<()> {
+    let schema_name: &'static str = Box::leak(
+        format!("test-{}", TEST_SCHEMAS.fetch_add(1, Ordering::Relaxed)).into_boxed_str(),
+    );
+    let schema = Schema::build(TestQuery, EmptyMutation, EmptySubscription)
+        .extension(GraphQLMetrics::new(schema_name).with_operation_name_limit(limit))
+        .finish();
+    for name in names.split(",").map(|name| name.trim()) {
+        let response = schema.execute(format!("query {name} {{ value }}")).await;
+        assert!(
+            response.is_ok(),
+            "the operation should succeed: {response:?}"
+        );
+    }
+
+    w.state
+        .insert("metrics-schema", serde_json::Value::from(schema_name));
+    Ok(())
+}
+
+#[then(expr = "the operation {string} was recorded {int} time(s)")]
+async fn operation_recorded(w: &mut AppWorld, name: String, count: u64) -> anyhow::Result<()> {
+    let schema = w
+        .state
+        .get("metrics-schema")
+        .and_then(|schema| schema.as_str())
+        .expect("the operations should be executed");
+    assert_eq!(
+        GRAPHQL_OPERATIONS
+            .with_label_values(&[schema, name.as_str()])
+            .get(),
+        count,
+        "unexpected count of the operation '{name}'"
+    );
+    Ok(())
+}
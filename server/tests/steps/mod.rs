@@ -1,4 +1,16 @@
+mod access_token;
+mod auth;
 mod common;
+mod config;
+mod discovery;
+mod health;
+mod http;
+mod login;
+mod metrics;
+mod rest;
+mod session;
 mod setup;
+mod shutdown;
 mod tag;
+mod telemetry;
 mod todo;
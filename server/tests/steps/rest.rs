@@ -0,0 +1,310 @@
+use super::auth::issuer_and_audience;
+use super::auth::TOKEN_LIFETIME;
+use crate::common::AppWorld;
+use crate::common::CustomBool;
+use crate::utils::mint_token;
+use axum::http::header::AUTHORIZATION;
+use axum::http::header::CONTENT_TYPE;
+use axum::http::header::HOST;
+use axum::http::Method;
+use axum::http::Request;
+use bson::doc;
+use bson::Document;
+use cucumber::gherkin::Step;
+use cucumber::given;
+use cucumber::then;
+use cucumber::when;
+use jsonpath_rust::JsonPath;
+use qgt_auth::config::PublicAuthMode;
+use qgt_domain::config::Config;
+use qgt_domain::db::collections::TODOS;
+use std::path::Path;
+use std::str::FromStr;
+
+/// The host the REST requests are sent to, unless a step sends them to another host.
+const HOST_NAME: &str = "localhost:3000";
+
+/// Sends a REST request with the `method` to the `uri` and the optional JSON `body`.
+///
+/// The request is sent with the `headers` and the bearer token with `rest-token` key in the world
+/// state, if there is one.
+async fn send(
+    w: &mut AppWorld,
+    method: Method,
+    uri: &str,
+    body: Option<&str>,
+    headers: &[(&str, &str)],
+) -> anyhow::Result<()> {
+    let mut request = Request::builder().method(method).uri(uri);
+    if !headers
+        .iter()
+        .any(|(name, _)| name.eq_ignore_ascii_case("host"))
+    {
+        request = request.header(HOST, HOST_NAME);
+    }
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    if let Some(token) = w.state.get("rest-token").and_then(|token| token.as_str()) {
+        request = request.header(AUTHORIZATION, format!("Bearer {token}"));
+    }
+    let request = match body {
+        Some(body) => request
+            .header(CONTENT_TYPE, "application/json")
+            .body(axum::body::Body::from(body.to_string()))?,
+        None => request.body(axum::body::Body::empty())?,
+    };
+
+    w.http(request).await;
+    Ok(())
+}
+
+/// Get the URI of the todo with the `title` in the database.
+async fn todo_uri(w: &AppWorld, title: &str) -> anyhow::Result<String> {
+    let todo = w
+        .app
+        .db()
+        .get()
+        .collection::<Document>(TODOS)
+        .find_one(doc! { "title": title })
+        .await?
+        .unwrap_or_else(|| panic!("a todo with title '{title}' should exist"));
+    Ok(format!("/todos/{}", todo.get_object_id("_id")?.to_hex()))
+}
+
+/// Get the single value of the last HTTP response body at the `json_path`.
+fn json_node(w: &AppWorld, json_path: &str) -> anyhow::Result<serde_json::Value> {
+    let body = &w.last_http_response().body;
+    let result = JsonPath::from_str(json_path)?.find(body);
+    let values = result.as_array().expect("the value should be an array");
+    assert_eq!(
+        values.len(),
+        1,
+        "unexpected length '{}' of found values for path '{json_path}': {body}",
+        values.len()
+    );
+    Ok(values[0].clone())
+}
+
+/// Reloads the app with the configuration `fixture`, which makes the public endpoint serve
+/// requests with a valid bearer token for the signed-in user.
+async fn reload_with_optional_auth(w: &mut AppWorld, fixture: &str) -> anyhow::Result<()> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/config")
+        .join(fixture);
+    let config = Config::load(Some(&path)).map_err(|e| anyhow::anyhow!("{e}"))?;
+    w.app = qgt_domain::app::App::new(config).await?;
+    assert_eq!(
+        w.app.auth_ctx().config().public_mode(),
+        PublicAuthMode::Optional
+    );
+    Ok(())
+}
+
+/// Reloads the app, so the public endpoint serves requests with a valid bearer token for the
+/// signed-in user.
+#[given("the public endpoint authenticates bearer tokens optionally")]
+async fn authenticate_optionally(w: &mut AppWorld) -> anyhow::Result<()> {
+    reload_with_optional_auth(w, "optional_auth.toml").await
+}
+
+/// Reloads the app, so the public endpoint serves requests with a valid bearer token for the
+/// signed-in user and anonymous requests have no roles.
+#[given("the public endpoint authenticates bearer tokens optionally without public roles")]
+async fn authenticate_optionally_without_public_roles(w: &mut AppWorld) -> anyhow::Result<()> {
+    reload_with_optional_auth(w, "optional_auth_without_public_roles.toml").await?;
+    assert_eq!(w.app.auth_ctx().config().public_roles().count(), 0);
+    Ok(())
+}
+
+/// Mints a token for the user and stores it with the `rest-token` key in the world state, so all
+/// following REST requests are sent with it.
+#[given(
+    expr = "the REST requests are sent with a token of the user {string} with the roles {string}"
+)]
+async fn with_token(w: &mut AppWorld, subject: String, roles: String) -> anyhow::Result<()> {
+    let (issuer, audience) = issuer_and_audience(w);
+    let roles: Vec<&str> = roles.split(",").map(|role| role.trim()).collect();
+    w.state.insert(
+        "rest-token",
+        serde_json::Value::from(mint_token(
+            &issuer,
+            &audience,
+            &subject,
+            &roles,
+            TOKEN_LIFETIME,
+        )),
+    );
+    Ok(())
+}
+
+#[when("the todos are requested with REST")]
+async fn list(w: &mut AppWorld) -> anyhow::Result<()> {
+    send(w, Method::GET, "/todos", None, &[]).await
+}
+
+#[when(
+    expr = "the todos are requested with REST from the host {string} with the protocol {string}"
+)]
+async fn list_from_host(w: &mut AppWorld, host: String, protocol: String) -> anyhow::Result<()> {
+    send(
+        w,
+        Method::GET,
+        "/todos",
+        None,
+        &[("host", &host), ("x-forwarded-proto", &protocol)],
+    )
+    .await
+}
+
+#[when("a todo is posted with REST with body")]
+async fn create(w: &mut AppWorld, step: &Step) -> anyhow::Result<()> {
+    let body = step.docstring().expect("the step should have a body");
+    send(w, Method::POST, "/todos", Some(body), &[]).await
+}
+
+#[when(expr = "the todo {string} is requested with REST")]
+async fn get(w: &mut AppWorld, title: String) -> anyhow::Result<()> {
+    let uri = todo_uri(w, &title).await?;
+    send(w, Method::GET, &uri, None, &[]).await
+}
+
+#[when(expr = "the todo with the id {string} is requested with REST")]
+async fn get_by_id(w: &mut AppWorld, id: String) -> anyhow::Result<()> {
+    send(w, Method::GET, &format!("/todos/{id}"), None, &[]).await
+}
+
+#[when(expr = "the todo {string} is patched with REST with body")]
+async fn update(w: &mut AppWorld, title: String, step: &Step) -> anyhow::Result<()> {
+    let uri = todo_uri(w, &title).await?;
+    let body = step.docstring().expect("the step should have a body");
+    send(w, Method::PATCH, &uri, Some(body), &[]).await
+}
+
+#[when(expr = "the todo {string} is deleted with REST")]
+async fn remove(w: &mut AppWorld, title: String) -> anyhow::Result<()> {
+    let uri = todo_uri(w, &title).await?;
+    send(w, Method::DELETE, &uri, None, &[]).await
+}
+
+#[when("all todos are deleted with REST")]
+async fn remove_all(w: &mut AppWorld) -> anyhow::Result<()> {
+    send(w, Method::DELETE, "/todos", None, &[]).await
+}
+
+#[when(expr = "a CORS preflight for {word} {string} is sent from the origin {string}")]
+async fn preflight(
+    w: &mut AppWorld,
+    method: String,
+    uri: String,
+    origin: String,
+) -> anyhow::Result<()> {
+    send(
+        w,
+        Method::OPTIONS,
+        &uri,
+        None,
+        &[
+            ("origin", &origin),
+            ("access-control-request-method", &method),
+            ("access-control-request-headers", "content-type"),
+        ],
+    )
+    .await
+}
+
+#[then(expr = "the HTTP response is a list with {int} entry/entries")]
+async fn list_length(w: &mut AppWorld, length: usize) -> anyhow::Result<()> {
+    let body = &w.last_http_response().body;
+    assert_eq!(
+        body.as_array().map(Vec::len),
+        Some(length),
+        "unexpected list length: {body}"
+    );
+    Ok(())
+}
+
+#[then(expr = "the HTTP response JSON node {string} has the value {string}")]
+async fn json_node_value(w: &mut AppWorld, json_path: String, value: String) -> anyhow::Result<()> {
+    assert_eq!(json_node(w, &json_path)?.as_str(), Some(value.as_str()));
+    Ok(())
+}
+
+#[then(expr = "the HTTP response JSON node {string} has the integer value {int}")]
+async fn json_node_integer_value(
+    w: &mut AppWorld,
+    json_path: String,
+    value: i64,
+) -> anyhow::Result<()> {
+    assert_eq!(json_node(w, &json_path)?.as_i64(), Some(value));
+    Ok(())
+}
+
+#[then(expr = "the HTTP response JSON node {string} has the boolean value {bool}")]
+async fn json_node_boolean_value(
+    w: &mut AppWorld,
+    json_path: String,
+    value: CustomBool,
+) -> anyhow::Result<()> {
+    assert_eq!(json_node(w, &json_path)?.as_bool(), Some(*value));
+    Ok(())
+}
+
+#[then(expr = "the HTTP response JSON node {string} starts with {string}")]
+async fn json_node_prefix(
+    w: &mut AppWorld,
+    json_path: String,
+    prefix: String,
+) -> anyhow::Result<()> {
+    let value = json_node(w, &json_path)?;
+    assert!(
+        value
+            .as_str()
+            .is_some_and(|value| value.starts_with(&prefix)),
+        "the node with path '{json_path}' should start with '{prefix}': {value}"
+    );
+    Ok(())
+}
+
+#[then(expr = "the HTTP response JSON node {string} is the url of the todo {string}")]
+async fn json_node_todo_url(
+    w: &mut AppWorld,
+    json_path: String,
+    title: String,
+) -> anyhow::Result<()> {
+    let url = format!("http://{HOST_NAME}{}", todo_uri(w, &title).await?);
+    assert_eq!(json_node(w, &json_path)?.as_str(), Some(url.as_str()));
+    Ok(())
+}
+
+#[then(expr = "the HTTP response header {string} is the url of the todo {string}")]
+async fn header_todo_url(w: &mut AppWorld, name: String, title: String) -> anyhow::Result<()> {
+    let url = format!("http://{HOST_NAME}{}", todo_uri(w, &title).await?);
+    let response = w.last_http_response();
+    assert_eq!(
+        response
+            .headers
+            .get(name.as_str())
+            .and_then(|header| header.to_str().ok()),
+        Some(url.as_str()),
+        "response: {response:?}"
+    );
+    Ok(())
+}
+
+#[then(expr = "the todo with title {string} is owned by the user {string}")]
+async fn owned_by(w: &mut AppWorld, title: String, owner: String) -> anyhow::Result<()> {
+    let cnt = w
+        .app
+        .db()
+        .get()
+        .collection::<Document>(TODOS)
+        .count_documents(doc! { "title": &title, "owner": &owner })
+        .await?;
+
+    assert_eq!(
+        cnt, 1,
+        "unexpected todo count '{cnt}' with title '{title}' and owner '{owner}'"
+    );
+    Ok(())
+}
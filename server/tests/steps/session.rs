@@ -0,0 +1,214 @@
+use crate::common::AppWorld;
+use axum::extract::Request;
+use axum::http::header::COOKIE;
+use axum::http::Method;
+use cucumber::given;
+use cucumber::then;
+use cucumber::when;
+use qgt_domain::session::SessionTokens;
+use std::time::Duration;
+
+/// Creates a session with the `access_token`, which expires after `expires_in` seconds.
+///
+/// Stores the id of the session with `session-id` key in the world state as string.
+async fn create(
+    w: &mut AppWorld,
+    access_token: String,
+    expires_in: u64,
+    refresh_token: Option<String>,
+) -> anyhow::Result<()> {
+    let session = w
+        .app
+        .sessions()
+        .create(SessionTokens {
+            access_token,
+            expires_in: Duration::from_secs(expires_in),
+            refresh_token,
+            refresh_expires_in: None,
+            id_token: None,
+        })
+        .await?;
+
+    w.state
+        .insert("session-id", serde_json::json!(session.id()));
+    Ok(())
+}
+
+/// Looks up the session with the `id`.
+///
+/// Stores the tokens of the session and if it needs a refresh with `session` key in the world
+/// state, which is null if the session was not found.
+async fn look_up(w: &mut AppWorld, id: &str) -> anyhow::Result<()> {
+    let session = w.app.sessions().get(id).await?;
+
+    w.state.insert(
+        "session",
+        session.map_or(serde_json::Value::Null, |session| {
+            serde_json::json!({
+                "accessToken": session.access_token(),
+                "refreshToken": session.refresh_token(),
+                "idToken": session.id_token(),
+                "needsRefresh": session.needs_refresh(),
+            })
+        }),
+    );
+    Ok(())
+}
+
+/// Get the id of the created session.
+fn session_id(w: &AppWorld) -> String {
+    w.state
+        .get("session-id")
+        .and_then(|id| id.as_str())
+        .expect("a session should be created")
+        .to_string()
+}
+
+/// Get the value of the `field` of the looked up session.
+fn session_field<'a>(w: &'a AppWorld, field: &str) -> &'a serde_json::Value {
+    let session = w
+        .state
+        .get("session")
+        .expect("a session should be looked up");
+    assert!(!session.is_null(), "the session should be found");
+    &session[field]
+}
+
+#[given(expr = "a session is created with the access token {string} expiring in {int} seconds")]
+async fn given_created(
+    w: &mut AppWorld,
+    access_token: String,
+    expires_in: u64,
+) -> anyhow::Result<()> {
+    create(w, access_token, expires_in, None).await
+}
+
+#[given(
+    expr = "a session is created with the access token {string} expiring in {int} seconds and the refresh token {string}"
+)]
+async fn given_created_with_refresh_token(
+    w: &mut AppWorld,
+    access_token: String,
+    expires_in: u64,
+    refresh_token: String,
+) -> anyhow::Result<()> {
+    create(w, access_token, expires_in, Some(refresh_token)).await
+}
+
+/// Refreshes the session with a token response, which only contains an access token.
+#[when(expr = "the session is refreshed with the access token {string}")]
+async fn refreshed(w: &mut AppWorld, access_token: String) -> anyhow::Result<()> {
+    let id = session_id(w);
+    let session = w
+        .app
+        .sessions()
+        .refresh(
+            &id,
+            SessionTokens {
+                access_token,
+                expires_in: Duration::from_secs(300),
+                refresh_token: None,
+                refresh_expires_in: None,
+                id_token: None,
+            },
+        )
+        .await?;
+    assert!(session.is_some(), "the session should be refreshed");
+    Ok(())
+}
+
+/// Sends a logout request with the `method` and the cookie of the created session.
+async fn logout(w: &mut AppWorld, method: Method) -> anyhow::Result<()> {
+    let request = Request::builder()
+        .method(method)
+        .uri("/secure/logout")
+        .header(COOKIE, format!("qgt_session={}", session_id(w)))
+        .body(axum::body::Body::empty())?;
+
+    w.http(request).await;
+    Ok(())
+}
+
+#[when(expr = "the logout is posted with the session cookie")]
+async fn logout_posted(w: &mut AppWorld) -> anyhow::Result<()> {
+    logout(w, Method::POST).await
+}
+
+#[when(expr = "the logout is requested with GET and the session cookie")]
+async fn logout_requested_with_get(w: &mut AppWorld) -> anyhow::Result<()> {
+    logout(w, Method::GET).await
+}
+
+#[when(expr = "the session is looked up")]
+async fn looked_up(w: &mut AppWorld) -> anyhow::Result<()> {
+    let id = session_id(w);
+    look_up(w, &id).await
+}
+
+#[when(expr = "a session with an unknown id is looked up")]
+async fn unknown_looked_up(w: &mut AppWorld) -> anyhow::Result<()> {
+    look_up(w, "unknown").await
+}
+
+#[then(expr = "the session is found with the access token {string}")]
+async fn found(w: &mut AppWorld, access_token: String) -> anyhow::Result<()> {
+    assert_eq!(
+        session_field(w, "accessToken").as_str(),
+        Some(access_token.as_str())
+    );
+    Ok(())
+}
+
+#[then(expr = "the session is not found")]
+async fn not_found(w: &mut AppWorld) -> anyhow::Result<()> {
+    let session = w
+        .state
+        .get("session")
+        .expect("a session should be looked up");
+    assert!(
+        session.is_null(),
+        "the session should not be found: {session}"
+    );
+    Ok(())
+}
+
+#[then(expr = "the session is found with the refresh token {string}")]
+async fn found_with_refresh_token(w: &mut AppWorld, refresh_token: String) -> anyhow::Result<()> {
+    assert_eq!(
+        session_field(w, "refreshToken").as_str(),
+        Some(refresh_token.as_str())
+    );
+    Ok(())
+}
+
+#[then(expr = "the session needs a refresh")]
+async fn needs_refresh(w: &mut AppWorld) -> anyhow::Result<()> {
+    assert_eq!(session_field(w, "needsRefresh").as_bool(), Some(true));
+    Ok(())
+}
+
+#[then(expr = "the session does not need a refresh")]
+async fn needs_no_refresh(w: &mut AppWorld) -> anyhow::Result<()> {
+    assert_eq!(session_field(w, "needsRefresh").as_bool(), Some(false));
+    Ok(())
+}
+
+#[then(expr = "the session was removed")]
+async fn removed(w: &mut AppWorld) -> anyhow::Result<()> {
+    let id = session_id(w);
+    assert!(
+        w.app.sessions().get(&id).await?.is_none(),
+        "the session should be removed"
+    );
+    Ok(())
+}
+
+#[then(expr = "the session still exists")]
+async fn exists(w: &mut AppWorld) -> anyhow::Result<()> {
+    let id = session_id(w);
+    assert!(
+        w.app.sessions().get(&id).await?.is_some(),
+        "the session should still exist"
+    );
+    Ok(())
+}
@@ -14,7 +14,7 @@ async fn run(w: &mut AppWorld, n: i32) -> anyhow::Result<()> {
 #[then("all database collections are empty")]
 async fn all_collections_empty(w: &mut AppWorld) -> anyhow::Result<()> {
     let mut counts: HashMap<String, u64> = HashMap::new();
-    let collections = w.app.db().collections().await;
+    let collections = w.app.db().get().list_collection_names().await?;
 
     for collection in collections.iter() {
         let cnt = w
@@ -0,0 +1,210 @@
+use crate::common::AppWorld;
+use crate::common::RunningServer;
+use cucumber::given;
+use cucumber::then;
+use cucumber::when;
+use qgt_server::shutdown::Shutdown;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+
+/// The subscription, which only responds if a tag is removed.
+const SUBSCRIPTION: &str =
+    r#"{"id":"1","type":"subscribe","payload":{"query":"subscription { tagRemoved }"}}"#;
+
+/// The time the server gets to respond to a message of the WebSocket.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The time a started subscription is polled to register it before the next step.
+const SUBSCRIPTION_START_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Get the started server.
+fn server(w: &mut AppWorld) -> &mut RunningServer {
+    w.server.as_mut().expect("the server should be started")
+}
+
+/// Encode the `text` as WebSocket frame of a client.
+///
+/// Frames of clients must be masked, and the zero mask keeps the payload unchanged.
+fn text_frame(text: &str) -> Vec<u8> {
+    let mut frame = vec![0x81];
+    match u8::try_from(text.len()) {
+        Ok(length) if length < 126 => frame.push(0x80 | length),
+        _ => {
+            frame.push(0x80 | 126);
+            frame.extend(
+                u16::try_from(text.len())
+                    .expect("the text should fit into a frame")
+                    .to_be_bytes(),
+            );
+        }
+    }
+    frame.extend([0; 4]);
+    frame.extend(text.as_bytes());
+    frame
+}
+
+/// Encode a close frame of a client with the `code`.
+fn close_frame(code: u16) -> Vec<u8> {
+    let mut frame = vec![0x88, 0x80 | 2];
+    frame.extend([0; 4]);
+    frame.extend(code.to_be_bytes());
+    frame
+}
+
+/// Read from the `connection` until the received bytes contain the `expected` text.
+async fn read_until(connection: &mut TcpStream, expected: &str) -> anyhow::Result<String> {
+    let mut received = Vec::new();
+    tokio::time::timeout(RESPONSE_TIMEOUT, async {
+        let mut buffer = [0; 1024];
+        while !String::from_utf8_lossy(&received).contains(expected) {
+            let read = connection.read(&mut buffer).await?;
+            anyhow::ensure!(read > 0, "the connection was closed before '{expected}'");
+            received.extend(&buffer[..read]);
+        }
+        Ok(())
+    })
+    .await??;
+
+    Ok(String::from_utf8_lossy(&received).into_owned())
+}
+
+#[given(expr = "the server is running with a shutdown timeout of {int} seconds")]
+async fn start(w: &mut AppWorld, timeout: u64) -> anyhow::Result<()> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let address = listener.local_addr()?;
+    let (signal, signaled) = tokio::sync::oneshot::channel();
+    let shutdown = Shutdown::with_signal(Duration::from_secs(timeout), async move {
+        let _ = signaled.await;
+    });
+    let router = qgt_server::api::router::get(w.app.clone(), shutdown.clone()).await;
+    let served =
+        tokio::spawn(async move { qgt_server::shutdown::serve(listener, router, &shutdown).await });
+
+    w.server = Some(RunningServer {
+        address,
+        signal: Some(signal),
+        served,
+        connection: None,
+    });
+    Ok(())
+}
+
+#[given("a WebSocket subscription is started")]
+async fn start_subscription(w: &mut AppWorld) -> anyhow::Result<()> {
+    let server = server(w);
+    let mut connection = TcpStream::connect(server.address).await?;
+    connection
+        .write_all(
+            format!(
+                "GET /api/graphql/ws HTTP/1.1\r\n\
+                Host: {}\r\n\
+                Upgrade: websocket\r\n\
+                Connection: Upgrade\r\n\
+                Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+                Sec-WebSocket-Version: 13\r\n\
+                Sec-WebSocket-Protocol: graphql-transport-ws\r\n\r\n",
+                server.address
+            )
+            .as_bytes(),
+        )
+        .await?;
+    let handshake = read_until(&mut connection, "\r\n\r\n").await?;
+    assert!(
+        handshake.starts_with("HTTP/1.1 101"),
+        "the WebSocket should be upgraded: {handshake}"
+    );
+
+    connection
+        .write_all(&text_frame(r#"{"type":"connection_init"}"#))
+        .await?;
+    read_until(&mut connection, "connection_ack").await?;
+    connection.write_all(&text_frame(SUBSCRIPTION)).await?;
+    let mut buffer = [0; 1024];
+    let response =
+        tokio::time::timeout(SUBSCRIPTION_START_TIMEOUT, connection.read(&mut buffer)).await;
+    assert!(
+        response.is_err(),
+        "the subscription should not respond before a change: {response:?}"
+    );
+
+    server.connection = Some(connection);
+    Ok(())
+}
+
+#[when("the server receives the shutdown signal")]
+async fn signal(w: &mut AppWorld) -> anyhow::Result<()> {
+    server(w)
+        .signal
+        .take()
+        .expect("the signal should only be sent once")
+        .send(())
+        .expect("the server should wait for the signal");
+    Ok(())
+}
+
+#[then(expr = "the WebSocket receives a close frame with the code {int} within {int} second(s)")]
+async fn close_frame_received(w: &mut AppWorld, code: u16, seconds: u64) -> anyhow::Result<()> {
+    let connection = server(w)
+        .connection
+        .as_mut()
+        .expect("a WebSocket subscription should be started");
+    let mut received: Vec<u8> = Vec::new();
+    tokio::time::timeout(Duration::from_secs(seconds), async {
+        let mut buffer = [0; 1024];
+        // Frames of the server are not masked, so the code follows the length
+        while !received
+            .windows(4)
+            .any(|frame| frame[0] == 0x88 && frame[2..] == code.to_be_bytes())
+        {
+            let read = connection.read(&mut buffer).await?;
+            anyhow::ensure!(read > 0, "the connection was closed before the close frame");
+            received.extend(&buffer[..read]);
+        }
+        Ok(())
+    })
+    .await??;
+    Ok(())
+}
+
+#[when("the WebSocket client answers with a close frame")]
+async fn answer_close(w: &mut AppWorld) -> anyhow::Result<()> {
+    server(w)
+        .connection
+        .as_mut()
+        .expect("a WebSocket subscription should be started")
+        .write_all(&close_frame(1000))
+        .await?;
+    Ok(())
+}
+
+#[then(expr = "the WebSocket connection is closed within {int} second(s)")]
+async fn connection_closed(w: &mut AppWorld, seconds: u64) -> anyhow::Result<()> {
+    let mut connection = server(w)
+        .connection
+        .take()
+        .expect("a WebSocket subscription should be started");
+    let closed = tokio::time::timeout(Duration::from_secs(seconds), async {
+        let mut buffer = [0; 1024];
+        // A reset connection is closed as well
+        while connection.read(&mut buffer).await.unwrap_or(0) > 0 {}
+    })
+    .await;
+    assert!(
+        closed.is_ok(),
+        "the WebSocket connection should be closed within {seconds}s"
+    );
+    Ok(())
+}
+
+#[then(expr = "the server stops within {int} second(s)")]
+async fn stopped(w: &mut AppWorld, seconds: u64) -> anyhow::Result<()> {
+    let served = &mut server(w).served;
+    let result = tokio::time::timeout(Duration::from_secs(seconds), served).await;
+    assert!(
+        matches!(result, Ok(Ok(Ok(())))),
+        "the server should stop within {seconds}s: {result:?}"
+    );
+    Ok(())
+}
@@ -39,6 +39,36 @@ async fn given_tag(w: &mut AppWorld, name: String) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Starts the tagChanged subscription.
+#[given(expr = "the tagChanged subscription is started")]
+async fn given_tag_changed_subscription(w: &mut AppWorld) -> anyhow::Result<()> {
+    let stream = w
+        .graphql(
+            String::from("tagChanged"),
+            include_str!("../graphql/tag/tag_changed.graphql"),
+        )
+        .subscribe();
+
+    w.start_subscription(String::from("tagChanged"), stream)
+        .await;
+    Ok(())
+}
+
+/// Starts the tagRemoved subscription.
+#[given(expr = "the tagRemoved subscription is started")]
+async fn given_tag_removed_subscription(w: &mut AppWorld) -> anyhow::Result<()> {
+    let stream = w
+        .graphql(
+            String::from("tagRemoved"),
+            include_str!("../graphql/tag/tag_removed.graphql"),
+        )
+        .subscribe();
+
+    w.start_subscription(String::from("tagRemoved"), stream)
+        .await;
+    Ok(())
+}
+
 /// Creates a tag with given payload.
 ///
 /// Stores the response as world data.
@@ -98,17 +128,68 @@ async fn update(w: &mut AppWorld, step: &Step) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Updates a non-existing tag with given payload.
+///
+/// Stores the response as world data.
+#[when(expr = "updateTag is sent with body for a non-existing tag")]
+async fn update_non_existing(w: &mut AppWorld, step: &Step) -> anyhow::Result<()> {
+    let docstring = step
+        .docstring()
+        .expect("a docstring payload should be provided");
+    let mut payload = serde_json::Value::from_str(docstring.trim())
+        .expect("docstring should be valid and parsable JSON");
+
+    // Replace id with a new id, which does not exist in the database
+    payload
+        .as_object_mut()
+        .expect("payload should be an object")
+        .insert(
+            String::from("id"),
+            serde_json::Value::from(ObjectId::new().to_hex()),
+        );
+
+    let response = w
+        .graphql(
+            String::from("updateTag"),
+            include_str!("../graphql/tag/update.graphql"),
+        )
+        .add_variable("input", payload)
+        .execute()
+        .await;
+
+    w.save_last_response(response);
+    Ok(())
+}
+
 /// Removes tags based on their ids.
 /// Retrieves the ids based on provided names.
 ///
 /// Stores the response as world data.
 #[when(expr = "removeTags is sent with ids for {string}")]
 async fn delete(w: &mut AppWorld, names: String) -> anyhow::Result<()> {
-    let names: Vec<&str> = names
-        .split(",")
-        .into_iter()
-        .map(|name| name.trim())
-        .collect();
+    delete_with_mode(w, names, None).await
+}
+
+/// Removes tags based on their ids with the requested removal mode.
+/// Retrieves the ids based on provided names.
+///
+/// Stores the response as world data.
+#[when(expr = "removeTags is sent with ids for {string} and mode {word}")]
+async fn delete_with_mode_step(
+    w: &mut AppWorld,
+    names: String,
+    mode: String,
+) -> anyhow::Result<()> {
+    delete_with_mode(w, names, Some(mode)).await
+}
+
+/// Removes tags based on their ids with an optional removal mode.
+async fn delete_with_mode(
+    w: &mut AppWorld,
+    names: String,
+    mode: Option<String>,
+) -> anyhow::Result<()> {
+    let names: Vec<&str> = names.split(",").map(|name| name.trim()).collect();
     let result = w
         .app
         .db()
@@ -131,14 +212,16 @@ async fn delete(w: &mut AppWorld, names: String) -> anyhow::Result<()> {
         })
         .collect();
 
-    let response = w
+    let mut query = w
         .graphql(
             String::from("removeTagsById"),
             include_str!("../graphql/tag/remove.graphql"),
         )
-        .add_variable("ids", ids.into())
-        .execute()
-        .await;
+        .add_variable("ids", ids.into());
+    if let Some(mode) = mode {
+        query = query.add_variable("mode", mode.into());
+    }
+    let response = query.execute().await;
 
     w.save_last_response(response);
     Ok(())
@@ -174,11 +257,7 @@ async fn is_not_in_database(w: &mut AppWorld, name: String) -> anyhow::Result<()
 
 #[then(expr = "the tags with names {string} are not in the database")]
 async fn are_not_in_database(w: &mut AppWorld, names: String) -> anyhow::Result<()> {
-    let names: Vec<&str> = names
-        .split(",")
-        .into_iter()
-        .map(|name| name.trim())
-        .collect();
+    let names: Vec<&str> = names.split(",").map(|name| name.trim()).collect();
     let cnt = w
         .app
         .db()
@@ -210,7 +289,7 @@ async fn tag_by_id_has_value(w: &mut AppWorld, field: String, value: String) ->
     {
         assert_eq!(tag.get_str(&field), Ok(value.as_str()));
     } else {
-        assert!(false, "tag with id {tag_id} not found");
+        panic!("tag with id {tag_id} not found");
     }
 
     Ok(())
@@ -0,0 +1,150 @@
+use crate::common::AppWorld;
+use axum::http::header::HOST;
+use axum::http::Request;
+use cucumber::then;
+use cucumber::when;
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::trace::SpanData;
+use opentelemetry_sdk::trace::SpanExporter;
+use qgt_telemetry::config::TelemetryConfig;
+use qgt_telemetry::mongodb::MongoDbTracingLayer;
+use qgt_telemetry::OtlpExporter;
+use serde_json::json;
+use serde_json::Value;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+use tracing::instrument::WithSubscriber;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::Layer;
+
+/// A span exporter, which keeps the exported spans in memory.
+#[derive(Clone, Debug, Default)]
+struct MemoryExporter(Arc<Mutex<Vec<SpanData>>>);
+
+impl SpanExporter for MemoryExporter {
+    fn export(
+        &mut self,
+        batch: Vec<SpanData>,
+    ) -> Pin<Box<dyn Future<Output = OTelSdkResult> + Send + 'static>> {
+        self.0
+            .lock()
+            .expect("the exported spans should not be poisoned")
+            .extend(batch);
+        Box::pin(std::future::ready(Ok(())))
+    }
+}
+
+/// Request the todos with REST and the optional `traceparent` header, while the spans are
+/// exported like by the server.
+///
+/// The exported spans are stored with the `exported-spans` key in the world state.
+async fn request_todos_traced(w: &mut AppWorld, traceparent: Option<&str>) -> anyhow::Result<()> {
+    let memory = MemoryExporter::default();
+    let exporter = OtlpExporter::with_span_exporter(&TelemetryConfig::default(), memory.clone());
+    let subscriber = tracing_subscriber::registry()
+        .with(exporter.layer().with_filter(OtlpExporter::filter()))
+        .with(
+            exporter
+                .mongodb_layer()
+                .with_filter(MongoDbTracingLayer::filter()),
+        );
+
+    let mut request = Request::get("/todos").header(HOST, "localhost:3000");
+    if let Some(traceparent) = traceparent {
+        request = request.header("traceparent", traceparent);
+    }
+    w.http(request.body(axum::body::Body::empty())?)
+        .with_subscriber(subscriber)
+        .await;
+    exporter.shutdown();
+
+    let spans: Vec<Value> = memory
+        .0
+        .lock()
+        .expect("the exported spans should not be poisoned")
+        .iter()
+        .map(|span| {
+            json!({
+                "name": span.name,
+                "trace_id": span.span_context.trace_id().to_string(),
+                "span_id": span.span_context.span_id().to_string(),
+                "parent_span_id": span.parent_span_id.to_string(),
+            })
+        })
+        .collect();
+    w.state.insert("exported-spans", Value::Array(spans));
+    Ok(())
+}
+
+/// Get the exported spans of the world state.
+fn exported_spans(w: &AppWorld) -> &[Value] {
+    w.state
+        .get("exported-spans")
+        .and_then(Value::as_array)
+        .expect("the spans should have been exported")
+}
+
+/// Get the exported span with the `name`.
+fn exported_span<'a>(w: &'a AppWorld, name: &str) -> &'a Value {
+    exported_spans(w)
+        .iter()
+        .find(|span| span["name"] == name)
+        .unwrap_or_else(|| panic!("the span '{name}' should be exported"))
+}
+
+#[when("the todos are requested with REST while the spans are exported")]
+async fn todos_requested_traced(w: &mut AppWorld) -> anyhow::Result<()> {
+    request_todos_traced(w, None).await
+}
+
+#[when(
+    expr = "the todos are requested with REST with the traceparent {string} while the spans are exported"
+)]
+async fn todos_requested_traced_with_parent(
+    w: &mut AppWorld,
+    traceparent: String,
+) -> anyhow::Result<()> {
+    request_todos_traced(w, Some(&traceparent)).await
+}
+
+#[then(expr = "the span {string} is exported within the span {string}")]
+fn span_exported_within(w: &mut AppWorld, name: String, ancestor: String) {
+    let spans = exported_spans(w);
+    let ancestor_id = &exported_span(w, &ancestor)["span_id"];
+    let within = spans
+        .iter()
+        .filter(|span| span["name"] == name.as_str())
+        .any(|span| {
+            let mut parent = &span["parent_span_id"];
+            while parent != ancestor_id {
+                match spans.iter().find(|span| &span["span_id"] == parent) {
+                    Some(span) => parent = &span["parent_span_id"],
+                    None => return false,
+                }
+            }
+            true
+        });
+    assert!(
+        within,
+        "the span '{name}' should be exported within the span '{ancestor}', but the spans are {spans:#?}"
+    );
+}
+
+#[then(expr = "all exported spans belong to the trace {string}")]
+fn spans_belong_to_trace(w: &mut AppWorld, trace_id: String) {
+    let spans = exported_spans(w);
+    assert!(!spans.is_empty(), "spans should be exported");
+    for span in spans {
+        assert_eq!(span["trace_id"], trace_id.as_str(), "{span:#?}");
+    }
+}
+
+#[then(expr = "the span {string} has the parent span id {string}")]
+fn span_has_parent(w: &mut AppWorld, name: String, parent_span_id: String) {
+    assert_eq!(
+        exported_span(w, &name)["parent_span_id"],
+        parent_span_id.as_str()
+    );
+}
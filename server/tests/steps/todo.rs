@@ -1,14 +1,18 @@
 use crate::common::AppWorld;
 use crate::common::CustomBool;
+use async_graphql::connection::CursorType;
+use async_graphql::connection::OpaqueCursor;
 use async_graphql::futures_util::TryStreamExt;
 use bson::doc;
 use bson::oid::ObjectId;
+use bson::Bson;
 use bson::DateTime;
 use bson::Document;
 use cucumber::gherkin::Step;
 use cucumber::given;
 use cucumber::then;
 use cucumber::when;
+use qgt_domain::db::collections::TAGS;
 use qgt_domain::db::collections::TODOS;
 use std::str::FromStr;
 
@@ -41,6 +45,209 @@ async fn given_todo(w: &mut AppWorld, title: String) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Inserts a todo with a title, which is not a string, so it is no valid todo document.
+///
+/// With change streams, the change of the todo can not be converted to a domain event.
+#[given(expr = "a todo with an invalid title is inserted into the database")]
+async fn given_invalid_todo(w: &mut AppWorld) -> anyhow::Result<()> {
+    w.app
+        .db()
+        .get()
+        .collection::<Document>(TODOS)
+        .insert_one(doc! { "completed": false, "created": DateTime::now(), "order": 1, "title": 1 })
+        .await?;
+    Ok(())
+}
+
+/// Creates todos with requested titles.
+///
+/// The todos are ordered as listed, starting with order `1`.
+#[given(expr = "the todos with titles {string} exist")]
+async fn given_todos(w: &mut AppWorld, titles: String) -> anyhow::Result<()> {
+    let docs: Vec<Document> = titles
+        .split(",")
+        .map(|title| title.trim())
+        .zip(1_i64..)
+        .map(|(title, order)| {
+            doc! { "completed": false, "created": DateTime::now(), "order": order, "title": title }
+        })
+        .collect();
+    w.app
+        .db()
+        .get()
+        .collection::<Document>(TODOS)
+        .insert_many(docs)
+        .await?;
+
+    Ok(())
+}
+
+/// Creates todos with requested titles owned by the user with requested subject.
+///
+/// The todos are ordered as listed, starting with order `1`.
+#[given(expr = "the todos with titles {string} exist for the user {string}")]
+async fn given_owned_todos(w: &mut AppWorld, titles: String, owner: String) -> anyhow::Result<()> {
+    let docs: Vec<Document> = titles
+        .split(",")
+        .map(|title| title.trim())
+        .zip(1_i64..)
+        .map(|(title, order)| {
+            doc! {
+                "completed": false,
+                "created": DateTime::now(),
+                "order": order,
+                "owner": &owner,
+                "title": title,
+            }
+        })
+        .collect();
+    w.app
+        .db()
+        .get()
+        .collection::<Document>(TODOS)
+        .insert_many(docs)
+        .await?;
+
+    Ok(())
+}
+
+/// Marks the todo with requested title as completed.
+#[given(expr = "the todo with title {string} is completed")]
+async fn given_completed(w: &mut AppWorld, title: String) -> anyhow::Result<()> {
+    let result = w
+        .app
+        .db()
+        .get()
+        .collection::<Document>(TODOS)
+        .update_one(
+            doc! { "title": &title },
+            doc! { "$set": { "completed": true, "modified": DateTime::now() } },
+        )
+        .await?;
+
+    assert_eq!(
+        result.matched_count, 1,
+        "a todo with title '{title}' should exist"
+    );
+    Ok(())
+}
+
+/// Adds the tag with requested name to the todo with requested title.
+#[given(expr = "the todo with title {string} has the tag {string}")]
+async fn given_todo_tag(w: &mut AppWorld, title: String, name: String) -> anyhow::Result<()> {
+    let tag = w
+        .app
+        .db()
+        .get()
+        .collection::<Document>(TAGS)
+        .find_one(doc! { "name": &name })
+        .await?
+        .unwrap_or_else(|| panic!("a tag with name '{name}' should exist"));
+    let result = w
+        .app
+        .db()
+        .get()
+        .collection::<Document>(TODOS)
+        .update_one(
+            doc! { "title": &title },
+            doc! { "$push": { "tags": tag.get_object_id("_id")? } },
+        )
+        .await?;
+
+    assert_eq!(
+        result.matched_count, 1,
+        "a todo with title '{title}' should exist"
+    );
+    Ok(())
+}
+
+/// Creates todos which have the tag with requested name.
+#[given(expr = "{int} todos with the tag {string} exist")]
+async fn given_tagged_todos(w: &mut AppWorld, count: i64, name: String) -> anyhow::Result<()> {
+    let tag = w
+        .app
+        .db()
+        .get()
+        .collection::<Document>(TAGS)
+        .find_one(doc! { "name": &name })
+        .await?
+        .unwrap_or_else(|| panic!("a tag with name '{name}' should exist"));
+    let tag_id = tag.get_object_id("_id")?;
+    let docs: Vec<Document> = (1..=count)
+        .map(|order| {
+            doc! {
+                "completed": false,
+                "created": DateTime::now(),
+                "order": order,
+                "tags": [tag_id],
+                "title": format!("todo {order}"),
+            }
+        })
+        .collect();
+    w.app
+        .db()
+        .get()
+        .collection::<Document>(TODOS)
+        .insert_many(docs)
+        .await?;
+
+    Ok(())
+}
+
+/// Starts the todoChanged subscription.
+#[given(expr = "the todoChanged subscription is started")]
+async fn given_todo_changed_subscription(w: &mut AppWorld) -> anyhow::Result<()> {
+    let stream = w
+        .graphql(
+            String::from("todoChanged"),
+            include_str!("../graphql/todo/todo_changed.graphql"),
+        )
+        .subscribe();
+
+    w.start_subscription(String::from("todoChanged"), stream)
+        .await;
+    Ok(())
+}
+
+/// Starts the todoChanged subscription with given filter.
+#[given(expr = "the todoChanged subscription is started with filter")]
+async fn given_filtered_todo_changed_subscription(
+    w: &mut AppWorld,
+    step: &Step,
+) -> anyhow::Result<()> {
+    let docstring = step
+        .docstring()
+        .expect("a docstring payload should be provided");
+    let filter = serde_json::Value::from_str(docstring.trim())
+        .expect("docstring should be valid and parsable JSON");
+    let stream = w
+        .graphql(
+            String::from("todoChanged"),
+            include_str!("../graphql/todo/todo_changed.graphql"),
+        )
+        .add_variable("filter", filter)
+        .subscribe();
+
+    w.start_subscription(String::from("todoChanged"), stream)
+        .await;
+    Ok(())
+}
+
+/// Starts the todoRemoved subscription.
+#[given(expr = "the todoRemoved subscription is started")]
+async fn given_todo_removed_subscription(w: &mut AppWorld) -> anyhow::Result<()> {
+    let stream = w
+        .graphql(
+            String::from("todoRemoved"),
+            include_str!("../graphql/todo/todo_removed.graphql"),
+        )
+        .subscribe();
+
+    w.start_subscription(String::from("todoRemoved"), stream)
+        .await;
+    Ok(())
+}
+
 /// Creates a todo with given payload.
 ///
 /// Stores the response as world data.
@@ -64,6 +271,129 @@ async fn create(w: &mut AppWorld, step: &Step) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Requests todos with given variables.
+///
+/// Stores the response as world data.
+#[when(expr = "todos is sent with body")]
+async fn todos(w: &mut AppWorld, step: &Step) -> anyhow::Result<()> {
+    let docstring = step
+        .docstring()
+        .expect("a docstring payload should be provided");
+    let variables = serde_json::Value::from_str(docstring.trim())
+        .expect("docstring should be valid and parsable JSON");
+    let response = w
+        .graphql(
+            String::from("todos"),
+            include_str!("../graphql/todo/todos.graphql"),
+        )
+        .variables(variables)
+        .execute()
+        .await;
+
+    w.save_last_response(response);
+    Ok(())
+}
+
+/// Requests all todos with their tags and the todo count of the tags.
+///
+/// Stores the response as world data and the count of database queries of the request with
+/// `database-query-count` key in the world state.
+#[when(expr = "todos with tags are requested")]
+async fn todos_with_tags(w: &mut AppWorld) -> anyhow::Result<()> {
+    let before = w.database_query_count().await;
+    let response = w
+        .graphql(
+            String::from("todos"),
+            include_str!("../graphql/todo/todos_with_tags.graphql"),
+        )
+        .execute()
+        .await;
+    let after = w.database_query_count().await;
+
+    w.state.insert(
+        "database-query-count",
+        serde_json::Value::from(after - before),
+    );
+    w.save_last_response(response);
+    Ok(())
+}
+
+/// Requests a page of todos with given variables.
+///
+/// Stores the response as world data.
+#[when(expr = "todosConnection is sent with body")]
+async fn connection(w: &mut AppWorld, step: &Step) -> anyhow::Result<()> {
+    let docstring = step
+        .docstring()
+        .expect("a docstring payload should be provided");
+    let variables = serde_json::Value::from_str(docstring.trim())
+        .expect("docstring should be valid and parsable JSON");
+
+    let response = w
+        .graphql(
+            String::from("todosConnection"),
+            include_str!("../graphql/todo/connection.graphql"),
+        )
+        .variables(variables)
+        .execute()
+        .await;
+
+    w.save_last_response(response);
+    Ok(())
+}
+
+/// Requests the page of todos after the end cursor of the last response.
+///
+/// Stores the response as world data.
+#[when(expr = "the next page of todosConnection is requested with first {int}")]
+async fn connection_next(w: &mut AppWorld, first: i64) -> anyhow::Result<()> {
+    let end_cursor = w.get_last_response_data()["pageInfo"]["endCursor"].clone();
+    assert!(
+        end_cursor.is_string(),
+        "the last response should have an end cursor"
+    );
+
+    let response = w
+        .graphql(
+            String::from("todosConnection"),
+            include_str!("../graphql/todo/connection.graphql"),
+        )
+        .add_variable("after", end_cursor)
+        .add_variable("first", first.into())
+        .execute()
+        .await;
+
+    w.save_last_response(response);
+    Ok(())
+}
+
+/// Requests a page of todos after a cursor with the given keyset values, like a forged cursor.
+///
+/// Stores the response as world data.
+#[when(expr = "todosConnection is sent after a cursor with the values")]
+async fn connection_after_values(w: &mut AppWorld, step: &Step) -> anyhow::Result<()> {
+    let docstring = step
+        .docstring()
+        .expect("a docstring payload should be provided");
+    let values: Vec<Bson> = serde_json::from_str::<Vec<serde_json::Value>>(docstring.trim())
+        .expect("docstring should be a valid and parsable JSON array")
+        .into_iter()
+        .map(Bson::try_from)
+        .collect::<Result<_, _>>()?;
+
+    let response = w
+        .graphql(
+            String::from("todosConnection"),
+            include_str!("../graphql/todo/connection.graphql"),
+        )
+        .add_variable("after", OpaqueCursor(values).encode_cursor().into())
+        .execute()
+        .await;
+
+    w.save_last_response(response);
+    Ok(())
+}
+
 // Updates a todo with given payload.
 ///
 /// Stores the response as world data.
@@ -100,17 +430,46 @@ async fn update(w: &mut AppWorld, step: &Step) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Updates a non-existing todo with given payload.
+///
+/// Stores the response as world data.
+#[when(expr = "updateTodo is sent with body for a non-existing todo")]
+async fn update_non_existing(w: &mut AppWorld, step: &Step) -> anyhow::Result<()> {
+    let docstring = step
+        .docstring()
+        .expect("a docstring payload should be provided");
+    let mut payload = serde_json::Value::from_str(docstring.trim())
+        .expect("docstring should be valid and parsable JSON");
+
+    // Replace id with a new id, which does not exist in the database
+    payload
+        .as_object_mut()
+        .expect("payload should be an object")
+        .insert(
+            String::from("id"),
+            serde_json::Value::from(ObjectId::new().to_hex()),
+        );
+
+    let response = w
+        .graphql(
+            String::from("updateTodo"),
+            include_str!("../graphql/todo/update.graphql"),
+        )
+        .add_variable("input", payload)
+        .execute()
+        .await;
+
+    w.save_last_response(response);
+    Ok(())
+}
+
 /// Removes todos based on their ids.
 /// Retrieves the ids based on provided titles.
 ///
 /// Stores the response as world data.
 #[when(expr = "removeTodos is sent with ids for {string}")]
 async fn delete(w: &mut AppWorld, titles: String) -> anyhow::Result<()> {
-    let titles: Vec<&str> = titles
-        .split(",")
-        .into_iter()
-        .map(|title| title.trim())
-        .collect();
+    let titles: Vec<&str> = titles.split(",").map(|title| title.trim()).collect();
     let result = w
         .app
         .db()
@@ -146,6 +505,113 @@ async fn delete(w: &mut AppWorld, titles: String) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Moves a todo before or after another todo.
+/// Retrieves the ids based on provided titles.
+///
+/// Stores the response as world data.
+#[when(expr = "moveTodo is sent to move {string} {word} {string}")]
+async fn move_todo(
+    w: &mut AppWorld,
+    title: String,
+    direction: String,
+    anchor: String,
+) -> anyhow::Result<()> {
+    let mut ids = vec![];
+    for title in [&title, &anchor] {
+        let todo = w
+            .app
+            .db()
+            .get()
+            .collection::<Document>(TODOS)
+            .find_one(doc! { "title": title })
+            .await?
+            .unwrap_or_else(|| panic!("a todo with title '{title}' should exist"));
+        ids.push(todo.get_object_id("_id")?.to_hex());
+    }
+
+    let response = w
+        .graphql(
+            String::from("moveTodo"),
+            include_str!("../graphql/todo/move.graphql"),
+        )
+        .add_variable("id", ids[0].clone().into())
+        .add_variable("position", serde_json::json!({ direction: ids[1] }))
+        .execute()
+        .await;
+
+    w.save_last_response(response);
+    Ok(())
+}
+
+/// Sets the completion state of all todos.
+///
+/// Stores the response as world data.
+#[when(expr = "toggleAllTodos is sent with completed {bool}")]
+async fn toggle_all(w: &mut AppWorld, completed: CustomBool) -> anyhow::Result<()> {
+    let response = w
+        .graphql(
+            String::from("toggleAllTodos"),
+            include_str!("../graphql/todo/toggle_all.graphql"),
+        )
+        .add_variable("completed", (*completed).into())
+        .execute()
+        .await;
+
+    w.save_last_response(response);
+    Ok(())
+}
+
+/// Removes all completed todos.
+///
+/// Stores the response as world data.
+#[when(expr = "clearCompletedTodos is sent")]
+async fn clear_completed(w: &mut AppWorld) -> anyhow::Result<()> {
+    let response = w
+        .graphql(
+            String::from("clearCompletedTodos"),
+            include_str!("../graphql/todo/clear_completed.graphql"),
+        )
+        .execute()
+        .await;
+
+    w.save_last_response(response);
+    Ok(())
+}
+
+#[then(expr = "{int} todos with completed {bool} are in the database")]
+async fn count_completed_in_database(
+    w: &mut AppWorld,
+    expected: u64,
+    completed: CustomBool,
+) -> anyhow::Result<()> {
+    let cnt = w
+        .app
+        .db()
+        .get()
+        .collection::<Document>(TODOS)
+        .count_documents(doc! { "completed": *completed })
+        .await?;
+
+    assert_eq!(
+        cnt, expected,
+        "unexpected todo count '{cnt}' with completed '{}'",
+        *completed
+    );
+    Ok(())
+}
+
+#[then(expr = "the request sent {int} queries to the database")]
+async fn database_query_count(w: &mut AppWorld, expected: u64) -> anyhow::Result<()> {
+    let cnt = w
+        .state
+        .get("database-query-count")
+        .and_then(|cnt| cnt.as_u64())
+        .expect("world state should have 'database-query-count'");
+
+    assert_eq!(cnt, expected, "unexpected database query count '{cnt}'");
+    Ok(())
+}
+
 #[then(expr = "the todo with title {string} is in the database")]
 async fn is_in_database(w: &mut AppWorld, title: String) -> anyhow::Result<()> {
     let cnt = w
@@ -160,13 +626,32 @@ async fn is_in_database(w: &mut AppWorld, title: String) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[then(expr = "the todo with title {string} has {int} tags in the database")]
+async fn tag_count_in_database(
+    w: &mut AppWorld,
+    title: String,
+    expected: usize,
+) -> anyhow::Result<()> {
+    let todo = w
+        .app
+        .db()
+        .get()
+        .collection::<Document>(TODOS)
+        .find_one(doc! { "title": &title })
+        .await?
+        .unwrap_or_else(|| panic!("a todo with title '{title}' should exist"));
+    let cnt = todo.get_array("tags").map(|tags| tags.len()).unwrap_or(0);
+
+    assert_eq!(
+        cnt, expected,
+        "unexpected tag count '{cnt}' for todo with title '{title}'"
+    );
+    Ok(())
+}
+
 #[then(expr = "the todos with titles {string} are not in the database")]
 async fn are_not_in_database(w: &mut AppWorld, titles: String) -> anyhow::Result<()> {
-    let titles: Vec<&str> = titles
-        .split(",")
-        .into_iter()
-        .map(|title| title.trim())
-        .collect();
+    let titles: Vec<&str> = titles.split(",").map(|title| title.trim()).collect();
     let cnt = w
         .app
         .db()
@@ -202,7 +687,7 @@ async fn todo_by_id_has_string_value(
     {
         assert_eq!(todo.get_str(&field), Ok(value.as_str()));
     } else {
-        assert!(false, "todo with id {todo_id} not found");
+        panic!("todo with id {todo_id} not found");
     }
 
     Ok(())
@@ -228,7 +713,7 @@ async fn todo_by_id_has_boolean_value(
     {
         assert_eq!(todo.get_bool(&field), Ok(*value));
     } else {
-        assert!(false, "todo with id {todo_id} not found");
+        panic!("todo with id {todo_id} not found");
     }
 
     Ok(())
@@ -6,3 +6,45 @@ pub fn has_tag_condition(tags: &[String], check_tags: &[String], include: bool)
         !tags.iter().any(|t| check_tags.contains(t))
     }
 }
+
+/// The private key which signs the tokens of the tests.
+///
+/// The public key is in `fixtures/jwks.json`.
+const SIGNING_KEY: &[u8] = include_bytes!("fixtures/signing_key.pem");
+
+/// The key id of the [SIGNING_KEY] in `fixtures/jwks.json`.
+const SIGNING_KEY_ID: &str = "qgt-test";
+
+/// Mint a token for the `subject` with the realm `roles`, issued by `issuer` for `audience`.
+///
+/// The token expires after `expires_in` seconds, which is negative for an expired token.
+pub fn mint_token(
+    issuer: &str,
+    audience: &str,
+    subject: &str,
+    roles: &[&str],
+    expires_in: i64,
+) -> String {
+    let mut header = jsonwebtoken::Header::new(jsonwebtoken::Algorithm::EdDSA);
+    header.kid = Some(String::from(SIGNING_KEY_ID));
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("the system time should be after the Unix epoch")
+        .as_secs() as i64;
+    let claims = serde_json::json!({
+        "iss": issuer,
+        "aud": audience,
+        "sub": subject,
+        "realm_access": { "roles": roles },
+        "iat": now,
+        "exp": now + expires_in,
+    });
+
+    jsonwebtoken::encode(
+        &header,
+        &claims,
+        &jsonwebtoken::EncodingKey::from_ed_pem(SIGNING_KEY)
+            .expect("the test signing key should be valid"),
+    )
+    .expect("the token should be encodable")
+}